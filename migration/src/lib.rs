@@ -2,6 +2,19 @@ pub use sea_orm_migration::prelude::*;
 
 mod m20260223_000001_create_mqtt_users_table;
 mod m20260302_000001_drop_is_deleted_column;
+mod m20261019_000001_add_validity_window_to_mqtt_users;
+mod m20261019_000002_add_previous_password_to_mqtt_users;
+mod m20261019_000003_add_metadata_to_mqtt_users;
+mod m20261019_000004_create_mqtt_provision_batches_table;
+mod m20261019_000005_create_mqtt_roles_and_acl_rules_tables;
+mod m20261019_000006_create_mqtt_user_tags_table;
+mod m20261019_000007_add_audit_timestamps_to_mqtt_users;
+mod m20261019_000008_add_deleted_at_to_mqtt_users;
+mod m20261019_000009_create_mqtt_tenants_table;
+mod m20261019_000010_add_quotas_to_mqtt_tenants;
+mod m20261019_000011_create_mqtt_api_keys_table;
+mod m20261019_000012_create_mqtt_audit_events_table;
+mod m20261019_000013_create_mqtt_user_client_ids_table;
 
 pub struct Migrator;
 
@@ -11,6 +24,19 @@ impl MigratorTrait for Migrator {
         vec![
             Box::new(m20260223_000001_create_mqtt_users_table::Migration),
             Box::new(m20260302_000001_drop_is_deleted_column::Migration),
+            Box::new(m20261019_000001_add_validity_window_to_mqtt_users::Migration),
+            Box::new(m20261019_000002_add_previous_password_to_mqtt_users::Migration),
+            Box::new(m20261019_000003_add_metadata_to_mqtt_users::Migration),
+            Box::new(m20261019_000004_create_mqtt_provision_batches_table::Migration),
+            Box::new(m20261019_000005_create_mqtt_roles_and_acl_rules_tables::Migration),
+            Box::new(m20261019_000006_create_mqtt_user_tags_table::Migration),
+            Box::new(m20261019_000007_add_audit_timestamps_to_mqtt_users::Migration),
+            Box::new(m20261019_000008_add_deleted_at_to_mqtt_users::Migration),
+            Box::new(m20261019_000009_create_mqtt_tenants_table::Migration),
+            Box::new(m20261019_000010_add_quotas_to_mqtt_tenants::Migration),
+            Box::new(m20261019_000011_create_mqtt_api_keys_table::Migration),
+            Box::new(m20261019_000012_create_mqtt_audit_events_table::Migration),
+            Box::new(m20261019_000013_create_mqtt_user_client_ids_table::Migration),
         ]
     }
 }
@@ -0,0 +1,73 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .add_column(
+                        ColumnDef::new(MqttUsers::IsEnabled)
+                            .boolean()
+                            .not_null()
+                            .default(true),
+                    )
+                    .add_column(
+                        ColumnDef::new(MqttUsers::ValidFrom)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .add_column(
+                        ColumnDef::new(MqttUsers::ValidUntil)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_users_valid_until")
+                    .table(MqttUsers::Table)
+                    .col(MqttUsers::ValidUntil)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_mqtt_users_valid_until")
+                    .table(MqttUsers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .drop_column(MqttUsers::ValidUntil)
+                    .drop_column(MqttUsers::ValidFrom)
+                    .drop_column(MqttUsers::IsEnabled)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    IsEnabled,
+    ValidFrom,
+    ValidUntil,
+}
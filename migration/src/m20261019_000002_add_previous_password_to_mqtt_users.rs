@@ -0,0 +1,42 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .add_column(ColumnDef::new(MqttUsers::PreviousPassword).string().null())
+                    .add_column(
+                        ColumnDef::new(MqttUsers::PreviousPasswordExpiresAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .drop_column(MqttUsers::PreviousPasswordExpiresAt)
+                    .drop_column(MqttUsers::PreviousPassword)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    PreviousPassword,
+    PreviousPasswordExpiresAt,
+}
@@ -0,0 +1,106 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttProvisionBatches::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttProvisionBatches::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttProvisionBatches::BatchId)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(ColumnDef::new(MqttProvisionBatches::Spec).json().not_null())
+                    .col(
+                        ColumnDef::new(MqttProvisionBatches::UserCount)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttProvisionBatches::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .add_column(
+                        ColumnDef::new(MqttUsers::ProvisionBatchId)
+                            .string()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_users_provision_batch_id")
+                    .table(MqttUsers::Table)
+                    .col(MqttUsers::ProvisionBatchId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_mqtt_users_provision_batch_id")
+                    .table(MqttUsers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .drop_column(MqttUsers::ProvisionBatchId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(MqttProvisionBatches::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttProvisionBatches {
+    Table,
+    Id,
+    BatchId,
+    Spec,
+    UserCount,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    ProvisionBatchId,
+}
@@ -0,0 +1,153 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttRoles::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttRoles::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttRoles::Name)
+                            .string()
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttUserRoles::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MqttUserRoles::UserId).integer().not_null())
+                    .col(ColumnDef::new(MqttUserRoles::RoleId).integer().not_null())
+                    .col(
+                        ColumnDef::new(MqttUserRoles::Priority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MqttUserRoles::UserId)
+                            .col(MqttUserRoles::RoleId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_user_roles_user_id")
+                            .from(MqttUserRoles::Table, MqttUserRoles::UserId)
+                            .to(MqttUsers::Table, MqttUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_user_roles_role_id")
+                            .from(MqttUserRoles::Table, MqttUserRoles::RoleId)
+                            .to(MqttRoles::Table, MqttRoles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttAclRules::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttAclRules::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(MqttAclRules::UserId).integer().null())
+                    .col(ColumnDef::new(MqttAclRules::RoleId).integer().null())
+                    .col(ColumnDef::new(MqttAclRules::Permission).string().not_null())
+                    .col(ColumnDef::new(MqttAclRules::Action).string().not_null())
+                    .col(ColumnDef::new(MqttAclRules::Topic).string().not_null())
+                    .col(
+                        ColumnDef::new(MqttAclRules::Priority)
+                            .integer()
+                            .not_null()
+                            .default(0),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_acl_rules_user_id")
+                            .from(MqttAclRules::Table, MqttAclRules::UserId)
+                            .to(MqttUsers::Table, MqttUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_acl_rules_role_id")
+                            .from(MqttAclRules::Table, MqttAclRules::RoleId)
+                            .to(MqttRoles::Table, MqttRoles::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MqttAclRules::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(MqttUserRoles::Table).to_owned())
+            .await?;
+        manager
+            .drop_table(Table::drop().table(MqttRoles::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttRoles {
+    Table,
+    Id,
+    Name,
+}
+
+#[derive(DeriveIden)]
+enum MqttUserRoles {
+    Table,
+    UserId,
+    RoleId,
+    Priority,
+}
+
+#[derive(DeriveIden)]
+enum MqttAclRules {
+    Table,
+    Id,
+    UserId,
+    RoleId,
+    Permission,
+    Action,
+    Topic,
+    Priority,
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    Id,
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttUserTags::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(MqttUserTags::UserId).integer().not_null())
+                    .col(ColumnDef::new(MqttUserTags::Tag).string_len(64).not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(MqttUserTags::UserId)
+                            .col(MqttUserTags::Tag),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_user_tags_user_id")
+                            .from(MqttUserTags::Table, MqttUserTags::UserId)
+                            .to(MqttUsers::Table, MqttUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_user_tags_tag")
+                    .table(MqttUserTags::Table)
+                    .col(MqttUserTags::Tag)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MqttUserTags::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttUserTags {
+    Table,
+    UserId,
+    Tag,
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    Id,
+}
@@ -0,0 +1,78 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // Existing rows get the migration time as `created_at`; their real creation time is unknown.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .add_column(
+                        ColumnDef::new(MqttUsers::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .add_column(
+                        ColumnDef::new(MqttUsers::UpdatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .add_column(
+                        ColumnDef::new(MqttUsers::LastLoginAt)
+                            .timestamp_with_time_zone()
+                            .null(),
+                    )
+                    .add_column(ColumnDef::new(MqttUsers::LastLoginIp).string_len(45).null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_users_last_login_at")
+                    .table(MqttUsers::Table)
+                    .col(MqttUsers::LastLoginAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_mqtt_users_last_login_at")
+                    .table(MqttUsers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttUsers::Table)
+                    .drop_column(MqttUsers::LastLoginIp)
+                    .drop_column(MqttUsers::LastLoginAt)
+                    .drop_column(MqttUsers::UpdatedAt)
+                    .drop_column(MqttUsers::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    CreatedAt,
+    UpdatedAt,
+    LastLoginAt,
+    LastLoginIp,
+}
@@ -0,0 +1,240 @@
+use sea_orm_migration::prelude::*;
+use sea_orm_migration::sea_orm::{ConnectionTrait, DbBackend};
+
+/// Id of the tenant seeded by this migration; existing users, roles and rules belong to it.
+const DEFAULT_TENANT_ID: i32 = 1;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttTenants::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttTenants::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttTenants::Name)
+                            .string_len(64)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttTenants::TopicPrefix)
+                            .string_len(128)
+                            .not_null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttTenants::ApiKeyHash)
+                            .string_len(64)
+                            .null()
+                            .unique_key(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttTenants::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // The first row of a fresh table gets id 1 on both MySQL and Postgres.
+        manager
+            .exec_stmt(
+                Query::insert()
+                    .into_table(MqttTenants::Table)
+                    .columns([MqttTenants::Name, MqttTenants::TopicPrefix])
+                    .values_panic(["default".into(), "".into()])
+                    .to_owned(),
+            )
+            .await?;
+
+        add_tenant_column(manager, MqttUsers::Table, "fk_mqtt_users_tenant_id").await?;
+        add_tenant_column(manager, MqttRoles::Table, "fk_mqtt_roles_tenant_id").await?;
+        add_tenant_column(manager, MqttAclRules::Table, "fk_mqtt_acl_rules_tenant_id").await?;
+
+        // Usernames and role names become unique per tenant instead of globally.
+        drop_column_unique(manager, "mqtt_users", "username").await?;
+        drop_column_unique(manager, "mqtt_roles", "name").await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_users_tenant_username")
+                    .table(MqttUsers::Table)
+                    .col(MqttUsers::TenantId)
+                    .col(MqttUsers::Username)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_roles_tenant_name")
+                    .table(MqttRoles::Table)
+                    .col(MqttRoles::TenantId)
+                    .col(MqttRoles::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_mqtt_roles_tenant_name")
+                    .table(MqttRoles::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_index(
+                Index::drop()
+                    .name("idx_mqtt_users_tenant_username")
+                    .table(MqttUsers::Table)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("name")
+                    .table(MqttRoles::Table)
+                    .col(MqttRoles::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("username")
+                    .table(MqttUsers::Table)
+                    .col(MqttUsers::Username)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        drop_tenant_column(manager, MqttAclRules::Table, "fk_mqtt_acl_rules_tenant_id").await?;
+        drop_tenant_column(manager, MqttRoles::Table, "fk_mqtt_roles_tenant_id").await?;
+        drop_tenant_column(manager, MqttUsers::Table, "fk_mqtt_users_tenant_id").await?;
+
+        manager
+            .drop_table(Table::drop().table(MqttTenants::Table).to_owned())
+            .await
+    }
+}
+
+async fn add_tenant_column<T: Iden + Copy + 'static>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    foreign_key: &str,
+) -> Result<(), DbErr> {
+    manager
+        .alter_table(
+            Table::alter()
+                .table(table)
+                .add_column(
+                    ColumnDef::new(TenantId)
+                        .integer()
+                        .not_null()
+                        .default(DEFAULT_TENANT_ID),
+                )
+                .to_owned(),
+        )
+        .await?;
+
+    manager
+        .create_foreign_key(
+            ForeignKey::create()
+                .name(foreign_key)
+                .from(table, TenantId)
+                .to(MqttTenants::Table, MqttTenants::Id)
+                .on_delete(ForeignKeyAction::Cascade)
+                .to_owned(),
+        )
+        .await
+}
+
+async fn drop_tenant_column<T: Iden + Copy + 'static>(
+    manager: &SchemaManager<'_>,
+    table: T,
+    foreign_key: &str,
+) -> Result<(), DbErr> {
+    manager
+        .drop_foreign_key(ForeignKey::drop().name(foreign_key).table(table).to_owned())
+        .await?;
+
+    manager
+        .alter_table(Table::alter().table(table).drop_column(TenantId).to_owned())
+        .await
+}
+
+/// Drops the constraint `unique_key()` created on a single column; its name depends on the backend.
+async fn drop_column_unique(
+    manager: &SchemaManager<'_>,
+    table: &str,
+    column: &str,
+) -> Result<(), DbErr> {
+    let sql = match manager.get_database_backend() {
+        DbBackend::Postgres => {
+            format!("ALTER TABLE \"{table}\" DROP CONSTRAINT \"{table}_{column}_key\"")
+        }
+        _ => format!("ALTER TABLE `{table}` DROP INDEX `{column}`"),
+    };
+    manager.get_connection().execute_unprepared(&sql).await?;
+    Ok(())
+}
+
+#[derive(DeriveIden)]
+enum MqttTenants {
+    Table,
+    Id,
+    Name,
+    TopicPrefix,
+    ApiKeyHash,
+    CreatedAt,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum MqttUsers {
+    Table,
+    TenantId,
+    Username,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum MqttRoles {
+    Table,
+    TenantId,
+    Name,
+}
+
+#[derive(DeriveIden, Clone, Copy)]
+enum MqttAclRules {
+    Table,
+}
+
+/// `tenant_id` column shared by the users, roles and ACL rules tables.
+#[derive(DeriveIden, Clone, Copy)]
+struct TenantId;
@@ -0,0 +1,49 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // NULL means unlimited, which keeps existing tenants unrestricted.
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttTenants::Table)
+                    .add_column(ColumnDef::new(MqttTenants::MaxUsers).integer().null())
+                    .add_column(ColumnDef::new(MqttTenants::MaxSuperusers).integer().null())
+                    .add_column(ColumnDef::new(MqttTenants::MaxAclRules).integer().null())
+                    .add_column(
+                        ColumnDef::new(MqttTenants::MaxAuthPerMinute)
+                            .integer()
+                            .null(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(MqttTenants::Table)
+                    .drop_column(MqttTenants::MaxUsers)
+                    .drop_column(MqttTenants::MaxSuperusers)
+                    .drop_column(MqttTenants::MaxAclRules)
+                    .drop_column(MqttTenants::MaxAuthPerMinute)
+                    .to_owned(),
+            )
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttTenants {
+    Table,
+    MaxUsers,
+    MaxSuperusers,
+    MaxAclRules,
+    MaxAuthPerMinute,
+}
@@ -0,0 +1,113 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttAuditEvents::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Id)
+                            .big_integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    // No foreign key: events outlive the tenants and users they mention
+                    .col(ColumnDef::new(MqttAuditEvents::TenantId).integer().null())
+                    // API key name, e.g. `static:primary` or `tenant:acme`
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Actor)
+                            .string_len(128)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Action)
+                            .string_len(64)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Target)
+                            .string_len(255)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::RequestId)
+                            .string_len(64)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::SourceIp)
+                            .string_len(45)
+                            .null(),
+                    )
+                    // `{"field": {"before": ..., "after": ...}}`, secrets redacted
+                    .col(ColumnDef::new(MqttAuditEvents::Changes).json().null())
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Outcome)
+                            .string_len(16)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::Error)
+                            .string_len(255)
+                            .null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttAuditEvents::CreatedAt)
+                            .timestamp_with_time_zone()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_audit_events_target")
+                    .table(MqttAuditEvents::Table)
+                    .col(MqttAuditEvents::Target)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx_mqtt_audit_events_created_at")
+                    .table(MqttAuditEvents::Table)
+                    .col(MqttAuditEvents::CreatedAt)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MqttAuditEvents::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttAuditEvents {
+    Table,
+    Id,
+    TenantId,
+    Actor,
+    Action,
+    Target,
+    RequestId,
+    SourceIp,
+    Changes,
+    Outcome,
+    Error,
+    CreatedAt,
+}
@@ -0,0 +1,59 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(MqttUserClientIds::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(MqttUserClientIds::UserId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(MqttUserClientIds::ClientId)
+                            .string_len(128)
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(MqttUserClientIds::UserId)
+                            .col(MqttUserClientIds::ClientId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk_mqtt_user_client_ids_user_id")
+                            .from(MqttUserClientIds::Table, MqttUserClientIds::UserId)
+                            .to(MqttUsers::Table, MqttUsers::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(MqttUserClientIds::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum MqttUserClientIds {
+    Table,
+    UserId,
+    ClientId,
+}
+
+#[derive(DeriveIden)]
+enum MqttUsers {
+    Table,
+    Id,
+}
@@ -0,0 +1,109 @@
+use std::io::Write;
+use std::sync::Arc;
+
+use crate::dtos::mqtt_dto::ExportFormat;
+use crate::infrastructure::database::{DbConfig, close_db};
+use crate::repositories::audit_event_repository::AuditEventRepository;
+use crate::repositories::export_mqtt_repository::ExportMqttRepository;
+use crate::repositories::get_mqtt_acl_rules_repository::GetMqttAclRulesRepository;
+use crate::repositories::tenant_repository::TenantRepository;
+use crate::services::audit_service::AuditService;
+use crate::services::export_mqtt_service::{ExportMqttService, emqx_users_csv};
+use crate::services::service_error::MqttServiceError;
+use crate::services::tenant_service::{DEFAULT_TENANT, TenantService};
+
+const EXPORT_USAGE: &str = "usage: emqx_auth_service export <users|acl> [--format json|csv] [--include-inactive] [--tenant NAME] [--output FILE]";
+
+/// `export users|acl`: writes the EMQX built-in database files of one tenant to stdout or
+/// `--output`.
+pub async fn run_export(args: &[String]) -> std::io::Result<()> {
+    let invalid = |message: String| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!("{}\n{}", message, EXPORT_USAGE),
+        )
+    };
+
+    let target = args
+        .first()
+        .ok_or_else(|| invalid("missing export target".to_string()))?;
+    let mut format = ExportFormat::Json;
+    let mut include_inactive = false;
+    let mut tenant_name = DEFAULT_TENANT;
+    let mut output = None;
+    let mut flags = args[1..].iter();
+    while let Some(flag) = flags.next() {
+        match flag.as_str() {
+            "--format" => {
+                format = match flags.next().map(String::as_str) {
+                    Some("json") => ExportFormat::Json,
+                    Some("csv") => ExportFormat::Csv,
+                    other => {
+                        return Err(invalid(format!("invalid --format {}", other.unwrap_or(""))));
+                    }
+                }
+            }
+            "--include-inactive" => include_inactive = true,
+            "--tenant" => {
+                tenant_name = flags
+                    .next()
+                    .ok_or_else(|| invalid("--tenant needs a name".to_string()))?
+                    .as_str()
+            }
+            "--output" => {
+                output = Some(
+                    flags
+                        .next()
+                        .ok_or_else(|| invalid("--output needs a file".to_string()))?,
+                )
+            }
+            other => return Err(invalid(format!("unknown option {}", other))),
+        }
+    }
+
+    dotenvy::dotenv().ok();
+    env_logger::Builder::from_env(env_logger::Env::new().filter_or("LOG_LEVEL", "warn")).init();
+
+    let db_conn = DbConfig::from_env()
+        .connect()
+        .await
+        .map_err(|e| std::io::Error::other(format!("Failed to initialize database: {}", e)))?;
+    let audit = Arc::new(AuditService::new(Arc::new(AuditEventRepository::new(
+        db_conn.clone(),
+    ))));
+    let tenant = TenantService::new(Arc::new(TenantRepository::new(db_conn.clone())), audit)
+        .by_name(tenant_name)
+        .await;
+    let Some(tenant) = tenant else {
+        close_db(db_conn).await;
+        return Err(invalid(format!("unknown tenant {}", tenant_name)));
+    };
+    let service = ExportMqttService::new(
+        Arc::new(ExportMqttRepository::new(db_conn.clone())),
+        Arc::new(GetMqttAclRulesRepository::new(db_conn.clone())),
+    );
+
+    let to_json = |e: serde_json::Error| MqttServiceError::InternalError(e.to_string());
+    let exported = match target.as_str() {
+        "users" => match service.export_users(&tenant, include_inactive).await {
+            Ok(users) if format == ExportFormat::Csv => emqx_users_csv(&users),
+            Ok(users) => serde_json::to_vec_pretty(&users).map_err(to_json),
+            Err(e) => Err(e),
+        },
+        "acl" => service
+            .export_acl(&tenant, include_inactive)
+            .await
+            .and_then(|rules| serde_json::to_vec_pretty(&rules).map_err(to_json)),
+        other => {
+            close_db(db_conn).await;
+            return Err(invalid(format!("unknown export target {}", other)));
+        }
+    };
+    close_db(db_conn).await;
+    let exported = exported.map_err(|e| std::io::Error::other(e.to_string()))?;
+
+    match output {
+        Some(path) => std::fs::write(path, exported),
+        None => std::io::stdout().write_all(&exported),
+    }
+}
@@ -0,0 +1,120 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Permission an API key grants; every route requires one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+pub enum ApiScope {
+    /// `POST /mqtt/check`
+    #[serde(rename = "hook:auth")]
+    HookAuth,
+    /// `POST /mqtt/acl`
+    #[serde(rename = "hook:acl")]
+    HookAcl,
+    /// Listing, reading and exporting users
+    #[serde(rename = "users:read")]
+    UsersRead,
+    /// Creating, updating, deleting, importing and provisioning users
+    #[serde(rename = "users:write")]
+    UsersWrite,
+    /// `GET /mqtt/credentials/{username}`
+    #[serde(rename = "credentials:reveal")]
+    CredentialsReveal,
+    /// Tenant and API key management; implies every other scope
+    #[serde(rename = "admin")]
+    Admin,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 6] = [
+        ApiScope::HookAuth,
+        ApiScope::HookAcl,
+        ApiScope::UsersRead,
+        ApiScope::UsersWrite,
+        ApiScope::CredentialsReveal,
+        ApiScope::Admin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ApiScope::HookAuth => "hook:auth",
+            ApiScope::HookAcl => "hook:acl",
+            ApiScope::UsersRead => "users:read",
+            ApiScope::UsersWrite => "users:write",
+            ApiScope::CredentialsReveal => "credentials:reveal",
+            ApiScope::Admin => "admin",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s.trim())
+    }
+
+    /// Scopes stored as a comma-separated column; unknown names are dropped.
+    pub fn parse_list(s: &str) -> Vec<Self> {
+        s.split(',').filter_map(Self::parse).collect()
+    }
+
+    pub fn join(scopes: &[Self]) -> String {
+        scopes
+            .iter()
+            .map(ApiScope::as_str)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// API key a request was authorized with, stored in the request extensions by `ApiKeyMiddleware`.
+#[derive(Clone, Debug)]
+pub struct ApiKeyContext {
+    /// Key name; `static:primary` / `static:secondary-{n}` for the static keys and
+    /// `tenant:{name}` for a tenant's own key
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+}
+
+impl ApiKeyContext {
+    pub fn allows(&self, scope: ApiScope) -> bool {
+        self.scopes
+            .iter()
+            .any(|granted| *granted == scope || *granted == ApiScope::Admin)
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateApiKeyDTO {
+    /// Unique name, shown as the actor of the key's requests
+    pub name: String,
+    pub scopes: Vec<ApiScope>,
+    /// Tenant the key is limited to; omitted, the key may act on any tenant through `X-Tenant`.
+    /// Keys created with a tenant-scoped key are always limited to that tenant.
+    #[serde(default)]
+    pub tenant: Option<String>,
+    /// Omitted, the key never expires
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ApiKeyDTO {
+    pub id: i32,
+    pub name: String,
+    pub tenant: Option<String>,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct CreatedApiKeyDTO {
+    pub id: i32,
+    pub name: String,
+    pub tenant: Option<String>,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Only its hash is stored, so it is shown this once
+    pub api_key: String,
+}
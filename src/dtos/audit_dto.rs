@@ -0,0 +1,200 @@
+use actix_web::{FromRequest, HttpMessage, HttpRequest, dev::Payload};
+use chrono::{DateTime, Utc};
+use futures_util::future::{Ready, ok};
+use serde::{Deserialize, Serialize};
+
+use crate::dtos::api_key_dto::ApiKeyContext;
+use crate::dtos::mqtt_dto::PaginationDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::middleware::request_id::RequestId;
+use crate::utils::client_ip::ClientIp;
+
+/// An audited action, stored as its dotted name, e.g. `user.delete`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+pub enum AuditAction {
+    #[serde(rename = "user.create")]
+    UserCreate,
+    #[serde(rename = "user.update")]
+    UserUpdate,
+    /// Moved to the trash
+    #[serde(rename = "user.delete")]
+    UserDelete,
+    /// Removed for good (`hard=true`)
+    #[serde(rename = "user.purge")]
+    UserPurge,
+    #[serde(rename = "user.restore")]
+    UserRestore,
+    #[serde(rename = "user.rotate_password")]
+    UserRotatePassword,
+    #[serde(rename = "user.reveal_credentials")]
+    UserRevealCredentials,
+    #[serde(rename = "user.import")]
+    UserImport,
+    #[serde(rename = "user.import_mosquitto")]
+    UserImportMosquitto,
+    #[serde(rename = "user.provision")]
+    UserProvision,
+    #[serde(rename = "tenant.create")]
+    TenantCreate,
+    #[serde(rename = "tenant.rotate_key")]
+    TenantRotateKey,
+    #[serde(rename = "tenant.set_quota")]
+    TenantSetQuota,
+    #[serde(rename = "api_key.create")]
+    ApiKeyCreate,
+    #[serde(rename = "api_key.revoke")]
+    ApiKeyRevoke,
+}
+
+impl AuditAction {
+    pub const ALL: [AuditAction; 15] = [
+        AuditAction::UserCreate,
+        AuditAction::UserUpdate,
+        AuditAction::UserDelete,
+        AuditAction::UserPurge,
+        AuditAction::UserRestore,
+        AuditAction::UserRotatePassword,
+        AuditAction::UserRevealCredentials,
+        AuditAction::UserImport,
+        AuditAction::UserImportMosquitto,
+        AuditAction::UserProvision,
+        AuditAction::TenantCreate,
+        AuditAction::TenantRotateKey,
+        AuditAction::TenantSetQuota,
+        AuditAction::ApiKeyCreate,
+        AuditAction::ApiKeyRevoke,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::UserCreate => "user.create",
+            AuditAction::UserUpdate => "user.update",
+            AuditAction::UserDelete => "user.delete",
+            AuditAction::UserPurge => "user.purge",
+            AuditAction::UserRestore => "user.restore",
+            AuditAction::UserRotatePassword => "user.rotate_password",
+            AuditAction::UserRevealCredentials => "user.reveal_credentials",
+            AuditAction::UserImport => "user.import",
+            AuditAction::UserImportMosquitto => "user.import_mosquitto",
+            AuditAction::UserProvision => "user.provision",
+            AuditAction::TenantCreate => "tenant.create",
+            AuditAction::TenantRotateKey => "tenant.rotate_key",
+            AuditAction::TenantSetQuota => "tenant.set_quota",
+            AuditAction::ApiKeyCreate => "api_key.create",
+            AuditAction::ApiKeyRevoke => "api_key.revoke",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.as_str() == value)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+        }
+    }
+}
+
+/// Who made a request and from where, recorded with every audit event. Extracted from what
+/// `RequestIdMiddleware`, `IpAllowListMiddleware` and `ApiKeyMiddleware` stored in the request.
+#[derive(Clone, Debug)]
+pub struct AuditContext {
+    /// Name of the API key, see `ApiKeyContext`
+    pub actor: String,
+    pub tenant_id: Option<i32>,
+    pub request_id: Option<String>,
+    pub source_ip: Option<String>,
+}
+
+impl FromRequest for AuditContext {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let extensions = req.extensions();
+        ok(AuditContext {
+            actor: extensions
+                .get::<ApiKeyContext>()
+                .map(|key| key.name.clone())
+                .unwrap_or_else(|| "unknown".to_string()),
+            tenant_id: extensions.get::<TenantContext>().map(|tenant| tenant.id),
+            request_id: extensions.get::<RequestId>().map(|id| id.0.clone()),
+            source_ip: extensions.get::<ClientIp>().map(|ip| ip.0.to_string()),
+        })
+    }
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetAuditEventsQueryDTO {
+    /// 1-based page number (default 1)
+    pub page: Option<u64>,
+    /// Items per page, 1-500 (default 50)
+    pub per_page: Option<u64>,
+    /// API key name, e.g. `static:primary`
+    pub actor: Option<String>,
+    /// Dotted action name, e.g. `user.delete`
+    pub action: Option<String>,
+    /// Username, provisioning batch, tenant or API key name the action applied to
+    pub target: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+    pub request_id: Option<String>,
+    /// Only events at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Only events before this time
+    pub until: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct AuditEventDTO {
+    pub id: i64,
+    pub tenant_id: Option<i32>,
+    pub actor: String,
+    pub action: String,
+    pub target: Option<String>,
+    pub request_id: Option<String>,
+    pub source_ip: Option<String>,
+    /// Changed fields as `{"field": {"before": ..., "after": ...}}`; secrets are redacted
+    #[schema(value_type = Option<Object>)]
+    pub changes: Option<serde_json::Value>,
+    pub outcome: String,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::audit_event_entity::Model> for AuditEventDTO {
+    fn from(event: crate::entities::audit_event_entity::Model) -> Self {
+        Self {
+            id: event.id,
+            tenant_id: event.tenant_id,
+            actor: event.actor,
+            action: event.action,
+            target: event.target,
+            request_id: event.request_id,
+            source_ip: event.source_ip,
+            changes: event.changes,
+            outcome: event.outcome,
+            error: event.error,
+            created_at: event.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct GetAuditEventsDTO {
+    pub events: Vec<AuditEventDTO>,
+    pub pagination: PaginationDTO,
+}
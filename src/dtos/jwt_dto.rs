@@ -6,4 +6,10 @@ pub struct Claims {
     pub exp: usize,
     pub iat: usize,
     pub sub: String,
+    /// The user's metadata attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<Object>)]
+    pub attrs: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 }
@@ -1,3 +1,6 @@
+pub mod api_key_dto;
+pub mod audit_dto;
 pub mod jwt_dto;
 pub mod mqtt_dto;
 pub mod response_dto;
+pub mod tenant_dto;
@@ -1,22 +1,110 @@
-use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::utils::attributes::render_topic_template;
+use crate::utils::password_generator::PasswordAlphabet;
 
 #[derive(Serialize, utoipa::ToSchema)]
-pub struct MqttDTO {
-    pub username: String,
-    pub password: String,
-    pub is_superuser: bool,
+pub struct GetMqttListDTO {
+    pub users: Vec<MqttUserDTO>,
+    pub pagination: PaginationDTO,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
-pub struct GetMqttListDTO {
-    pub users: Vec<MqttDTO>,
+pub struct PaginationDTO {
+    pub page: u64,
+    pub per_page: u64,
+    pub total_items: u64,
+    pub total_pages: u64,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MqttListSortField {
+    #[default]
+    Username,
+    Id,
+    ValidUntil,
+    CreatedAt,
+    LastLoginAt,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    #[default]
+    Asc,
+    Desc,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct GetMqttListQueryDTO {
+    /// 1-based page number (default 1)
+    pub page: Option<u64>,
+    /// Items per page, 1-500 (default 50)
+    pub per_page: Option<u64>,
+    /// Only return usernames starting with this prefix
+    pub username_prefix: Option<String>,
+    pub is_superuser: Option<bool>,
+    pub is_enabled: Option<bool>,
+    /// `true` returns only expired users, `false` hides them
+    pub expired: Option<bool>,
+    /// Only return users whose validity ends within this many seconds
+    pub expiring_within_secs: Option<i64>,
+    /// Only return users that have not authenticated for this many seconds, including never
+    pub inactive_for_secs: Option<i64>,
+    /// Only return users created at or after this time
+    pub created_from: Option<DateTime<Utc>>,
+    /// Only return users created before this time
+    pub created_until: Option<DateTime<Utc>>,
+    /// `true` lists the trash instead of live users
+    pub deleted: Option<bool>,
+    /// Comma-separated tags; only users carrying all of them are returned
+    pub tags: Option<String>,
+    /// Comma-separated `key:value` pairs matched against scalar metadata attributes
+    pub attributes: Option<String>,
+    pub sort_by: Option<MqttListSortField>,
+    pub order: Option<SortOrder>,
 }
 
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 pub struct CreateMqttDTO {
     pub username: String,
-    pub password: String,
+    /// Required unless `generate_password` is set
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Let the service generate the password and return it once in the response
+    #[serde(default)]
+    pub generate_password: bool,
+    /// Generated password length; defaults to `PASSWORD_GENERATOR_LENGTH`
+    #[serde(default)]
+    pub password_length: Option<usize>,
+    /// Generated password alphabet; defaults to `PASSWORD_GENERATOR_ALPHABET`
+    #[serde(default)]
+    pub password_alphabet: Option<PasswordAlphabet>,
     pub is_superuser: bool,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Free-form attributes, usable in ACL topic templates and JWT claims
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Client IDs bound to the user, shown in the user detail
+    #[serde(default)]
+    pub client_ids: Vec<String>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct CreateMqttResultDTO {
+    pub username: String,
+    /// Only present when the password was generated; it is not retrievable here again
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
@@ -24,6 +112,12 @@ pub struct MqttLoginDTO {
     pub username: String,
     pub password: String,
     pub method: Option<AuthType>,
+    /// Client address as seen by the broker (EMQX `${peerhost}`), stored as `last_login_ip`
+    #[serde(default)]
+    pub peerhost: Option<String>,
+    /// Tenant of the user when the hook uses the global API key; defaults to `X-Tenant`
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 #[derive(Serialize, utoipa::ToSchema)]
@@ -31,6 +125,18 @@ pub struct MqttJwtDTO {
     pub token: String,
 }
 
+#[derive(Serialize, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchedCredential {
+    Current,
+    Previous,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MqttLoginResultDTO {
+    pub credential: MatchedCredential,
+}
+
 #[derive(Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum AuthType {
@@ -38,10 +144,32 @@ pub enum AuthType {
     Jwt,
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum AclAction {
+    Publish,
+    Subscribe,
+}
+
+impl AclAction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            AclAction::Publish => "publish",
+            AclAction::Subscribe => "subscribe",
+        }
+    }
+}
+
 #[derive(Deserialize, utoipa::ToSchema)]
 pub struct MqttAclDTO {
     pub username: String,
     pub topic: String,
+    /// When omitted only rules with action `all` and the default topic scheme apply
+    #[serde(default)]
+    pub action: Option<AclAction>,
+    /// Tenant of the user when the hook uses the global API key; defaults to `X-Tenant`
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 #[derive(Deserialize, utoipa::ToSchema)]
@@ -49,8 +177,398 @@ pub struct DeleteMqttDTO {
     pub username: String,
 }
 
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct DeleteMqttQueryDTO {
+    /// Remove the user for good instead of moving it to the trash
+    #[serde(default)]
+    pub hard: bool,
+}
+
 #[derive(Serialize, utoipa::ToSchema)]
 pub struct MqttCredentialsDTO {
     pub username: String,
     pub password: String,
 }
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct RotateMqttPasswordDTO {
+    pub password: String,
+    /// Seconds the previous password stays valid; defaults to `PASSWORD_ROTATION_GRACE_SECS`
+    #[serde(default)]
+    pub grace_period_secs: Option<i64>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct RotateMqttPasswordResultDTO {
+    pub username: String,
+    pub previous_password_expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MqttUserDTO {
+    pub username: String,
+    pub is_superuser: bool,
+    pub is_enabled: bool,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    /// Last successful `/mqtt/check`; written in batches, so it may lag by the flush interval
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub last_login_ip: Option<String>,
+    /// When the user was moved to the trash; only set when listing the trash
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl MqttUserDTO {
+    pub fn new(mqtt: crate::entities::mqtt_entity::Model, tags: Vec<String>) -> Self {
+        Self {
+            username: mqtt.username,
+            is_superuser: mqtt.is_superuser,
+            is_enabled: mqtt.is_enabled,
+            valid_from: mqtt.valid_from,
+            valid_until: mqtt.valid_until,
+            metadata: mqtt.metadata,
+            tags,
+            created_at: mqtt.created_at,
+            updated_at: mqtt.updated_at,
+            last_login_at: mqtt.last_login_at,
+            last_login_ip: mqtt.last_login_ip,
+            deleted_at: mqtt.deleted_at,
+        }
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MqttAclRuleDTO {
+    pub permission: String,
+    pub action: String,
+    pub topic: String,
+}
+
+impl MqttAclRuleDTO {
+    /// Effective rules of `mqtt` in the order `MqttAclService` evaluates them: the stored
+    /// rules with their topic templates rendered, then the default `users/{username}/#`
+    /// scheme, all under the tenant's `topic_prefix`. Rules referencing a missing attribute
+    /// are left out; superusers need no rules.
+    pub fn for_user(
+        mqtt: &crate::entities::mqtt_entity::Model,
+        rules: Vec<crate::entities::acl_rule_entity::Model>,
+        topic_prefix: &str,
+    ) -> Vec<Self> {
+        if mqtt.is_superuser {
+            return Vec::new();
+        }
+        rules
+            .into_iter()
+            .filter_map(|rule| {
+                let topic =
+                    render_topic_template(&rule.topic, &mqtt.username, mqtt.metadata.as_ref())?;
+                Some(MqttAclRuleDTO {
+                    permission: rule.permission,
+                    action: rule.action,
+                    topic: format!("{}{}", topic_prefix, topic),
+                })
+            })
+            .chain(std::iter::once(MqttAclRuleDTO {
+                permission: "allow".to_string(),
+                action: "all".to_string(),
+                topic: format!("{}users/{}/#", topic_prefix, mqtt.username),
+            }))
+            .collect()
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MqttUserRoleDTO {
+    pub name: String,
+    pub priority: i32,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct MqttUserDetailDTO {
+    pub username: String,
+    pub is_superuser: bool,
+    /// Assigned roles, highest priority first
+    pub roles: Vec<MqttUserRoleDTO>,
+    pub is_enabled: bool,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    /// End of the rotation grace period of the previous password, if one is running
+    pub previous_password_expires_at: Option<DateTime<Utc>>,
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    pub tags: Vec<String>,
+    /// Client IDs bound to the user
+    pub client_ids: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub last_login_ip: Option<String>,
+    /// Rules evaluated by `/mqtt/acl`; empty for superusers, who bypass them
+    pub acl_rules: Vec<MqttAclRuleDTO>,
+}
+
+/// Partial update; omitted fields are left untouched, `null` clears nullable ones.
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct UpdateMqttDTO {
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub is_superuser: Option<bool>,
+    #[serde(default)]
+    pub is_enabled: Option<bool>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<Option<serde_json::Value>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub valid_from: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "double_option")]
+    #[schema(value_type = Option<DateTime<Utc>>)]
+    pub valid_until: Option<Option<DateTime<Utc>>>,
+    /// Replaces the whole tag set; `[]` removes every tag
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// Replaces the client ID bindings; `[]` removes them
+    #[serde(default)]
+    pub client_ids: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportFormat {
+    Ndjson,
+    Csv,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    /// Every row is written in one transaction; any failure rolls back the whole import
+    #[default]
+    Atomic,
+    /// Rows are written independently and failures are reported per row
+    BestEffort,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum DuplicateHandling {
+    Skip,
+    #[default]
+    Fail,
+    Overwrite,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportMqttQueryDTO {
+    /// Defaults to `csv` for `text/csv` bodies and `ndjson` otherwise
+    pub format: Option<ImportFormat>,
+    pub mode: Option<ImportMode>,
+    pub on_duplicate: Option<DuplicateHandling>,
+    /// Length of passwords generated for rows without one
+    pub password_length: Option<usize>,
+    /// Alphabet of passwords generated for rows without one
+    pub password_alphabet: Option<PasswordAlphabet>,
+}
+
+/// One NDJSON line or CSV record of a bulk import. A missing password is generated.
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ImportMqttRowDTO {
+    pub username: String,
+    #[serde(default)]
+    pub password: Option<String>,
+    #[serde(default)]
+    pub is_superuser: bool,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    /// Names of existing roles of the tenant to assign
+    #[serde(default)]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Serialize, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportRowStatus {
+    Created,
+    Updated,
+    Skipped,
+    Failed,
+    RolledBack,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ImportRowResultDTO {
+    /// 1-based line (NDJSON) or record (CSV) number
+    pub line: usize,
+    pub username: String,
+    pub status: ImportRowStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Only present when the password was generated by the service
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ImportMqttResultDTO {
+    pub committed: bool,
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub rows: Vec<ImportRowResultDTO>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ProvisionMqttQueryDTO {
+    /// Format of the returned credential manifest, `json` by default
+    pub format: Option<ManifestFormat>,
+}
+
+/// Provisions a batch of users named after `pattern`, e.g. `sensor-{site}-{0001..5000}`.
+///
+/// The pattern holds one sequence placeholder (`{seq}`, `{seq:4}` or a `{first..last}` range)
+/// and any number of `{name}` placeholders filled from `variables`.
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ProvisionMqttDTO {
+    /// Client-chosen identifier; re-running the same batch returns the same manifest
+    pub batch_id: String,
+    pub pattern: String,
+    #[serde(default)]
+    pub variables: std::collections::BTreeMap<String, String>,
+    /// First sequence number for `{seq}` placeholders, 1 by default
+    #[serde(default)]
+    pub start: Option<u64>,
+    /// Number of users; required with `{seq}`, implied by a range
+    #[serde(default)]
+    pub count: Option<u64>,
+    #[serde(default)]
+    pub is_superuser: bool,
+    /// Names of existing roles given to every user of the batch
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    #[serde(default)]
+    #[schema(value_type = Option<Object>)]
+    pub metadata: Option<serde_json::Value>,
+    #[serde(default)]
+    pub valid_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub valid_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub password_length: Option<usize>,
+    #[serde(default)]
+    pub password_alphabet: Option<PasswordAlphabet>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ProvisionedCredentialDTO {
+    pub username: String,
+    pub password: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ProvisionMqttResultDTO {
+    pub batch_id: String,
+    pub total: usize,
+    /// Users written by this request
+    pub created: usize,
+    /// Users already written by an earlier run of the same batch
+    pub existing: usize,
+    pub credentials: Vec<ProvisionedCredentialDTO>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportMqttQueryDTO {
+    /// `json` (default) or `csv`; ACL rules are always exported as JSON
+    pub format: Option<ExportFormat>,
+    /// Also export users that are disabled or outside their validity window
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+/// One user of EMQX's built-in database import file (`sha256`, `salt_position = suffix`).
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct EmqxUserDTO {
+    pub user_id: String,
+    pub password_hash: String,
+    pub salt: String,
+    pub is_superuser: bool,
+}
+
+/// Rules of one user in EMQX's built-in database authorization format.
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct EmqxAclUserDTO {
+    pub username: String,
+    pub rules: Vec<MqttAclRuleDTO>,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MosquittoImportFormat {
+    /// `mosquitto_passwd` file, one `username:hash` per line
+    Passwd,
+    /// Dynamic Security plugin `dynamic-security.json`
+    Dynsec,
+}
+
+#[derive(Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportMosquittoQueryDTO {
+    pub format: MosquittoImportFormat,
+    pub on_duplicate: Option<DuplicateHandling>,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct ImportMosquittoResultDTO {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub roles: usize,
+    pub acl_rules: usize,
+    /// Entries that were imported partially or ignored
+    pub warnings: Vec<String>,
+}
+
+/// Distinguishes an explicit `null` (`Some(None)`) from an omitted field (`None`).
+fn double_option<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,95 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Tenant a request acts on, resolved by `ApiKeyMiddleware` from the API key and `X-Tenant`.
+#[derive(Clone, Debug)]
+pub struct TenantContext {
+    pub id: i32,
+    pub name: String,
+    /// Prepended to every topic of the tenant's users; empty for the default tenant
+    pub topic_prefix: String,
+    /// Whether the request used the tenant's own API key rather than the global one
+    pub scoped: bool,
+    pub quota: TenantQuotaDTO,
+}
+
+/// Limits of a tenant; `null` means unlimited.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, utoipa::ToSchema)]
+pub struct TenantQuotaDTO {
+    /// Users, including those in the trash
+    #[serde(default)]
+    pub max_users: Option<i32>,
+    #[serde(default)]
+    pub max_superusers: Option<i32>,
+    /// ACL rules of the tenant's users and roles
+    #[serde(default)]
+    pub max_acl_rules: Option<i32>,
+    /// `/mqtt/check` calls per minute, counted per service instance
+    #[serde(default)]
+    pub max_auth_per_minute: Option<i32>,
+}
+
+impl From<&crate::entities::tenant_entity::Model> for TenantQuotaDTO {
+    fn from(tenant: &crate::entities::tenant_entity::Model) -> Self {
+        Self {
+            max_users: tenant.max_users,
+            max_superusers: tenant.max_superusers,
+            max_acl_rules: tenant.max_acl_rules,
+            max_auth_per_minute: tenant.max_auth_per_minute,
+        }
+    }
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+pub struct CreateTenantDTO {
+    pub name: String,
+    /// Defaults to `tenants/{name}/`
+    #[serde(default)]
+    pub topic_prefix: Option<String>,
+    /// Defaults to unlimited
+    #[serde(default)]
+    pub quota: TenantQuotaDTO,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TenantDTO {
+    pub name: String,
+    pub topic_prefix: String,
+    pub has_api_key: bool,
+    pub quota: TenantQuotaDTO,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<crate::entities::tenant_entity::Model> for TenantDTO {
+    fn from(tenant: crate::entities::tenant_entity::Model) -> Self {
+        Self {
+            quota: TenantQuotaDTO::from(&tenant),
+            name: tenant.name,
+            topic_prefix: tenant.topic_prefix,
+            has_api_key: tenant.api_key_hash.is_some(),
+            created_at: tenant.created_at,
+        }
+    }
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TenantApiKeyDTO {
+    pub name: String,
+    pub topic_prefix: String,
+    /// Tenant-scoped admin API key; only its hash is stored, so it is shown this once
+    pub api_key: String,
+}
+
+#[derive(Serialize, utoipa::ToSchema)]
+pub struct TenantUsageDTO {
+    pub name: String,
+    /// Users, including those in the trash
+    pub users: u64,
+    pub superusers: u64,
+    pub acl_rules: u64,
+    /// `/mqtt/check` calls in the current minute on this instance
+    pub auth_calls_this_minute: u64,
+    /// `/mqtt/check` calls rejected by `max_auth_per_minute` since this instance started
+    pub auth_calls_throttled: u64,
+    pub quota: TenantQuotaDTO,
+}
@@ -0,0 +1,28 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// An ACL rule owned by either a user (`user_id`) or a role (`role_id`).
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "mqtt_acl_rules")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub user_id: Option<i32>,
+    pub role_id: Option<i32>,
+    /// `allow` or `deny`
+    pub permission: String,
+    /// `publish`, `subscribe` or `all`
+    pub action: String,
+    /// MQTT topic filter, wildcards allowed
+    pub topic: String,
+    /// Rules with a higher priority are evaluated first within their owner.
+    pub priority: i32,
+    pub tenant_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// End of file
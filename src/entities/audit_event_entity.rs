@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "mqtt_audit_events")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i64,
+    /// Tenant the request acted on
+    pub tenant_id: Option<i32>,
+    /// Name of the API key that made the request
+    pub actor: String,
+    pub action: String,
+    /// Username, tenant or API key the action applied to
+    pub target: Option<String>,
+    pub request_id: Option<String>,
+    pub source_ip: Option<String>,
+    /// Changed fields with their values before and after, secrets redacted
+    pub changes: Option<Json>,
+    /// `success` or `failure`
+    pub outcome: String,
+    pub error: Option<String>,
+    pub created_at: DateTimeUtc,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// End of file
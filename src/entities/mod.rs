@@ -1 +1,10 @@
+pub mod acl_rule_entity;
+pub mod api_key_entity;
+pub mod audit_event_entity;
 pub mod mqtt_entity;
+pub mod provision_batch_entity;
+pub mod role_entity;
+pub mod tenant_entity;
+pub mod user_client_id_entity;
+pub mod user_role_entity;
+pub mod user_tag_entity;
@@ -6,10 +6,24 @@ use serde::{Deserialize, Serialize};
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: i32,
-    #[sea_orm(unique)]
+    /// Unique within its tenant
     pub username: String,
     pub password: String,
     pub is_superuser: bool,
+    pub is_enabled: bool,
+    pub valid_from: Option<DateTimeUtc>,
+    pub valid_until: Option<DateTimeUtc>,
+    pub previous_password: Option<String>,
+    pub previous_password_expires_at: Option<DateTimeUtc>,
+    pub metadata: Option<Json>,
+    pub provision_batch_id: Option<String>,
+    pub created_at: DateTimeUtc,
+    pub updated_at: DateTimeUtc,
+    pub last_login_at: Option<DateTimeUtc>,
+    pub last_login_ip: Option<String>,
+    /// Set while the user is in the trash; such users do not exist for auth
+    pub deleted_at: Option<DateTimeUtc>,
+    pub tenant_id: i32,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -17,4 +31,21 @@ pub enum Relation {}
 
 impl ActiveModelBehavior for ActiveModel {}
 
+impl Model {
+    /// Whether the account is enabled and `now` falls inside its validity window.
+    pub fn is_active_at(&self, now: DateTimeUtc) -> bool {
+        self.is_enabled
+            && self.valid_from.is_none_or(|from| now >= from)
+            && self.valid_until.is_none_or(|until| now < until)
+    }
+
+    /// The previous stored password (encrypted or hashed), if its rotation grace period is still running.
+    pub fn previous_password_at(&self, now: DateTimeUtc) -> Option<&str> {
+        match (&self.previous_password, self.previous_password_expires_at) {
+            (Some(password), Some(expires_at)) if now < expires_at => Some(password),
+            _ => None,
+        }
+    }
+}
+
 // End of file
@@ -0,0 +1,30 @@
+use sea_orm::entity::prelude::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
+#[sea_orm(table_name = "mqtt_tenants")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    #[sea_orm(unique)]
+    pub name: String,
+    /// Prepended to every topic of the tenant's users; empty for the default tenant
+    #[sea_orm(unique)]
+    pub topic_prefix: String,
+    /// `hex(sha256(key))` of the tenant-scoped admin API key
+    #[serde(skip_serializing)]
+    pub api_key_hash: Option<String>,
+    pub created_at: DateTimeUtc,
+    /// Quotas; `None` is unlimited
+    pub max_users: Option<i32>,
+    pub max_superusers: Option<i32>,
+    pub max_acl_rules: Option<i32>,
+    pub max_auth_per_minute: Option<i32>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+// End of file
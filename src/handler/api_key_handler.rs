@@ -0,0 +1,126 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::api_key_dto::{ApiKeyDTO, CreateApiKeyDTO, CreatedApiKeyDTO};
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub api_key_service: Arc<ApiKeyService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/keys",
+    tag = "API Keys",
+    request_body = CreateApiKeyDTO,
+    responses(
+        (status = 200, description = "API key created successfully", body = CreatedApiKeyDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 403, description = "API key lacks the admin scope or is scoped to another tenant"),
+        (status = 409, description = "API key name already exists")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Create API Key
+///
+/// Creates an API key with the given scopes. The key is returned once; only its hash is stored.
+pub async fn create_api_key_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    body: web::Json<CreateApiKeyDTO>,
+) -> impl Responder {
+    match data
+        .api_key_service
+        .create_key(&tenant, &audit, body.into_inner())
+        .await
+    {
+        Ok(created) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "API key created successfully",
+            data: Some(created),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/keys",
+    tag = "API Keys",
+    responses(
+        (status = 200, description = "API key list retrieved successfully", body = [ApiKeyDTO]),
+        (status = 403, description = "API key lacks the admin scope")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// List API Keys
+///
+/// Lists API keys, including revoked and expired ones. Tenant-scoped keys only see their tenant's keys.
+pub async fn get_api_key_list_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+) -> impl Responder {
+    match data.api_key_service.list_keys(&tenant).await {
+        Ok(keys) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "API key list retrieved successfully",
+            data: Some(keys),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/mqtt/keys/{id}",
+    tag = "API Keys",
+    params(
+        ("id" = i32, Path, description = "Id of the API key")
+    ),
+    responses(
+        (status = 200, description = "API key revoked successfully", body = ApiKeyDTO),
+        (status = 403, description = "API key lacks the admin scope"),
+        (status = 404, description = "API key not found"),
+        (status = 409, description = "API key is already revoked")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Revoke API Key
+///
+/// Revokes an API key. The row is kept, so the key's name still identifies its past requests.
+pub async fn revoke_api_key_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<i32>,
+) -> impl Responder {
+    let id = path.into_inner();
+    match data.api_key_service.revoke_key(&tenant, &audit, id).await {
+        Ok(revoked) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "API key revoked successfully",
+            data: Some(revoked),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
@@ -0,0 +1,55 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{GetAuditEventsDTO, GetAuditEventsQueryDTO};
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::audit_service::AuditService;
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub audit_service: Arc<AuditService>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/audit",
+    tag = "Audit",
+    params(GetAuditEventsQueryDTO),
+    responses(
+        (status = 200, description = "Audit events retrieved successfully", body = GetAuditEventsDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 403, description = "API key lacks the admin scope")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// List Audit Events
+///
+/// Lists recorded admin actions, newest first. Tenant-scoped keys only see their tenant's events.
+pub async fn get_audit_events_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    query: web::Query<GetAuditEventsQueryDTO>,
+) -> impl Responder {
+    match data
+        .audit_service
+        .list_events(&tenant, query.into_inner())
+        .await
+    {
+        Ok(events) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Audit events retrieved successfully",
+            data: Some(events),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
@@ -1,8 +1,10 @@
 use actix_web::{HttpResponse, Responder, web};
 use std::sync::Arc;
 
+use crate::dtos::audit_dto::AuditContext;
 use crate::dtos::mqtt_dto::CreateMqttDTO;
 use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
 use crate::services::create_mqtt_service::CreateMqttService;
 use crate::services::service_error::MqttServiceError;
@@ -18,7 +20,8 @@ pub struct AppState {
     request_body = CreateMqttDTO,
     responses(
         (status = 200, description = "User mqtt created successfully"),
-        (status = 400, description = "Validation Error", body = ErrorResponseValidation)
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 409, description = "User mqtt already exists or the tenant quota is exceeded")
     ),
     security(
         ("api_key" = [])
@@ -26,20 +29,22 @@ pub struct AppState {
 )]
 /// Create MQTT User
 ///
-/// Creates a new MQTT user or superuser with a username and password.
+/// Creates a new MQTT user or superuser. The password can be supplied or generated by the service.
 pub async fn create_mqtt_handler(
     data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
     body: web::Json<CreateMqttDTO>,
 ) -> impl Responder {
     match data
         .create_mqtt_service
-        .create_mqtt(body.into_inner())
+        .create_mqtt(&tenant, &audit, body.into_inner())
         .await
     {
-        Ok(_) => HttpResponse::Ok().json(ResponseDTO::<()> {
+        Ok(created) => HttpResponse::Ok().json(ResponseDTO {
             success: true,
             message: "User mqtt created successfully",
-            data: None,
+            data: Some(created),
             result: None,
         }),
         Err(e) => match &e {
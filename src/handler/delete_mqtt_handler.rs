@@ -1,5 +1,7 @@
-use crate::dtos::mqtt_dto::DeleteMqttDTO;
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::{DeleteMqttDTO, DeleteMqttQueryDTO};
 use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
 use crate::services::delete_mqtt_service::DeleteMqttService;
 use crate::services::service_error::MqttServiceError;
@@ -15,11 +17,13 @@ pub struct AppState {
     path = "/mqtt/{username}",
     tag = "MQTT",
     params(
-        ("username" = String, Path, description = "Username of the client to delete")
+        ("username" = String, Path, description = "Username of the client to delete"),
+        DeleteMqttQueryDTO
     ),
     responses(
         (status = 200, description = "User mqtt deleted successfully"),
-        (status = 400, description = "Validation Error", body = ErrorResponseValidation)
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 404, description = "User mqtt not found")
     ),
     security(
         ("api_key" = [])
@@ -27,15 +31,19 @@ pub struct AppState {
 )]
 /// Delete MQTT User
 ///
-/// Hard-deletes an existing MQTT user by their username.
+/// Moves an MQTT user to the trash, where auth treats it as nonexistent until it is restored
+/// or purged. `?hard=true` removes it immediately.
 pub async fn delete_mqtt(
     data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
     params: web::Path<DeleteMqttDTO>,
+    query: web::Query<DeleteMqttQueryDTO>,
 ) -> impl Responder {
     let username = &params.username;
     match data
         .delete_mqtt_service
-        .delete_mqtt(username)
+        .delete_mqtt(&tenant, &audit, username, query.hard)
         .await
     {
         Ok(_) => HttpResponse::Ok().json(ResponseDTO::<()> {
@@ -0,0 +1,86 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::mqtt_dto::{EmqxAclUserDTO, EmqxUserDTO, ExportFormat, ExportMqttQueryDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::export_mqtt_service::{ExportMqttService, emqx_users_csv};
+
+pub struct AppState {
+    pub export_mqtt_service: Arc<ExportMqttService>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/export/users",
+    tag = "MQTT",
+    params(ExportMqttQueryDTO),
+    responses(
+        (status = 200, description = "EMQX built-in database user import file", body = [EmqxUserDTO])
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Export MQTT Users for EMQX
+///
+/// Returns the users as an EMQX built-in database import file in JSON or CSV.
+pub async fn export_mqtt_users_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    query: web::Query<ExportMqttQueryDTO>,
+) -> impl Responder {
+    let users = match data
+        .export_mqtt_service
+        .export_users(&tenant, query.include_inactive)
+        .await
+    {
+        Ok(users) => users,
+        Err(e) => return e.to_http_response_with_details(None::<String>),
+    };
+
+    match query.format.unwrap_or_default() {
+        ExportFormat::Json => HttpResponse::Ok()
+            .insert_header(("Content-Disposition", "attachment; filename=\"users.json\""))
+            .json(users),
+        ExportFormat::Csv => match emqx_users_csv(&users) {
+            Ok(csv) => HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header(("Content-Disposition", "attachment; filename=\"users.csv\""))
+                .body(csv),
+            Err(e) => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/export/acl",
+    tag = "MQTT",
+    params(ExportMqttQueryDTO),
+    responses(
+        (status = 200, description = "EMQX built-in database authorization rules", body = [EmqxAclUserDTO])
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Export MQTT ACL Rules for EMQX
+///
+/// Returns per-user ACL rules in EMQX's built-in database authorization format.
+pub async fn export_mqtt_acl_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    query: web::Query<ExportMqttQueryDTO>,
+) -> impl Responder {
+    match data
+        .export_mqtt_service
+        .export_acl(&tenant, query.include_inactive)
+        .await
+    {
+        Ok(rules) => HttpResponse::Ok()
+            .insert_header(("Content-Disposition", "attachment; filename=\"acl.json\""))
+            .json(rules),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
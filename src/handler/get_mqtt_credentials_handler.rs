@@ -1,7 +1,9 @@
 use actix_web::{HttpResponse, Responder, web};
 use std::sync::Arc;
 
+use crate::dtos::audit_dto::AuditContext;
 use crate::dtos::response_dto::ResponseDTO;
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
 use crate::services::get_mqtt_credentials_service::GetMqttCredentialsService;
 
@@ -28,12 +30,14 @@ pub struct AppState {
 /// Retrieves the credentials (username and hashed password) for a specific MQTT user.
 pub async fn get_mqtt_credentials_handler(
     data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
     path: web::Path<String>,
 ) -> impl Responder {
     let username = path.into_inner();
     match data
         .get_mqtt_credentials_service
-        .get_credentials(&username)
+        .get_credentials(&tenant, &audit, &username)
         .await
     {
         Ok(creds) => HttpResponse::Ok().json(ResponseDTO {
@@ -1,8 +1,9 @@
 use actix_web::{HttpResponse, Responder, web};
 use std::sync::Arc;
 
-use crate::dtos::mqtt_dto::GetMqttListDTO;
-use crate::dtos::response_dto::ResponseDTO;
+use crate::dtos::mqtt_dto::GetMqttListQueryDTO;
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
 use crate::services::get_mqtt_list_service::GetMqttListService;
 
@@ -14,8 +15,10 @@ pub struct AppState {
     get,
     path = "/mqtt",
     tag = "MQTT",
+    params(GetMqttListQueryDTO),
     responses(
-        (status = 200, description = "User MQTT list retrieved successfully")
+        (status = 200, description = "User MQTT list retrieved successfully"),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation)
     ),
     security(
         ("api_key" = [])
@@ -23,13 +26,21 @@ pub struct AppState {
 )]
 /// Get MQTT List
 ///
-/// Retrieves a list of all registered MQTT users.
-pub async fn get_mqtt_list_handler(data: web::Data<AppState>) -> impl Responder {
-    match data.get_mqtt_list_service.get_mqtt_list().await {
-        Ok(users) => HttpResponse::Ok().json(ResponseDTO {
+/// Retrieves a paginated, filterable list of MQTT users. Passwords are never included.
+pub async fn get_mqtt_list_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    query: web::Query<GetMqttListQueryDTO>,
+) -> impl Responder {
+    match data
+        .get_mqtt_list_service
+        .get_mqtt_list(&tenant, query.into_inner())
+        .await
+    {
+        Ok(list) => HttpResponse::Ok().json(ResponseDTO {
             success: true,
             message: "User MQTT list retrieved successfully",
-            data: Some(GetMqttListDTO { users }),
+            data: Some(list),
             result: None,
         }),
         Err(e) => e.to_http_response(),
@@ -0,0 +1,50 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::response_dto::ResponseDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::get_mqtt_user_service::GetMqttUserService;
+
+pub struct AppState {
+    pub get_mqtt_user_service: Arc<GetMqttUserService>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/users/{username}",
+    tag = "MQTT",
+    params(
+        ("username" = String, Path, description = "Username of the client")
+    ),
+    responses(
+        (status = 200, description = "User MQTT retrieved successfully"),
+        (status = 404, description = "User MQTT not found")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Get MQTT User
+///
+/// Retrieves an MQTT user's metadata and ACL rules. Secrets are never included.
+pub async fn get_mqtt_user_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let username = path.into_inner();
+    match data
+        .get_mqtt_user_service
+        .get_mqtt_user(&tenant, &username)
+        .await
+    {
+        Ok(user) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "User MQTT retrieved successfully",
+            data: Some(user),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
@@ -0,0 +1,69 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::{ImportMosquittoQueryDTO, ImportMosquittoResultDTO};
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::import_mosquitto_service::ImportMosquittoService;
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub import_mosquitto_service: Arc<ImportMosquittoService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/import/mosquitto",
+    tag = "MQTT",
+    params(ImportMosquittoQueryDTO),
+    request_body(
+        content = String,
+        description = "A mosquitto_passwd file or a dynamic-security.json document",
+        content_type = "text/plain"
+    ),
+    responses(
+        (status = 200, description = "Mosquitto import completed", body = ImportMosquittoResultDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 409, description = "A client already exists and on_duplicate is fail, or the tenant quota is exceeded")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Import Mosquitto Users
+///
+/// Imports users from a Mosquitto password file, or users, roles and ACLs from a dynsec config.
+pub async fn import_mosquitto_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    query: web::Query<ImportMosquittoQueryDTO>,
+    body: web::Bytes,
+) -> impl Responder {
+    match data
+        .import_mosquitto_service
+        .import_mosquitto(
+            &tenant,
+            &audit,
+            &body,
+            query.format,
+            query.on_duplicate.unwrap_or_default(),
+        )
+        .await
+    {
+        Ok(report) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Mosquitto import completed",
+            data: Some(report),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
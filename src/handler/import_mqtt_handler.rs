@@ -0,0 +1,91 @@
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::{ImportFormat, ImportMqttQueryDTO};
+use crate::dtos::response_dto::{ErrorResponseDTO, ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::import_mqtt_service::{ImportMqttService, ImportOptions};
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub import_mqtt_service: Arc<ImportMqttService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/import",
+    tag = "MQTT",
+    params(ImportMqttQueryDTO),
+    request_body(
+        content = String,
+        description = "NDJSON (one ImportMqttRowDTO per line) or CSV with a header row",
+        content_type = "application/x-ndjson"
+    ),
+    responses(
+        (status = 200, description = "User mqtt import completed"),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 409, description = "Import would exceed the tenant quota"),
+        (status = 422, description = "Atomic import rolled back")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Bulk Import MQTT Users
+///
+/// Imports users from NDJSON or CSV, either atomically or best-effort with a per-row report.
+pub async fn import_mqtt_handler(
+    req: HttpRequest,
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    query: web::Query<ImportMqttQueryDTO>,
+    body: web::Bytes,
+) -> impl Responder {
+    let query = query.into_inner();
+    let format = query.format.unwrap_or_else(|| {
+        if req.content_type().eq_ignore_ascii_case("text/csv") {
+            ImportFormat::Csv
+        } else {
+            ImportFormat::Ndjson
+        }
+    });
+
+    match data
+        .import_mqtt_service
+        .import_mqtt(
+            &tenant,
+            &audit,
+            &body,
+            ImportOptions {
+                format,
+                mode: query.mode.unwrap_or_default(),
+                on_duplicate: query.on_duplicate.unwrap_or_default(),
+                password_length: query.password_length,
+                password_alphabet: query.password_alphabet,
+            },
+        )
+        .await
+    {
+        Ok(report) if report.committed => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "User mqtt import completed",
+            data: Some(report),
+            result: None,
+        }),
+        Ok(report) => HttpResponse::UnprocessableEntity().json(ErrorResponseDTO {
+            success: false,
+            message: "User mqtt import rolled back",
+            result: None,
+            details: Some(report),
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
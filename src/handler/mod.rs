@@ -1,7 +1,18 @@
+pub mod api_key_handler;
+pub mod audit_handler;
 pub mod create_mqtt_handler;
 pub mod delete_mqtt_handler;
+pub mod export_mqtt_handler;
 pub mod get_mqtt_credentials_handler;
 pub mod get_mqtt_list_handler;
+pub mod get_mqtt_user_handler;
 pub mod handler_error;
+pub mod import_mosquitto_handler;
+pub mod import_mqtt_handler;
 pub mod mqtt_acl_handler;
 pub mod mqtt_login_handler;
+pub mod provision_mqtt_handler;
+pub mod restore_mqtt_handler;
+pub mod rotate_mqtt_password_handler;
+pub mod tenant_handler;
+pub mod update_mqtt_handler;
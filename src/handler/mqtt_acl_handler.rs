@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 use crate::dtos::mqtt_dto::MqttAclDTO;
 use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
 use crate::services::mqtt_acl_service::MqttAclService;
 use crate::services::service_error::MqttServiceError;
@@ -29,11 +30,12 @@ pub struct AppState {
 /// Verifies whether the specified MQTT user has permissions to access the given topic.
 pub async fn mqtt_acl_handler(
     data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
     body: web::Json<MqttAclDTO>,
 ) -> impl Responder {
     match data
         .mqtt_acl_service
-        .check_acl_permission(body.into_inner())
+        .check_acl_permission(&tenant, body.into_inner())
         .await
     {
         Ok(true) => HttpResponse::Ok().json(ResponseDTO::<()> {
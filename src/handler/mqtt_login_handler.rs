@@ -1,10 +1,11 @@
 use actix_web::{HttpResponse, Responder, web};
 use std::sync::Arc;
 
-use crate::dtos::mqtt_dto::{MqttJwtDTO, MqttLoginDTO};
+use crate::dtos::mqtt_dto::{MqttJwtDTO, MqttLoginDTO, MqttLoginResultDTO};
 use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::handler::handler_error::AppError;
-use crate::services::mqtt_login_service::MqttLoginService;
+use crate::services::mqtt_login_service::{MqttLoginOutcome, MqttLoginService};
 use crate::services::service_error::MqttServiceError;
 
 pub struct AppState {
@@ -18,7 +19,8 @@ pub struct AppState {
     request_body = MqttLoginDTO,
     responses(
         (status = 200, description = "User MQTT checked"),
-        (status = 400, description = "Validation Error", body = ErrorResponseValidation)
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 429, description = "Tenant exceeded its auth calls per minute")
     ),
     security(
         ("api_key" = [])
@@ -29,30 +31,28 @@ pub struct AppState {
 /// Validates an MQTT user's login credentials. Returns an access token if successful.
 pub async fn login_with_credentials_handler(
     data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
     body: web::Json<MqttLoginDTO>,
 ) -> impl Responder {
     match data
         .mqtt_login_service
-        .login_with_credentials(body.into_inner())
+        .login_with_credentials(&tenant, body.into_inner())
         .await
     {
-        Ok((_, token)) => {
-            if token.is_empty() {
-                HttpResponse::Ok().json(ResponseDTO::<()> {
-                    success: true,
-                    message: "User MQTT is active",
-                    data: None,
-                    result: Some("allow"),
-                })
-            } else {
-                HttpResponse::Ok().json(ResponseDTO::<MqttJwtDTO> {
-                    success: true,
-                    message: "User MQTT is active",
-                    data: Some(MqttJwtDTO { token }),
-                    result: Some("allow"),
-                })
-            }
+        Ok(MqttLoginOutcome::Credentials(credential)) => {
+            HttpResponse::Ok().json(ResponseDTO::<MqttLoginResultDTO> {
+                success: true,
+                message: "User MQTT is active",
+                data: Some(MqttLoginResultDTO { credential }),
+                result: Some("allow"),
+            })
         }
+        Ok(MqttLoginOutcome::Jwt(token)) => HttpResponse::Ok().json(ResponseDTO::<MqttJwtDTO> {
+            success: true,
+            message: "User MQTT is active",
+            data: Some(MqttJwtDTO { token }),
+            result: Some("allow"),
+        }),
         Err(e) => match &e {
             MqttServiceError::BadRequest(validation_errors) => {
                 e.to_http_response_with_result(Some("deny"), Some(validation_errors))
@@ -0,0 +1,84 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::{
+    ManifestFormat, ProvisionMqttDTO, ProvisionMqttQueryDTO, ProvisionMqttResultDTO,
+};
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::provision_mqtt_service::ProvisionMqttService;
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub provision_mqtt_service: Arc<ProvisionMqttService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/provision",
+    tag = "MQTT",
+    params(ProvisionMqttQueryDTO),
+    request_body = ProvisionMqttDTO,
+    responses(
+        (status = 200, description = "User mqtt batch provisioned", body = ProvisionMqttResultDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 409, description = "Batch conflicts with an earlier run or existing users, or exceeds the tenant quota")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Provision MQTT Users
+///
+/// Creates a batch of users from a naming pattern and returns a JSON or CSV credential manifest.
+pub async fn provision_mqtt_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    query: web::Query<ProvisionMqttQueryDTO>,
+    body: web::Json<ProvisionMqttDTO>,
+) -> impl Responder {
+    match data
+        .provision_mqtt_service
+        .provision_mqtt(&tenant, &audit, body.into_inner())
+        .await
+    {
+        Ok(manifest) => match query.format.unwrap_or_default() {
+            ManifestFormat::Json => HttpResponse::Ok().json(ResponseDTO {
+                success: true,
+                message: "User mqtt batch provisioned",
+                data: Some(manifest),
+                result: None,
+            }),
+            ManifestFormat::Csv => match manifest_csv(&manifest) {
+                Ok(csv) => HttpResponse::Ok()
+                    .content_type("text/csv; charset=utf-8")
+                    .insert_header((
+                        "Content-Disposition",
+                        format!("attachment; filename=\"{}.csv\"", manifest.batch_id),
+                    ))
+                    .body(csv),
+                Err(e) => MqttServiceError::InternalError(e.to_string())
+                    .to_http_response_with_details(None::<String>),
+            },
+        },
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
+
+fn manifest_csv(manifest: &ProvisionMqttResultDTO) -> Result<Vec<u8>, csv::Error> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for credential in &manifest.credentials {
+        writer.serialize(credential)?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
@@ -0,0 +1,53 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::MqttUserDTO;
+use crate::dtos::response_dto::ResponseDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::restore_mqtt_service::RestoreMqttService;
+
+pub struct AppState {
+    pub restore_mqtt_service: Arc<RestoreMqttService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/{username}/restore",
+    tag = "MQTT",
+    params(
+        ("username" = String, Path, description = "Username of the client to restore")
+    ),
+    responses(
+        (status = 200, description = "User mqtt restored successfully", body = MqttUserDTO),
+        (status = 404, description = "User mqtt not found in the trash")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Restore MQTT User
+///
+/// Takes a soft-deleted MQTT user out of the trash.
+pub async fn restore_mqtt_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<String>,
+) -> impl Responder {
+    let username = path.into_inner();
+    match data
+        .restore_mqtt_service
+        .restore_mqtt(&tenant, &audit, &username)
+        .await
+    {
+        Ok(user) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "User mqtt restored successfully",
+            data: Some(user),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
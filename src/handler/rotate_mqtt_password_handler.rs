@@ -0,0 +1,62 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::RotateMqttPasswordDTO;
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::rotate_mqtt_password_service::RotateMqttPasswordService;
+use crate::services::service_error::MqttServiceError;
+
+pub struct AppState {
+    pub rotate_mqtt_password_service: Arc<RotateMqttPasswordService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/{username}/rotate",
+    tag = "MQTT",
+    params(
+        ("username" = String, Path, description = "Username of the client to rotate")
+    ),
+    request_body = RotateMqttPasswordDTO,
+    responses(
+        (status = 200, description = "User mqtt password rotated successfully"),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 404, description = "User mqtt not found")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Rotate MQTT Password
+///
+/// Stores a new password while the previous one stays valid for a grace period.
+pub async fn rotate_mqtt_password_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<String>,
+    body: web::Json<RotateMqttPasswordDTO>,
+) -> impl Responder {
+    let username = path.into_inner();
+    match data
+        .rotate_mqtt_password_service
+        .rotate_password(&tenant, &audit, &username, body.into_inner())
+        .await
+    {
+        Ok(result) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "User mqtt password rotated successfully",
+            data: Some(result),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
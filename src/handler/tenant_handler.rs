@@ -0,0 +1,225 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::{
+    CreateTenantDTO, TenantApiKeyDTO, TenantContext, TenantDTO, TenantQuotaDTO, TenantUsageDTO,
+};
+use crate::handler::handler_error::AppError;
+use crate::services::quota_service::QuotaService;
+use crate::services::service_error::MqttServiceError;
+use crate::services::tenant_service::TenantService;
+
+pub struct AppState {
+    pub tenant_service: Arc<TenantService>,
+    pub quota_service: Arc<QuotaService>,
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/tenants",
+    tag = "Tenants",
+    request_body = CreateTenantDTO,
+    responses(
+        (status = 200, description = "Tenant created successfully", body = TenantApiKeyDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 403, description = "Tenant-scoped API keys cannot manage tenants"),
+        (status = 409, description = "Tenant name or topic prefix already exists")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Create Tenant
+///
+/// Creates a tenant with its own user namespace and topic prefix, and returns its API key.
+pub async fn create_tenant_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    body: web::Json<CreateTenantDTO>,
+) -> impl Responder {
+    match data
+        .tenant_service
+        .create_tenant(&tenant, &audit, body.into_inner())
+        .await
+    {
+        Ok(created) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Tenant created successfully",
+            data: Some(created),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/tenants",
+    tag = "Tenants",
+    responses(
+        (status = 200, description = "Tenant list retrieved successfully", body = [TenantDTO]),
+        (status = 403, description = "Tenant-scoped API keys cannot manage tenants")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// List Tenants
+///
+/// Lists every tenant with its topic prefix.
+pub async fn get_tenant_list_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+) -> impl Responder {
+    match data.tenant_service.list_tenants(&tenant).await {
+        Ok(tenants) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Tenant list retrieved successfully",
+            data: Some(tenants),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/mqtt/tenants/{name}/key",
+    tag = "Tenants",
+    params(
+        ("name" = String, Path, description = "Name of the tenant")
+    ),
+    responses(
+        (status = 200, description = "Tenant API key rotated successfully", body = TenantApiKeyDTO),
+        (status = 403, description = "Tenant-scoped API keys cannot manage tenants"),
+        (status = 404, description = "Tenant not found")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Rotate Tenant API Key
+///
+/// Issues a new API key for the tenant; the previous key stops working immediately.
+pub async fn rotate_tenant_api_key_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    match data
+        .tenant_service
+        .rotate_api_key(&tenant, &audit, &name)
+        .await
+    {
+        Ok(rotated) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Tenant API key rotated successfully",
+            data: Some(rotated),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/mqtt/tenants/{name}/quota",
+    tag = "Tenants",
+    params(
+        ("name" = String, Path, description = "Name of the tenant")
+    ),
+    request_body = TenantQuotaDTO,
+    responses(
+        (status = 200, description = "Tenant quota updated successfully", body = TenantDTO),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 403, description = "Tenant-scoped API keys cannot manage tenants"),
+        (status = 404, description = "Tenant not found")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Set Tenant Quota
+///
+/// Replaces the quota of a tenant. Omitted or `null` limits are unlimited.
+pub async fn set_tenant_quota_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<String>,
+    body: web::Json<TenantQuotaDTO>,
+) -> impl Responder {
+    let name = path.into_inner();
+    match data
+        .tenant_service
+        .set_quota(&tenant, &audit, &name, body.into_inner())
+        .await
+    {
+        Ok(updated) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Tenant quota updated successfully",
+            data: Some(updated),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/mqtt/tenants/{name}/usage",
+    tag = "Tenants",
+    params(
+        ("name" = String, Path, description = "Name of the tenant")
+    ),
+    responses(
+        (status = 200, description = "Tenant usage retrieved successfully", body = TenantUsageDTO),
+        (status = 403, description = "API key is scoped to another tenant"),
+        (status = 404, description = "Tenant not found")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Get Tenant Usage
+///
+/// Shows a tenant's usage against its quota. Tenant-scoped keys may read their own tenant.
+pub async fn get_tenant_usage_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    path: web::Path<String>,
+) -> impl Responder {
+    let name = path.into_inner();
+    let target = match data
+        .tenant_service
+        .resolve_tenant(&tenant, Some(&name))
+        .await
+    {
+        Ok(target) => target,
+        Err(e) => return e.to_http_response_with_details(None::<String>),
+    };
+    match data.quota_service.usage(&target).await {
+        Ok(usage) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "Tenant usage retrieved successfully",
+            data: Some(usage),
+            result: None,
+        }),
+        Err(e) => e.to_http_response_with_details(None::<String>),
+    }
+}
@@ -0,0 +1,63 @@
+use actix_web::{HttpResponse, Responder, web};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::AuditContext;
+use crate::dtos::mqtt_dto::UpdateMqttDTO;
+use crate::dtos::response_dto::{ErrorResponseValidation, ResponseDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::handler::handler_error::AppError;
+use crate::services::service_error::MqttServiceError;
+use crate::services::update_mqtt_service::UpdateMqttService;
+
+pub struct AppState {
+    pub update_mqtt_service: Arc<UpdateMqttService>,
+}
+
+#[utoipa::path(
+    patch,
+    path = "/mqtt/{username}",
+    tag = "MQTT",
+    params(
+        ("username" = String, Path, description = "Username of the client to update")
+    ),
+    request_body = UpdateMqttDTO,
+    responses(
+        (status = 200, description = "User mqtt updated successfully"),
+        (status = 400, description = "Validation Error", body = ErrorResponseValidation),
+        (status = 404, description = "User mqtt not found"),
+        (status = 409, description = "Tenant superuser quota exceeded")
+    ),
+    security(
+        ("api_key" = [])
+    )
+)]
+/// Update MQTT User
+///
+/// Partially updates an MQTT user and returns it without the password.
+pub async fn update_mqtt_handler(
+    data: web::Data<AppState>,
+    tenant: web::ReqData<TenantContext>,
+    audit: AuditContext,
+    path: web::Path<String>,
+    body: web::Json<UpdateMqttDTO>,
+) -> impl Responder {
+    let username = path.into_inner();
+    match data
+        .update_mqtt_service
+        .update_mqtt(&tenant, &audit, &username, body.into_inner())
+        .await
+    {
+        Ok(user) => HttpResponse::Ok().json(ResponseDTO {
+            success: true,
+            message: "User mqtt updated successfully",
+            data: Some(user),
+            result: None,
+        }),
+        Err(e) => match &e {
+            MqttServiceError::BadRequest(validation_errors) => {
+                e.to_http_response_with_details(Some(validation_errors))
+            }
+            _ => e.to_http_response_with_details(None::<String>),
+        },
+    }
+}
@@ -3,6 +3,7 @@ use migration::{Migrator, MigratorTrait};
 use sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr};
 use std::time::Duration;
 
+#[derive(Clone, Copy)]
 pub enum DbType {
     Mysql,
     Postgres,
@@ -31,6 +32,56 @@ impl DbType {
     }
 }
 
+/// Connection settings read from `DB_TYPE` and the matching `MYSQL_*` / `POSTGRES_*` variables.
+pub struct DbConfig {
+    pub db_type: DbType,
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    pub password: String,
+    pub database: String,
+}
+
+impl DbConfig {
+    pub fn from_env() -> Self {
+        let db_type_str = std::env::var("DB_TYPE").unwrap_or_else(|_| "mysql".to_string());
+        let db_type = DbType::from_str(&db_type_str);
+        let prefix = match db_type {
+            DbType::Postgres => "POSTGRES",
+            DbType::Mysql => "MYSQL",
+        };
+        let default_port = match db_type {
+            DbType::Postgres => 5432,
+            DbType::Mysql => 3306,
+        };
+        let var = |name: &str| std::env::var(format!("{}_{}", prefix, name));
+
+        DbConfig {
+            db_type,
+            host: var("HOST").unwrap_or_else(|_| "127.0.0.1".to_string()),
+            port: var("PORT")
+                .ok()
+                .and_then(|v| v.parse::<u16>().ok())
+                .unwrap_or(default_port),
+            user: var("USER").expect("❌ Database user is not set"),
+            password: var("PASSWORD").expect("❌ Database password is not set"),
+            database: var("DATABASE").expect("❌ Database name is not set"),
+        }
+    }
+
+    pub async fn connect(&self) -> Result<DatabaseConnection, DbErr> {
+        init_db(
+            self.db_type,
+            &self.host,
+            self.port,
+            &self.user,
+            &self.password,
+            &self.database,
+        )
+        .await
+    }
+}
+
 /// Initialize database connection and return a `DatabaseConnection`.
 pub async fn init_db(
     db_type: DbType,
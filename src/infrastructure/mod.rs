@@ -1 +1,3 @@
 pub mod database;
+pub mod tls;
+pub mod unix_socket;
@@ -0,0 +1,362 @@
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::dev::Extensions;
+use actix_web::rt::net::TcpStream;
+use log::{debug, info, warn};
+use rustls::crypto::ring::{default_provider, sign::any_supported_type};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{RootCertStore, ServerConfig};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::any::Any;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::SystemTime;
+
+use crate::dtos::api_key_dto::ApiScope;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ClientAuth {
+    /// No client certificate is requested
+    None,
+    /// A client certificate is verified when presented
+    Optional,
+    /// Connections without a valid client certificate are refused
+    Required,
+}
+
+impl ClientAuth {
+    /// Parses `TLS_CLIENT_AUTH`; an empty value is `required`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "required" => Ok(ClientAuth::Required),
+            "optional" => Ok(ClientAuth::Optional),
+            "none" => Ok(ClientAuth::None),
+            other => Err(format!(
+                "unknown TLS_CLIENT_AUTH {:?}, expected required, optional or none",
+                other
+            )),
+        }
+    }
+
+    /// Parses `TLS_CLIENT_AUTH` for a listener with or without `TLS_CLIENT_CA_PATH`. Without a
+    /// CA bundle an empty value is `none`, and `required` or `optional` are rejected.
+    pub fn resolve(s: &str, has_client_ca: bool) -> Result<Self, String> {
+        if !has_client_ca && s.trim().is_empty() {
+            return Ok(ClientAuth::None);
+        }
+        match Self::parse(s)? {
+            ClientAuth::None => Ok(ClientAuth::None),
+            _ if !has_client_ca => Err(format!(
+                "TLS_CLIENT_AUTH {:?} requires TLS_CLIENT_CA_PATH",
+                s.trim()
+            )),
+            client_auth => Ok(client_auth),
+        }
+    }
+}
+
+/// HTTPS settings read from the `TLS_*` variables; TLS is enabled by `TLS_CERT_PATH`.
+pub struct TlsConfig {
+    pub cert_path: PathBuf,
+    pub key_path: PathBuf,
+    /// CA bundle client certificates are verified against
+    pub client_ca_path: Option<PathBuf>,
+    pub client_auth: ClientAuth,
+    /// Client certificate common name to the scopes it grants without an API key
+    pub client_cert_scopes: HashMap<String, Vec<ApiScope>>,
+}
+
+impl TlsConfig {
+    pub fn from_env() -> Option<Self> {
+        let path = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .filter(|v| !v.trim().is_empty())
+                .map(PathBuf::from)
+        };
+        let cert_path = path("TLS_CERT_PATH")?;
+        let key_path =
+            path("TLS_KEY_PATH").expect("❌ TLS_KEY_PATH is required with TLS_CERT_PATH");
+        let client_ca_path = path("TLS_CLIENT_CA_PATH");
+        let client_auth = ClientAuth::resolve(
+            &std::env::var("TLS_CLIENT_AUTH").unwrap_or_default(),
+            client_ca_path.is_some(),
+        )
+        .unwrap_or_else(|e| panic!("❌ {}", e));
+        let client_cert_scopes =
+            parse_client_cert_scopes(&std::env::var("TLS_CLIENT_CERT_SCOPES").unwrap_or_default())
+                .unwrap_or_else(|e| panic!("❌ Invalid TLS_CLIENT_CERT_SCOPES: {}", e));
+
+        Some(TlsConfig {
+            cert_path,
+            key_path,
+            client_ca_path,
+            client_auth,
+            client_cert_scopes,
+        })
+    }
+
+    /// rustls config serving the certificate of `resolver`, verifying client certificates
+    /// when a CA bundle is configured.
+    pub fn server_config(
+        &self,
+        resolver: Arc<ReloadableCertResolver>,
+    ) -> Result<ServerConfig, String> {
+        let provider = Arc::new(default_provider());
+        let builder = ServerConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .map_err(|e| format!("Unsupported TLS protocol versions: {}", e))?;
+
+        let builder = match (&self.client_ca_path, self.client_auth) {
+            (Some(ca_path), ClientAuth::Optional | ClientAuth::Required) => {
+                let mut roots = RootCertStore::empty();
+                for cert in read_certs(ca_path)? {
+                    roots
+                        .add(cert)
+                        .map_err(|e| format!("Invalid CA certificate: {}", e))?;
+                }
+                let verifier =
+                    WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+                let verifier = match self.client_auth {
+                    ClientAuth::Optional => verifier.allow_unauthenticated(),
+                    _ => verifier,
+                };
+                builder.with_client_cert_verifier(
+                    verifier
+                        .build()
+                        .map_err(|e| format!("Invalid client CA bundle: {}", e))?,
+                )
+            }
+            _ => builder.with_no_client_auth(),
+        };
+        Ok(builder.with_cert_resolver(resolver))
+    }
+}
+
+/// Parses `cn=scope,scope;cn=scope`, e.g. `emqx-node=hook:auth,hook:acl`.
+fn parse_client_cert_scopes(value: &str) -> Result<HashMap<String, Vec<ApiScope>>, String> {
+    let mut mapped = HashMap::new();
+    for entry in value.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let Some((common_name, scopes)) = entry.split_once('=') else {
+            return Err(format!("'{}' is not a cn=scopes entry", entry));
+        };
+        let common_name = common_name.trim();
+        if common_name.is_empty() {
+            return Err(format!("'{}' has an empty common name", entry));
+        }
+        let scopes = scopes
+            .split(',')
+            .map(str::trim)
+            .filter(|scope| !scope.is_empty())
+            .map(|scope| {
+                ApiScope::parse(scope)
+                    .ok_or_else(|| format!("unknown scope '{}' for {}", scope, common_name))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if scopes.is_empty() {
+            return Err(format!("{} has no scopes", common_name));
+        }
+        if mapped.insert(common_name.to_string(), scopes).is_some() {
+            return Err(format!("{} is mapped twice", common_name));
+        }
+    }
+    Ok(mapped)
+}
+
+/// Modification times of the certificate and key, to skip reloads when nothing changed.
+type Mtimes = (Option<SystemTime>, Option<SystemTime>);
+
+/// Serves the current certificate, replaced by `reload` without restarting the listener.
+/// Connections already open keep the certificate they were accepted with.
+#[derive(Debug)]
+pub struct ReloadableCertResolver {
+    cert_path: PathBuf,
+    key_path: PathBuf,
+    current: RwLock<(Arc<CertifiedKey>, Mtimes)>,
+}
+
+impl ReloadableCertResolver {
+    pub fn load(cert_path: &Path, key_path: &Path) -> Result<Self, String> {
+        let certified = load_certified_key(cert_path, key_path)?;
+        Ok(Self {
+            cert_path: cert_path.to_path_buf(),
+            key_path: key_path.to_path_buf(),
+            current: RwLock::new((certified, mtimes(cert_path, key_path))),
+        })
+    }
+
+    /// Reloads the certificate and key if either file changed, or always with `force`.
+    /// On failure the current certificate stays in use.
+    pub fn reload(&self, force: bool) -> Result<bool, String> {
+        let latest = mtimes(&self.cert_path, &self.key_path);
+        {
+            let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+            if !force && current.1 == latest {
+                return Ok(false);
+            }
+        }
+
+        let certified = load_certified_key(&self.cert_path, &self.key_path)?;
+        *self.current.write().unwrap_or_else(|e| e.into_inner()) = (certified, latest);
+        info!(
+            "🔒 TLS certificate reloaded from {}",
+            self.cert_path.display()
+        );
+        Ok(true)
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        let current = self.current.read().unwrap_or_else(|e| e.into_inner());
+        Some(Arc::clone(&current.0))
+    }
+}
+
+/// Verified client certificate of a TLS connection, stored in the connection data.
+#[derive(Clone, Debug)]
+pub struct ClientCertificate {
+    pub subject: String,
+    pub common_name: Option<String>,
+    /// Scopes granted by `TLS_CLIENT_CERT_SCOPES`; empty if the common name is not mapped
+    pub scopes: Vec<ApiScope>,
+}
+
+impl ClientCertificate {
+    pub fn from_der(der: &[u8], scopes: &HashMap<String, Vec<ApiScope>>) -> Option<Self> {
+        let (_, cert) = match x509_parser::parse_x509_certificate(der) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                warn!("⚠️ Failed to parse client certificate: {}", e);
+                return None;
+            }
+        };
+        let common_name = cert
+            .subject()
+            .iter_common_name()
+            .next()
+            .and_then(|cn| cn.as_str().ok())
+            .map(str::to_string);
+        let scopes = common_name
+            .as_ref()
+            .and_then(|cn| scopes.get(cn))
+            .cloned()
+            .unwrap_or_default();
+        debug!(
+            "[Infrastructure | Tls] Client certificate {} (scopes {})",
+            cert.subject(),
+            ApiScope::join(&scopes)
+        );
+        Some(Self {
+            subject: cert.subject().to_string(),
+            common_name,
+            scopes,
+        })
+    }
+}
+
+/// `HttpServer::on_connect` hook storing the verified client certificate of a TLS connection
+/// in its connection data, where `ApiKeyMiddleware` reads it.
+pub fn store_client_certificate(
+    scopes: Arc<HashMap<String, Vec<ApiScope>>>,
+) -> impl Fn(&dyn Any, &mut Extensions) + Send + Sync + 'static {
+    move |conn, data| {
+        let Some(tls) = conn.downcast_ref::<TlsStream<TcpStream>>() else {
+            return;
+        };
+        let peer = tls
+            .get_ref()
+            .1
+            .peer_certificates()
+            .and_then(|certs| certs.first());
+        if let Some(cert) = peer.and_then(|der| ClientCertificate::from_der(der, &scopes)) {
+            data.insert(cert);
+        }
+    }
+}
+
+fn mtimes(cert_path: &Path, key_path: &Path) -> Mtimes {
+    let mtime = |path: &Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+    (mtime(cert_path), mtime(key_path))
+}
+
+fn read_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| format!("Failed to read certificates from {}: {}", path.display(), e))?;
+    if certs.is_empty() {
+        return Err(format!("No certificates found in {}", path.display()));
+    }
+    Ok(certs)
+}
+
+fn load_certified_key(cert_path: &Path, key_path: &Path) -> Result<Arc<CertifiedKey>, String> {
+    let certs = read_certs(cert_path)?;
+    let key = PrivateKeyDer::from_pem_file(key_path).map_err(|e| {
+        format!(
+            "Failed to read private key from {}: {}",
+            key_path.display(),
+            e
+        )
+    })?;
+    let signing_key =
+        any_supported_type(&key).map_err(|e| format!("Unsupported private key: {}", e))?;
+    Ok(Arc::new(CertifiedKey::new(certs, signing_key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_client_auth() {
+        assert!(ClientAuth::parse("").unwrap() == ClientAuth::Required);
+        assert!(ClientAuth::parse("Required").unwrap() == ClientAuth::Required);
+        assert!(ClientAuth::parse("optional").unwrap() == ClientAuth::Optional);
+        assert!(ClientAuth::parse("none").unwrap() == ClientAuth::None);
+    }
+
+    #[test]
+    fn rejects_unknown_client_auth() {
+        assert!(ClientAuth::parse("requried").is_err());
+        assert!(ClientAuth::parse("off").is_err());
+    }
+
+    #[test]
+    fn resolves_client_auth_against_the_ca() {
+        assert!(ClientAuth::resolve("", true).unwrap() == ClientAuth::Required);
+        assert!(ClientAuth::resolve("optional", true).unwrap() == ClientAuth::Optional);
+        assert!(ClientAuth::resolve("", false).unwrap() == ClientAuth::None);
+        assert!(ClientAuth::resolve("none", false).unwrap() == ClientAuth::None);
+        assert!(ClientAuth::resolve("required", false).is_err());
+        assert!(ClientAuth::resolve("optional", false).is_err());
+        assert!(ClientAuth::resolve("requried", false).is_err());
+    }
+
+    #[test]
+    fn parses_client_cert_scopes() {
+        let mapped =
+            parse_client_cert_scopes(" emqx-node=hook:auth, hook:acl ; ops=users:read;").unwrap();
+        assert_eq!(
+            mapped["emqx-node"],
+            vec![ApiScope::HookAuth, ApiScope::HookAcl]
+        );
+        assert_eq!(mapped["ops"], vec![ApiScope::UsersRead]);
+        assert!(parse_client_cert_scopes("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_client_cert_scopes() {
+        for value in [
+            "emqx-node",
+            "=hook:auth",
+            "emqx-node=",
+            "emqx-node=hook:auth,hook:acls",
+            "a=admin;a=users:read",
+        ] {
+            assert!(parse_client_cert_scopes(value).is_err(), "{value}");
+        }
+    }
+}
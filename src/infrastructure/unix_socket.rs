@@ -0,0 +1,65 @@
+use log::{info, warn};
+use std::io;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::Path;
+
+/// Removes a socket file left behind by a previous run, so binding does not fail with
+/// "address in use". Anything that is not a socket is left alone.
+pub fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => {
+            warn!("⚠️ Removing stale Unix socket {}", path.display());
+            std::fs::remove_file(path)
+        }
+        Ok(_) => Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} exists and is not a socket", path.display()),
+        )),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses `HOOK_UNIX_SOCKET_MODE`, an octal mode such as `660` or `0o600`; empty means `0o660`.
+pub fn parse_socket_mode(value: &str) -> Result<u32, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(0o660);
+    }
+    u32::from_str_radix(value.strip_prefix("0o").unwrap_or(value), 8)
+        .ok()
+        .filter(|mode| *mode <= 0o777)
+        .ok_or_else(|| {
+            format!(
+                "HOOK_UNIX_SOCKET_MODE {:?} must be an octal mode between 000 and 777",
+                value
+            )
+        })
+}
+
+/// Restricts who can connect to the socket, e.g. `0o660` for the owner and its group.
+pub fn set_socket_mode(path: &Path, mode: u32) -> io::Result<()> {
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode))?;
+    info!("🔌 Unix socket {} mode set to {:o}", path.display(), mode);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_octal_socket_modes() {
+        assert_eq!(parse_socket_mode(""), Ok(0o660));
+        assert_eq!(parse_socket_mode(" 600 "), Ok(0o600));
+        assert_eq!(parse_socket_mode("0o777"), Ok(0o777));
+        assert_eq!(parse_socket_mode("0"), Ok(0));
+    }
+
+    #[test]
+    fn rejects_malformed_socket_modes() {
+        for value in ["1000", "689", "rw-rw----", "-1", "0x1ff", "4294967296"] {
+            assert!(parse_socket_mode(value).is_err(), "{value}");
+        }
+    }
+}
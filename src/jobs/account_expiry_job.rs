@@ -0,0 +1,37 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::services::expire_mqtt_service::{ExpireMqttService, ExpiryAction};
+
+/// Spawns the periodic expiry sweep. Returns `None` when the action is `none`.
+pub fn spawn_account_expiry_job(
+    service: Arc<ExpireMqttService>,
+    interval: Duration,
+) -> Option<JoinHandle<()>> {
+    if service.action() == ExpiryAction::None {
+        return None;
+    }
+
+    info!(
+        "⏰ Account expiry job started (action={}, interval={:?})",
+        service.action().as_str(),
+        interval
+    );
+    Some(tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match service.sweep_expired().await {
+                Ok(0) => {}
+                Ok(affected) => info!(
+                    "⏰ Account expiry job applied '{}' to {} expired user(s)",
+                    service.action().as_str(),
+                    affected
+                ),
+                Err(e) => error!("❌ Account expiry job failed: {}", e),
+            }
+        }
+    }))
+}
@@ -0,0 +1,26 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::services::api_key_service::ApiKeyService;
+
+/// Spawns the periodic flush of buffered `last_used_at` times and reload of the API key cache,
+/// so keys created or revoked through another instance take effect here too.
+pub fn spawn_api_key_reload_job(service: Arc<ApiKeyService>, interval: Duration) -> JoinHandle<()> {
+    info!("⏰ API key reload job started (interval={:?})", interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; the cache was just loaded at startup.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = service.flush_last_used().await {
+                error!("❌ API key last-used flush failed: {}", e);
+            }
+            if let Err(e) = service.reload().await {
+                error!("❌ API key reload failed: {}", e);
+            }
+        }
+    })
+}
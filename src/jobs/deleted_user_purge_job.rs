@@ -0,0 +1,29 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::services::delete_mqtt_service::DeleteMqttService;
+
+/// Spawns the periodic purge of users whose trash retention period has passed.
+pub fn spawn_deleted_user_purge_job(
+    service: Arc<DeleteMqttService>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    info!(
+        "⏰ Deleted user purge job started (retention={}s, interval={:?})",
+        service.retention().num_seconds(),
+        interval
+    );
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            match service.purge_deleted().await {
+                Ok(0) => {}
+                Ok(purged) => info!("⏰ Deleted user purge job removed {} user(s)", purged),
+                Err(e) => error!("❌ Deleted user purge job failed: {}", e),
+            }
+        }
+    })
+}
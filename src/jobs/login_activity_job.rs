@@ -0,0 +1,23 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::services::login_activity_service::LoginActivityService;
+
+/// Spawns the periodic flush of buffered `last_login_at` / `last_login_ip` updates.
+pub fn spawn_login_activity_job(
+    service: Arc<LoginActivityService>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    info!("⏰ Login activity job started (interval={:?})", interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = service.flush().await {
+                error!("❌ Login activity flush failed: {}", e);
+            }
+        }
+    })
+}
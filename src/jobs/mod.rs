@@ -0,0 +1,6 @@
+pub mod api_key_reload_job;
+pub mod account_expiry_job;
+pub mod deleted_user_purge_job;
+pub mod login_activity_job;
+pub mod tenant_reload_job;
+pub mod tls_reload_job;
@@ -0,0 +1,23 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::services::tenant_service::TenantService;
+
+/// Spawns the periodic reload of the tenant cache, so quota changes and rotated tenant keys
+/// made through another instance take effect here too.
+pub fn spawn_tenant_reload_job(service: Arc<TenantService>, interval: Duration) -> JoinHandle<()> {
+    info!("⏰ Tenant reload job started (interval={:?})", interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; the cache was just loaded at startup.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = service.reload().await {
+                error!("❌ Tenant reload failed: {}", e);
+            }
+        }
+    })
+}
@@ -0,0 +1,26 @@
+use log::{error, info};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+use crate::infrastructure::tls::ReloadableCertResolver;
+
+/// Spawns the periodic check of the TLS certificate and key files, reloading them when
+/// either changed, e.g. after a renewal by cert-manager or certbot.
+pub fn spawn_tls_reload_job(
+    resolver: Arc<ReloadableCertResolver>,
+    interval: Duration,
+) -> JoinHandle<()> {
+    info!("⏰ TLS reload job started (interval={:?})", interval);
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick fires immediately; the certificate was just loaded at startup.
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if let Err(e) = resolver.reload(false) {
+                error!("❌ TLS certificate reload failed: {}", e);
+            }
+        }
+    })
+}
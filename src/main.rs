@@ -1,7 +1,9 @@
+mod cli;
 mod dtos;
 mod entities;
 mod handler;
 mod infrastructure;
+mod jobs;
 mod middleware;
 mod repositories;
 mod server;
@@ -10,5 +12,15 @@ mod utils;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    server::run_server().await
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("export") => {
+            if let Err(e) = cli::run_export(&args[1..]).await {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        _ => server::run_server().await,
+    }
 }
@@ -1,16 +1,60 @@
+use crate::dtos::api_key_dto::{ApiKeyContext, ApiScope};
 use crate::dtos::response_dto::ErrorResponseDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::infrastructure::tls::ClientCertificate;
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::request_signing_service::{RequestSigningService, SignedRequest};
+use crate::services::tenant_service::{DEFAULT_TENANT, TenantService};
 use actix_web::{
-    Error, HttpResponse,
+    Error, HttpMessage, HttpResponse,
     body::EitherBody,
-    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform},
     http::header,
+    web,
 };
+use futures_util::StreamExt;
 use futures_util::future::{LocalBoxFuture, Ready, ok};
 use log::debug;
-use std::env;
+use std::rc::Rc;
+use std::sync::Arc;
 
+/// Header a caller holding the global API key uses to pick the tenant it acts on.
+pub const TENANT_HEADER: &str = "X-Tenant";
+
+/// Headers of an HMAC-signed request; see `RequestSigningService`.
+pub const SIGNATURE_HEADER: &str = "X-Signature";
+pub const SIGNATURE_KEY_HEADER: &str = "X-Signature-Key";
+pub const SIGNATURE_TIMESTAMP_HEADER: &str = "X-Signature-Timestamp";
+pub const SIGNATURE_NONCE_HEADER: &str = "X-Signature-Nonce";
+
+/// Signed bodies are buffered to be hashed; hook calls are far smaller than this.
+const MAX_SIGNED_BODY_BYTES: usize = 64 * 1024;
+
+/// Authorizes requests with a static key (`API_KEY`, its secondaries or `API_KEY_FILE`), a key
+/// from `mqtt_api_keys`, a tenant's own key, when it carries `X-Signature` an HMAC request
+/// signature, or, without any key, a client certificate mapped by `TLS_CLIENT_CERT_SCOPES`.
+/// Stores the resolved `TenantContext` and `ApiKeyContext` in the request extensions. Scopes
+/// are checked per route by `RequireScope`.
 #[derive(Clone)]
-pub struct ApiKeyMiddleware;
+pub struct ApiKeyMiddleware {
+    tenants: Arc<TenantService>,
+    api_keys: Arc<ApiKeyService>,
+    signing: Arc<RequestSigningService>,
+}
+
+impl ApiKeyMiddleware {
+    pub fn new(
+        tenants: Arc<TenantService>,
+        api_keys: Arc<ApiKeyService>,
+        signing: Arc<RequestSigningService>,
+    ) -> Self {
+        Self {
+            tenants,
+            api_keys,
+            signing,
+        }
+    }
+}
 
 impl<S, B> Transform<S, ServiceRequest> for ApiKeyMiddleware
 where
@@ -24,15 +68,21 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        let api_key = env::var("API_KEY").unwrap_or_default();
-        ok(ApiKeyMiddlewareService { service, api_key })
+        ok(ApiKeyMiddlewareService {
+            service: Rc::new(service),
+            tenants: Arc::clone(&self.tenants),
+            api_keys: Arc::clone(&self.api_keys),
+            signing: Arc::clone(&self.signing),
+        })
     }
 }
 
 #[derive(Clone)]
 pub struct ApiKeyMiddlewareService<S> {
-    service: S,
-    api_key: String,
+    service: Rc<S>,
+    tenants: Arc<TenantService>,
+    api_keys: Arc<ApiKeyService>,
+    signing: Arc<RequestSigningService>,
 }
 
 impl<S, B> Service<ServiceRequest> for ApiKeyMiddlewareService<S>
@@ -52,7 +102,6 @@ where
     }
 
     fn call(&self, req: ServiceRequest) -> Self::Future {
-        let expected_key = self.api_key.clone();
         let path = req.path().to_string();
         let has_auth_header = req.headers().get(header::AUTHORIZATION).is_some();
         let auth_value = req
@@ -60,55 +109,223 @@ where
             .get(header::AUTHORIZATION)
             .and_then(|v| v.to_str().ok())
             .map(str::trim)
-            .unwrap_or("");
+            .unwrap_or("")
+            .to_string();
+        let requested_tenant = req
+            .headers()
+            .get(TENANT_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty());
         debug!(
-            "[Middleware | ApiKey] call - path='{}' auth_header_present={} auth_len={} expected_key_set={}",
+            "[Middleware | ApiKey] call - path='{}' auth_header_present={} auth_len={}",
             path,
             has_auth_header,
-            auth_value.len(),
-            !expected_key.is_empty()
+            auth_value.len()
         );
 
-        let is_valid = !expected_key.is_empty() && is_authorized(auth_value, &expected_key);
-        if !is_valid {
-            debug!("[Middleware | ApiKey] Unauthorized request to '{}'", path);
-            let res = HttpResponse::Unauthorized()
-                .insert_header((header::CONTENT_TYPE, "application/json"))
-                .json(ErrorResponseDTO {
-                    success: false,
-                    message: "Unauthorized",
-                    details: None::<()>,
-                    result: None,
-                });
-            return Box::pin(async move { Ok(req.into_response(res.map_into_right_body())) });
-        }
-
-        debug!("[Middleware | ApiKey] Authorized request to '{}'", path);
-        let fut = self.service.call(req);
+        let service = Rc::clone(&self.service);
+        let tenants = Arc::clone(&self.tenants);
+        let api_keys = Arc::clone(&self.api_keys);
+        let signing = Arc::clone(&self.signing);
+        let signed = signing.is_enabled() && req.headers().contains_key(SIGNATURE_HEADER);
         Box::pin(async move {
-            let res = fut.await?;
-            Ok(res.map_into_left_body())
+            let mut req = req;
+            let token = bearer_token(&auth_value);
+            let client_cert = req
+                .conn_data::<ClientCertificate>()
+                .filter(|cert| !cert.scopes.is_empty())
+                .cloned();
+            let authorized = if signed {
+                match verify_signature(&mut req, &signing).await {
+                    Ok(key) => global_tenant(&tenants, requested_tenant.as_deref())
+                        .await
+                        .map(|tenant| (tenant, key)),
+                    Err(rejection) => Err(rejection),
+                }
+            } else if let Some(key) = api_keys.authenticate(token).await {
+                match key.tenant_id {
+                    Some(tenant_id) => match tenants.by_id(tenant_id).await {
+                        Some(tenant)
+                            if requested_tenant
+                                .as_deref()
+                                .is_some_and(|n| n != tenant.name) =>
+                        {
+                            Err(Rejection::OtherTenant)
+                        }
+                        Some(tenant) => Ok((tenant, key.context)),
+                        None => Err(Rejection::Unauthorized),
+                    },
+                    None => global_tenant(&tenants, requested_tenant.as_deref())
+                        .await
+                        .map(|tenant| (tenant, key.context)),
+                }
+            } else {
+                match tenants.by_api_key(token).await {
+                    Some(tenant)
+                        if requested_tenant
+                            .as_deref()
+                            .is_some_and(|n| n != tenant.name) =>
+                    {
+                        Err(Rejection::OtherTenant)
+                    }
+                    Some(tenant) => {
+                        let key = tenant_key_context(&tenant.name);
+                        Ok((tenant, key))
+                    }
+                    // Without any key, a mapped client certificate authorizes the request
+                    None if token.is_empty() => match client_cert {
+                        Some(cert) => global_tenant(&tenants, requested_tenant.as_deref())
+                            .await
+                            .map(|tenant| (tenant, client_cert_context(cert))),
+                        None => Err(Rejection::Unauthorized),
+                    },
+                    None => Err(Rejection::Unauthorized),
+                }
+            };
+
+            match authorized {
+                Ok((tenant, key)) => {
+                    debug!(
+                        "[Middleware | ApiKey] Authorized request to '{}' with key '{}' for tenant '{}' (scoped={})",
+                        path, key.name, tenant.name, tenant.scoped
+                    );
+                    req.extensions_mut().insert(tenant);
+                    req.extensions_mut().insert(key);
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_left_body())
+                }
+                Err(rejection) => {
+                    debug!(
+                        "[Middleware | ApiKey] Rejected request to '{}': {}",
+                        path,
+                        rejection.message()
+                    );
+                    let res = rejection
+                        .response()
+                        .insert_header((header::CONTENT_TYPE, "application/json"))
+                        .json(ErrorResponseDTO {
+                            success: false,
+                            message: rejection.message(),
+                            details: None::<()>,
+                            result: None,
+                        });
+                    Ok(req.into_response(res.map_into_right_body()))
+                }
+            }
         })
     }
 }
 
-fn is_authorized(header_value: &str, expected_key: &str) -> bool {
-    if header_value == expected_key {
-        return true;
+enum Rejection {
+    Unauthorized,
+    UnknownTenant,
+    OtherTenant,
+    InvalidSignature(String),
+    SignedBodyTooLarge,
+}
+
+impl Rejection {
+    fn response(&self) -> actix_web::HttpResponseBuilder {
+        match self {
+            Rejection::Unauthorized | Rejection::InvalidSignature(_) => {
+                HttpResponse::Unauthorized()
+            }
+            Rejection::UnknownTenant => HttpResponse::NotFound(),
+            Rejection::OtherTenant => HttpResponse::Forbidden(),
+            Rejection::SignedBodyTooLarge => HttpResponse::PayloadTooLarge(),
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            Rejection::Unauthorized => "Unauthorized",
+            Rejection::UnknownTenant => "Tenant not found",
+            Rejection::OtherTenant => "API key is scoped to another tenant",
+            Rejection::InvalidSignature(message) => message,
+            Rejection::SignedBodyTooLarge => "Signed request body is too large",
+        }
+    }
+}
+
+/// Tenant of a key not limited to one: the `X-Tenant` header, or the default tenant.
+async fn global_tenant(
+    tenants: &TenantService,
+    requested: Option<&str>,
+) -> Result<TenantContext, Rejection> {
+    tenants
+        .by_name(requested.unwrap_or(DEFAULT_TENANT))
+        .await
+        .ok_or(Rejection::UnknownTenant)
+}
+
+/// Buffers the body to verify the request signature, then puts it back for the handler.
+async fn verify_signature(
+    req: &mut ServiceRequest,
+    signing: &RequestSigningService,
+) -> Result<ApiKeyContext, Rejection> {
+    let mut payload = req.take_payload();
+    let mut body = web::BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk =
+            chunk.map_err(|_| Rejection::InvalidSignature("Failed to read request body".into()))?;
+        if body.len() + chunk.len() > MAX_SIGNED_BODY_BYTES {
+            return Err(Rejection::SignedBodyTooLarge);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body = body.freeze();
+    req.set_payload(Payload::from(body.clone()));
+
+    let header = |name: &str| {
+        req.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(str::trim)
+            .unwrap_or("")
+    };
+    let path_and_query = req
+        .uri()
+        .path_and_query()
+        .map(|pq| pq.as_str())
+        .unwrap_or_else(|| req.path());
+    signing
+        .verify(&SignedRequest {
+            key_id: header(SIGNATURE_KEY_HEADER),
+            timestamp: header(SIGNATURE_TIMESTAMP_HEADER),
+            nonce: header(SIGNATURE_NONCE_HEADER),
+            signature: header(SIGNATURE_HEADER),
+            method: req.method().as_str(),
+            path_and_query,
+            body: &body,
+        })
+        .map_err(|e| Rejection::InvalidSignature(e.to_string()))
+}
+
+/// A tenant's own key grants every scope but `admin` within the tenant.
+fn tenant_key_context(tenant_name: &str) -> ApiKeyContext {
+    ApiKeyContext {
+        name: format!("tenant:{}", tenant_name),
+        scopes: ApiScope::ALL
+            .into_iter()
+            .filter(|scope| *scope != ApiScope::Admin)
+            .collect(),
     }
+}
 
-    if let Some(token) = header_value
-        .strip_prefix("Bearer ")
-        .or_else(|| header_value.strip_prefix("bearer "))
-    {
-        return token.trim() == expected_key;
+/// A client certificate mapped by `TLS_CLIENT_CERT_SCOPES` grants the mapped scopes.
+fn client_cert_context(cert: ClientCertificate) -> ApiKeyContext {
+    ApiKeyContext {
+        name: format!("cert:{}", cert.common_name.unwrap_or(cert.subject)),
+        scopes: cert.scopes,
     }
+}
 
+/// The key in an `Authorization` header, with or without a `Bearer` scheme.
+fn bearer_token(header_value: &str) -> &str {
     let mut parts = header_value.split_whitespace();
-    match (parts.next(), parts.next()) {
-        (Some(scheme), Some(token)) => {
-            scheme.eq_ignore_ascii_case("bearer") && token.trim() == expected_key
-        }
-        _ => false,
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(scheme), Some(token), None) if scheme.eq_ignore_ascii_case("bearer") => token,
+        _ => header_value,
     }
 }
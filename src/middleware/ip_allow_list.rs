@@ -0,0 +1,108 @@
+use crate::dtos::response_dto::ErrorResponseDTO;
+use crate::middleware::route_group::RouteGroup;
+use crate::services::ip_allow_list_service::IpAllowListService;
+use crate::utils::client_ip::ClientIp;
+use actix_web::{
+    Error, HttpMessage, HttpResponse,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ok};
+use log::debug;
+use std::rc::Rc;
+use std::sync::Arc;
+
+const FORWARDED_FOR_HEADER: &str = "X-Forwarded-For";
+
+/// Resolves the client address, stores it as `ClientIp` in the request extensions and rejects
+/// it if the request's route group does not allow it. Wraps a scope outside `ApiKeyMiddleware`,
+/// so a rejected address never reaches key lookup. Unix socket connections have no address and
+/// are left to the socket's permissions.
+#[derive(Clone)]
+pub struct IpAllowListMiddleware {
+    allow_list: Arc<IpAllowListService>,
+}
+
+impl IpAllowListMiddleware {
+    pub fn new(allow_list: Arc<IpAllowListService>) -> Self {
+        Self { allow_list }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for IpAllowListMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = IpAllowListMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(IpAllowListMiddlewareService {
+            service: Rc::new(service),
+            allow_list: Arc::clone(&self.allow_list),
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct IpAllowListMiddlewareService<S> {
+    service: Rc<S>,
+    allow_list: Arc<IpAllowListService>,
+}
+
+impl<S, B> Service<ServiceRequest> for IpAllowListMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let client_ip = req.peer_addr().map(|peer| {
+            let forwarded_for = req
+                .headers()
+                .get(FORWARDED_FOR_HEADER)
+                .and_then(|v| v.to_str().ok());
+            self.allow_list.client_ip(peer.ip(), forwarded_for)
+        });
+        let group = RouteGroup::of(&req);
+
+        if let Some(client_ip) = client_ip {
+            if !self.allow_list.is_allowed(group, client_ip) {
+                debug!(
+                    "[Middleware | IpAllowList] Rejected {} request to '{}' from {}",
+                    group.as_str(),
+                    req.path(),
+                    client_ip
+                );
+                let res = HttpResponse::Forbidden().json(ErrorResponseDTO {
+                    success: false,
+                    message: "Client address is not allowed",
+                    details: None::<()>,
+                    result: None,
+                });
+                return Box::pin(async move { Ok(req.into_response(res.map_into_right_body())) });
+            }
+            req.extensions_mut().insert(ClientIp(client_ip));
+        }
+
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
@@ -1,5 +1,6 @@
+use crate::utils::client_ip::ClientIp;
 use actix_web::{
-    Error,
+    Error, HttpMessage,
     dev::{Service, ServiceRequest, ServiceResponse, Transform},
 };
 use futures_util::future::LocalBoxFuture;
@@ -62,6 +63,13 @@ where
             let res = svc.call(req).await?;
             let duration = start.elapsed();
             let status = res.response().status().as_u16();
+            // Prefer the address resolved against TRUSTED_PROXIES by IpAllowListMiddleware
+            let peer_ip = res
+                .request()
+                .extensions()
+                .get::<ClientIp>()
+                .map(|ip| ip.0.to_string())
+                .unwrap_or(peer_ip);
 
             info!(
                 "[{}] {} {} | {} | {:?}",
@@ -1,3 +1,8 @@
 pub mod api_key;
+pub mod ip_allow_list;
 pub mod logger_request;
 pub mod powered_by;
+pub mod rate_limit;
+pub mod request_id;
+pub mod require_scope;
+pub mod route_group;
@@ -0,0 +1,173 @@
+use crate::dtos::api_key_dto::ApiKeyContext;
+use crate::dtos::response_dto::ErrorResponseDTO;
+use crate::middleware::route_group::RouteGroup;
+use crate::services::rate_limit_service::{RateLimitDecision, RateLimitService};
+use crate::utils::client_ip::ClientIp;
+use actix_web::{
+    Error, HttpMessage, HttpResponse,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    http::header::{self, HeaderName, HeaderValue},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ok};
+use log::debug;
+use std::rc::Rc;
+use std::sync::Arc;
+
+/// Headers reporting the bucket closest to running out: its burst size and tokens left.
+pub const RATE_LIMIT_LIMIT_HEADER: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+pub const RATE_LIMIT_REMAINING_HEADER: HeaderName =
+    HeaderName::from_static("x-ratelimit-remaining");
+
+/// Which bucket of the request's route group a `RateLimitMiddleware` charges.
+#[derive(Clone, Copy, PartialEq)]
+enum RateLimitBy {
+    ClientIp,
+    ApiKey,
+}
+
+/// Applies the `RateLimitService` limits of the request's route group.
+///
+/// The per-IP layer wraps a scope outside `ApiKeyMiddleware`, so requests with a bad or
+/// missing key are charged too and key guessing from one address is limited. The per-key
+/// layer wraps it inside, where the API key resolved there is known.
+#[derive(Clone)]
+pub struct RateLimitMiddleware {
+    limits: Arc<RateLimitService>,
+    by: RateLimitBy,
+}
+
+impl RateLimitMiddleware {
+    pub fn per_ip(limits: Arc<RateLimitService>) -> Self {
+        Self {
+            limits,
+            by: RateLimitBy::ClientIp,
+        }
+    }
+
+    pub fn per_key(limits: Arc<RateLimitService>) -> Self {
+        Self {
+            limits,
+            by: RateLimitBy::ApiKey,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RateLimitMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RateLimitMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RateLimitMiddlewareService {
+            service: Rc::new(service),
+            limits: Arc::clone(&self.limits),
+            by: self.by,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitMiddlewareService<S> {
+    service: Rc<S>,
+    limits: Arc<RateLimitService>,
+    by: RateLimitBy,
+}
+
+impl<S, B> Service<ServiceRequest> for RateLimitMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        if !self.limits.is_enabled() {
+            return Box::pin(async move {
+                let res = service.call(req).await?;
+                Ok(res.map_into_left_body())
+            });
+        }
+
+        let group = RouteGroup::of(&req);
+        let decision = match self.by {
+            // Unix socket connections have no client address and are only limited per key
+            RateLimitBy::ClientIp => {
+                let client_ip = req
+                    .extensions()
+                    .get::<ClientIp>()
+                    .map(|ip| ip.0.to_string());
+                self.limits.check(group, None, client_ip.as_deref())
+            }
+            RateLimitBy::ApiKey => {
+                let key_name = req
+                    .extensions()
+                    .get::<ApiKeyContext>()
+                    .map(|key| key.name.clone());
+                self.limits.check(group, key_name.as_deref(), None)
+            }
+        };
+
+        Box::pin(async move {
+            match decision {
+                RateLimitDecision::Unlimited => {
+                    let res = service.call(req).await?;
+                    Ok(res.map_into_left_body())
+                }
+                RateLimitDecision::Allowed { limit, remaining } => {
+                    let mut res = service.call(req).await?;
+                    let headers = res.headers_mut();
+                    // The inner layer may already have reported a bucket closer to running out
+                    let tighter = headers
+                        .get(RATE_LIMIT_REMAINING_HEADER)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| v.parse::<u64>().ok())
+                        .is_some_and(|reported| reported <= remaining);
+                    if !tighter {
+                        headers.insert(RATE_LIMIT_LIMIT_HEADER, HeaderValue::from(limit));
+                        headers.insert(RATE_LIMIT_REMAINING_HEADER, HeaderValue::from(remaining));
+                    }
+                    Ok(res.map_into_left_body())
+                }
+                RateLimitDecision::Limited {
+                    limit,
+                    retry_after_secs,
+                } => {
+                    debug!(
+                        "[Middleware | RateLimit] Rejected {} request to '{}' (retry after {}s)",
+                        group.as_str(),
+                        req.path(),
+                        retry_after_secs
+                    );
+                    let res = HttpResponse::TooManyRequests()
+                        .insert_header((header::RETRY_AFTER, retry_after_secs))
+                        .insert_header((RATE_LIMIT_LIMIT_HEADER, limit))
+                        .insert_header((RATE_LIMIT_REMAINING_HEADER, 0))
+                        .json(ErrorResponseDTO {
+                            success: false,
+                            message: "Too many requests",
+                            details: None::<()>,
+                            result: None,
+                        });
+                    Ok(req.into_response(res.map_into_right_body()))
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,87 @@
+use crate::dtos::api_key_dto::{ApiKeyContext, ApiScope};
+use crate::dtos::response_dto::ErrorResponseDTO;
+use actix_web::{
+    Error, HttpMessage, HttpResponse,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+};
+use futures_util::future::{LocalBoxFuture, Ready, ok};
+use log::debug;
+use std::rc::Rc;
+
+/// Rejects requests whose API key, as resolved by `ApiKeyMiddleware`, lacks `scope`.
+/// Wraps a single route, e.g. `web::post().to(handler).wrap(RequireScope(ApiScope::UsersWrite))`.
+#[derive(Clone, Copy)]
+pub struct RequireScope(pub ApiScope);
+
+impl<S, B> Transform<S, ServiceRequest> for RequireScope
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = RequireScopeService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(RequireScopeService {
+            service: Rc::new(service),
+            scope: self.0,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct RequireScopeService<S> {
+    service: Rc<S>,
+    scope: ApiScope,
+}
+
+impl<S, B> Service<ServiceRequest> for RequireScopeService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(
+        &self,
+        ctx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(ctx)
+    }
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let scope = self.scope;
+        let allowed = req
+            .extensions()
+            .get::<ApiKeyContext>()
+            .is_some_and(|key| key.allows(scope));
+
+        if !allowed {
+            debug!(
+                "[Middleware | RequireScope] Rejected request to '{}': missing scope {}",
+                req.path(),
+                scope.as_str()
+            );
+            let message = format!("API key lacks the {} scope", scope.as_str());
+            let res = HttpResponse::Forbidden().json(ErrorResponseDTO {
+                success: false,
+                message: &message,
+                details: None::<()>,
+                result: None,
+            });
+            return Box::pin(async move { Ok(req.into_response(res.map_into_right_body())) });
+        }
+
+        let service = Rc::clone(&self.service);
+        Box::pin(async move {
+            let res = service.call(req).await?;
+            Ok(res.map_into_left_body())
+        })
+    }
+}
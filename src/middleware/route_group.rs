@@ -0,0 +1,38 @@
+use actix_web::dev::ServiceRequest;
+
+/// Route patterns of the broker hooks, as registered by `hook_routes` in `server.rs`.
+const HOOK_PATTERNS: [&str; 2] = ["/mqtt/check", "/mqtt/acl"];
+
+/// Routes that share network and rate limit settings: the broker hooks, and everything else.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RouteGroup {
+    Hook,
+    Admin,
+}
+
+impl RouteGroup {
+    pub const ALL: [RouteGroup; 2] = [RouteGroup::Hook, RouteGroup::Admin];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteGroup::Hook => "hook",
+            RouteGroup::Admin => "admin",
+        }
+    }
+
+    /// Prefix of the variables configuring this group, e.g. `RATE_LIMIT_HOOK_PER_KEY`.
+    pub fn env_name(&self) -> &'static str {
+        match self {
+            RouteGroup::Hook => "HOOK",
+            RouteGroup::Admin => "ADMIN",
+        }
+    }
+
+    /// Group of the route the request matches; unmatched requests count as admin.
+    pub fn of(req: &ServiceRequest) -> Self {
+        match req.match_pattern() {
+            Some(pattern) if HOOK_PATTERNS.contains(&pattern.as_str()) => RouteGroup::Hook,
+            _ => RouteGroup::Admin,
+        }
+    }
+}
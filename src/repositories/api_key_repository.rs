@@ -0,0 +1,112 @@
+use crate::entities::api_key_entity::{
+    ActiveModel, Column, Entity as ApiKey, Model as ApiKeyEntity,
+};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::sea_query::Expr;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Unchanged, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Set, SqlErr,
+};
+
+pub struct NewApiKey {
+    pub name: String,
+    pub tenant_id: Option<i32>,
+    pub key_hash: String,
+    pub scopes: String,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+pub struct ApiKeyRepository {
+    db: DatabaseConnection,
+}
+
+impl ApiKeyRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ApiKeyRepository { db }
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<ApiKeyEntity>, MqttRepositoryError> {
+        debug!("[Repository | ApiKey] Loading all API keys");
+        ApiKey::find()
+            .order_by_asc(Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)
+    }
+
+    /// Inserts a key; a taken name is reported as `AlreadyExists`.
+    pub async fn create_api_key(
+        &self,
+        key: NewApiKey,
+    ) -> Result<ApiKeyEntity, MqttRepositoryError> {
+        debug!("[Repository | ApiKey] Creating API key {}", key.name);
+        let name = key.name.clone();
+        let api_key = ActiveModel {
+            name: Set(key.name),
+            tenant_id: Set(key.tenant_id),
+            key_hash: Set(key.key_hash),
+            scopes: Set(key.scopes),
+            expires_at: Set(key.expires_at),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        api_key.insert(&self.db).await.map_err(|e| {
+            if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) {
+                return MqttRepositoryError::AlreadyExists;
+            }
+            error!(
+                "[Repository | ApiKey] Failed to create API key {}: {e}",
+                name
+            );
+            MqttRepositoryError::SeaOrm(e)
+        })
+    }
+
+    pub async fn revoke(
+        &self,
+        id: i32,
+        at: DateTime<Utc>,
+    ) -> Result<ApiKeyEntity, MqttRepositoryError> {
+        debug!("[Repository | ApiKey] Revoking API key id {}", id);
+        let api_key = ActiveModel {
+            id: Unchanged(id),
+            revoked_at: Set(Some(at)),
+            ..Default::default()
+        };
+
+        match api_key.update(&self.db).await {
+            Ok(updated) => Ok(updated),
+            Err(DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | ApiKey] Failed to revoke API key id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+
+    /// Writes buffered last-used times; a time never moves backwards.
+    pub async fn record_last_used(
+        &self,
+        used: &[(i32, DateTime<Utc>)],
+    ) -> Result<(), MqttRepositoryError> {
+        debug!(
+            "[Repository | ApiKey] Recording last use of {} API keys",
+            used.len()
+        );
+        for (id, at) in used {
+            ApiKey::update_many()
+                .col_expr(Column::LastUsedAt, Expr::value(*at))
+                .filter(Column::Id.eq(*id))
+                .filter(Column::LastUsedAt.is_null().or(Column::LastUsedAt.lt(*at)))
+                .exec(&self.db)
+                .await?;
+        }
+        Ok(())
+    }
+}
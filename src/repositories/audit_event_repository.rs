@@ -0,0 +1,129 @@
+use crate::entities::audit_event_entity::{
+    ActiveModel, Column, Entity as AuditEvent, Model as AuditEventEntity,
+};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, Condition, DatabaseConnection, EntityTrait, PaginatorTrait,
+    QueryFilter, QueryOrder, Set,
+};
+
+pub struct NewAuditEvent {
+    pub tenant_id: Option<i32>,
+    pub actor: String,
+    pub action: String,
+    pub target: Option<String>,
+    pub request_id: Option<String>,
+    pub source_ip: Option<String>,
+    pub changes: Option<serde_json::Value>,
+    pub outcome: String,
+    pub error: Option<String>,
+}
+
+/// Filters and paging for an audit event listing. `None` filters are not applied.
+pub struct AuditEventFilter {
+    pub tenant_id: Option<i32>,
+    pub actor: Option<String>,
+    pub action: Option<String>,
+    pub target: Option<String>,
+    pub outcome: Option<String>,
+    pub request_id: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+    pub page: u64,
+    pub per_page: u64,
+}
+
+/// Append-only: events are inserted and listed, never updated or deleted.
+pub struct AuditEventRepository {
+    db: DatabaseConnection,
+}
+
+impl AuditEventRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        AuditEventRepository { db }
+    }
+
+    pub async fn insert(&self, event: NewAuditEvent) -> Result<(), MqttRepositoryError> {
+        debug!(
+            "[Repository | AuditEvent] Recording {} on {:?} by {}",
+            event.action, event.target, event.actor
+        );
+        let audit_event = ActiveModel {
+            tenant_id: Set(event.tenant_id),
+            actor: Set(event.actor),
+            action: Set(event.action),
+            target: Set(event.target),
+            request_id: Set(event.request_id),
+            source_ip: Set(event.source_ip),
+            changes: Set(event.changes),
+            outcome: Set(event.outcome),
+            error: Set(event.error),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        audit_event.insert(&self.db).await.map(|_| ()).map_err(|e| {
+            error!("[Repository | AuditEvent] Failed to record audit event: {e}");
+            MqttRepositoryError::SeaOrm(e)
+        })
+    }
+
+    /// Returns the requested page (1-based), newest first, with the total item and page counts.
+    pub async fn get_audit_events(
+        &self,
+        filter: AuditEventFilter,
+    ) -> Result<(Vec<AuditEventEntity>, u64, u64), MqttRepositoryError> {
+        debug!(
+            "[Repository | AuditEvent] Fetching audit event page {} (per_page={})",
+            filter.page, filter.per_page
+        );
+
+        let mut condition = Condition::all();
+        if let Some(tenant_id) = filter.tenant_id {
+            condition = condition.add(Column::TenantId.eq(tenant_id));
+        }
+        if let Some(actor) = filter.actor {
+            condition = condition.add(Column::Actor.eq(actor));
+        }
+        if let Some(action) = filter.action {
+            condition = condition.add(Column::Action.eq(action));
+        }
+        if let Some(target) = filter.target {
+            condition = condition.add(Column::Target.eq(target));
+        }
+        if let Some(outcome) = filter.outcome {
+            condition = condition.add(Column::Outcome.eq(outcome));
+        }
+        if let Some(request_id) = filter.request_id {
+            condition = condition.add(Column::RequestId.eq(request_id));
+        }
+        if let Some(from) = filter.from {
+            condition = condition.add(Column::CreatedAt.gte(from));
+        }
+        if let Some(until) = filter.until {
+            condition = condition.add(Column::CreatedAt.lt(until));
+        }
+
+        let paginator = AuditEvent::find()
+            .filter(condition)
+            .order_by_desc(Column::Id)
+            .paginate(&self.db, filter.per_page);
+        let totals = paginator
+            .num_items_and_pages()
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        let events = paginator
+            .fetch_page(filter.page.saturating_sub(1))
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        debug!(
+            "[Repository | AuditEvent] Successfully fetched {} of {} audit events",
+            events.len(),
+            totals.number_of_items
+        );
+        Ok((events, totals.number_of_items, totals.number_of_pages))
+    }
+}
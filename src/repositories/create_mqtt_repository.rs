@@ -1,7 +1,11 @@
 use crate::entities::mqtt_entity::{ActiveModel, Entity as MqttUser};
+use crate::repositories::get_mqtt_client_ids_repository::replace_client_ids;
+use crate::repositories::get_mqtt_tags_repository::replace_tags;
+use crate::repositories::import_mqtt_repository::NewMqttUser;
 use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::Utc;
 use log::{debug, error};
-use sea_orm::{DatabaseConnection, EntityTrait, Set};
+use sea_orm::{DatabaseConnection, EntityTrait, Set, TransactionTrait};
 
 pub struct CreateMqttRepository {
     db: DatabaseConnection,
@@ -12,26 +16,47 @@ impl CreateMqttRepository {
         CreateMqttRepository { db }
     }
 
+    /// Inserts the user together with its tags and client ID bindings in one transaction.
     pub async fn create_mqtt(
         &self,
-        username: &str,
-        password_hash: &str,
-        is_superuser: bool,
+        user: NewMqttUser,
+        tags: &[String],
+        client_ids: &[String],
     ) -> Result<(), MqttRepositoryError> {
         debug!(
             "[Repository | CreateMQTT] Starting user MQTT creation for username: {}",
-            username
+            user.username
         );
 
+        let username = user.username.clone();
+        let now = Utc::now();
         let new_user = ActiveModel {
-            username: Set(username.to_owned()),
-            password: Set(password_hash.to_owned()),
-            is_superuser: Set(is_superuser),
+            tenant_id: Set(user.tenant_id),
+            username: Set(user.username),
+            password: Set(user.password),
+            is_superuser: Set(user.is_superuser),
+            is_enabled: Set(user.is_enabled),
+            metadata: Set(user.metadata),
+            valid_from: Set(user.valid_from),
+            valid_until: Set(user.valid_until),
+            provision_batch_id: Set(user.provision_batch_id),
+            created_at: Set(now),
+            updated_at: Set(now),
             ..Default::default()
         };
 
-        match MqttUser::insert(new_user).exec(&self.db).await {
-            Ok(_) => {
+        let result = async {
+            let txn = self.db.begin().await?;
+            let inserted = MqttUser::insert(new_user).exec(&txn).await?;
+            replace_tags(&txn, inserted.last_insert_id, tags).await?;
+            replace_client_ids(&txn, inserted.last_insert_id, client_ids).await?;
+            txn.commit().await?;
+            Ok::<_, MqttRepositoryError>(())
+        }
+        .await;
+
+        match result {
+            Ok(()) => {
                 debug!(
                     "[Repository | CreateMQTT] User MQTT {} successfully written to MySQL",
                     username
@@ -43,7 +68,7 @@ impl CreateMqttRepository {
                     "[Repository | CreateMQTT] Failed to write user MQTT {} to MySQL: {e}",
                     username
                 );
-                Err(MqttRepositoryError::SeaOrm(e))
+                Err(e)
             }
         }
     }
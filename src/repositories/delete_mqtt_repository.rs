@@ -1,6 +1,8 @@
 use crate::entities::mqtt_entity::{Column, Entity as MqttUser};
 use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
 use log::{debug, error};
+use sea_orm::sea_query::Expr;
 use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
 
 pub struct DeleteMqttRepository {
@@ -12,37 +14,80 @@ impl DeleteMqttRepository {
         DeleteMqttRepository { db }
     }
 
-    pub async fn delete_mqtt(&self, username: &str) -> Result<(), MqttRepositoryError> {
+    /// Moves a live user to the trash.
+    pub async fn soft_delete_mqtt(
+        &self,
+        id: i32,
+        now: DateTime<Utc>,
+    ) -> Result<(), MqttRepositoryError> {
         debug!(
-            "[Repository | Delete] Deleting user MQTT {} from MySQL",
-            username
+            "[Repository | Delete] Moving user MQTT id {} to the trash",
+            id
         );
 
-        let user = MqttUser::find()
-            .filter(Column::Username.eq(username))
-            .one(&self.db)
+        let result = MqttUser::update_many()
+            .col_expr(Column::DeletedAt, Expr::value(now))
+            .col_expr(Column::UpdatedAt, Expr::value(now))
+            .filter(Column::Id.eq(id))
+            .filter(Column::DeletedAt.is_null())
+            .exec(&self.db)
             .await
             .map_err(MqttRepositoryError::SeaOrm)?;
 
-        match user {
-            Some(m) => {
-                MqttUser::delete_by_id(m.id)
-                    .exec(&self.db)
-                    .await
-                    .map_err(MqttRepositoryError::SeaOrm)?;
-                debug!(
-                    "[Repository | Delete] Successfully deleted user MQTT {}",
-                    username
-                );
-                Ok(())
-            }
-            None => {
-                error!(
-                    "[Repository | Delete] User MQTT {} not found in MySQL",
-                    username
-                );
-                Err(MqttRepositoryError::NotFound)
-            }
+        if result.rows_affected == 0 {
+            error!(
+                "[Repository | Delete] User MQTT id {} not found in MySQL",
+                id
+            );
+            return Err(MqttRepositoryError::NotFound);
         }
+        debug!(
+            "[Repository | Delete] User MQTT id {} moved to the trash",
+            id
+        );
+        Ok(())
+    }
+
+    /// Removes a user, live or in the trash, for good.
+    pub async fn delete_mqtt(&self, id: i32) -> Result<(), MqttRepositoryError> {
+        debug!(
+            "[Repository | Delete] Deleting user MQTT id {} from MySQL",
+            id
+        );
+
+        let result = MqttUser::delete_by_id(id)
+            .exec(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        if result.rows_affected == 0 {
+            error!(
+                "[Repository | Delete] User MQTT id {} not found in MySQL",
+                id
+            );
+            return Err(MqttRepositoryError::NotFound);
+        }
+        debug!(
+            "[Repository | Delete] Successfully deleted user MQTT id {}",
+            id
+        );
+        Ok(())
+    }
+
+    /// Removes users that were moved to the trash before `cutoff`.
+    pub async fn purge_deleted(&self, cutoff: DateTime<Utc>) -> Result<u64, MqttRepositoryError> {
+        debug!("[Repository | Delete] Purging user MQTT records trashed before {cutoff}");
+
+        let result = MqttUser::delete_many()
+            .filter(Column::DeletedAt.lte(cutoff))
+            .exec(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        debug!(
+            "[Repository | Delete] Purged {} trashed user MQTT records",
+            result.rows_affected
+        );
+        Ok(result.rows_affected)
     }
 }
@@ -0,0 +1,51 @@
+use crate::entities::mqtt_entity::{Column, Entity as MqttUser};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::debug;
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+
+pub struct ExpireMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl ExpireMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ExpireMqttRepository { db }
+    }
+
+    pub async fn disable_expired(&self, now: DateTime<Utc>) -> Result<u64, MqttRepositoryError> {
+        debug!("[Repository | ExpireMQTT] Disabling user MQTT records expired before {now}");
+
+        let result = MqttUser::update_many()
+            .col_expr(Column::IsEnabled, Expr::value(false))
+            .col_expr(Column::UpdatedAt, Expr::value(now))
+            .filter(Column::IsEnabled.eq(true))
+            .filter(Column::ValidUntil.lte(now))
+            .exec(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        debug!(
+            "[Repository | ExpireMQTT] Disabled {} expired user MQTT records",
+            result.rows_affected
+        );
+        Ok(result.rows_affected)
+    }
+
+    pub async fn purge_expired(&self, now: DateTime<Utc>) -> Result<u64, MqttRepositoryError> {
+        debug!("[Repository | ExpireMQTT] Purging user MQTT records expired before {now}");
+
+        let result = MqttUser::delete_many()
+            .filter(Column::ValidUntil.lte(now))
+            .exec(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        debug!(
+            "[Repository | ExpireMQTT] Purged {} expired user MQTT records",
+            result.rows_affected
+        );
+        Ok(result.rows_affected)
+    }
+}
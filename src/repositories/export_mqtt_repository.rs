@@ -0,0 +1,28 @@
+use crate::entities::mqtt_entity::{Column, Entity as MqttUser, Model};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::debug;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+pub struct ExportMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl ExportMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ExportMqttRepository { db }
+    }
+
+    pub async fn get_all_mqtt(&self, tenant_id: i32) -> Result<Vec<Model>, MqttRepositoryError> {
+        debug!(
+            "[Repository | ExportMQTT] Loading all user MQTT records of tenant id {} for export",
+            tenant_id
+        );
+        MqttUser::find()
+            .filter(Column::TenantId.eq(tenant_id))
+            .filter(Column::DeletedAt.is_null())
+            .order_by_asc(Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)
+    }
+}
@@ -0,0 +1,146 @@
+use crate::entities::acl_rule_entity::{self, Entity as AclRule};
+use crate::entities::role_entity::{self, Entity as Role};
+use crate::entities::user_role_entity::{self, Entity as UserRole};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::debug;
+use sea_orm::{ColumnTrait, Condition, DatabaseConnection, EntityTrait, QueryFilter};
+use std::collections::HashMap;
+
+pub struct GetMqttAclRulesRepository {
+    db: DatabaseConnection,
+}
+
+impl GetMqttAclRulesRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        GetMqttAclRulesRepository { db }
+    }
+
+    /// Rules applying to a user, in evaluation order (see `evaluation_order`).
+    pub async fn get_rules_for_user(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<acl_rule_entity::Model>, MqttRepositoryError> {
+        debug!(
+            "[Repository | GetAclRules] Fetching ACL rules for user id {}",
+            user_id
+        );
+
+        let user_roles = UserRole::find()
+            .filter(user_role_entity::Column::UserId.eq(user_id))
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        let rules = AclRule::find()
+            .filter(
+                Condition::any()
+                    .add(acl_rule_entity::Column::UserId.eq(user_id))
+                    .add(
+                        acl_rule_entity::Column::RoleId.is_in(user_roles.iter().map(|r| r.role_id)),
+                    ),
+            )
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        Ok(evaluation_order(user_id, &user_roles, &rules))
+    }
+
+    /// Names and priorities of the roles assigned to a user, highest priority first.
+    pub async fn get_roles_for_user(
+        &self,
+        user_id: i32,
+    ) -> Result<Vec<(String, i32)>, MqttRepositoryError> {
+        debug!(
+            "[Repository | GetAclRules] Fetching roles for user id {}",
+            user_id
+        );
+
+        let user_roles = UserRole::find()
+            .filter(user_role_entity::Column::UserId.eq(user_id))
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        let names: HashMap<i32, String> = Role::find()
+            .filter(role_entity::Column::Id.is_in(user_roles.iter().map(|r| r.role_id)))
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?
+            .into_iter()
+            .map(|role| (role.id, role.name))
+            .collect();
+
+        let mut roles: Vec<(String, i32)> = user_roles
+            .into_iter()
+            .filter_map(|r| Some((names.get(&r.role_id)?.clone(), r.priority)))
+            .collect();
+        roles.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)));
+        Ok(roles)
+    }
+
+    /// Rules of every user that has any, keyed by user id, each list in evaluation order.
+    pub async fn get_all_rules(
+        &self,
+    ) -> Result<HashMap<i32, Vec<acl_rule_entity::Model>>, MqttRepositoryError> {
+        debug!("[Repository | GetAclRules] Fetching all ACL rules");
+
+        let user_roles = UserRole::find()
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        let rules = AclRule::find()
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+
+        let mut user_ids: Vec<i32> = rules
+            .iter()
+            .filter_map(|rule| rule.user_id)
+            .chain(user_roles.iter().map(|r| r.user_id))
+            .collect();
+        user_ids.sort_unstable();
+        user_ids.dedup();
+
+        Ok(user_ids
+            .into_iter()
+            .map(|user_id| {
+                let roles: Vec<_> = user_roles
+                    .iter()
+                    .filter(|r| r.user_id == user_id)
+                    .cloned()
+                    .collect();
+                (user_id, evaluation_order(user_id, &roles, &rules))
+            })
+            .filter(|(_, rules)| !rules.is_empty())
+            .collect())
+    }
+}
+
+/// Orders the rules of one user: their own rules first, then rules of their roles by
+/// descending role priority, each group by descending rule priority.
+fn evaluation_order(
+    user_id: i32,
+    user_roles: &[user_role_entity::Model],
+    rules: &[acl_rule_entity::Model],
+) -> Vec<acl_rule_entity::Model> {
+    let mut ordered: Vec<(i32, &acl_rule_entity::Model)> = rules
+        .iter()
+        .filter_map(|rule| {
+            if rule.user_id == Some(user_id) {
+                return Some((i32::MAX, rule));
+            }
+            user_roles
+                .iter()
+                .find(|r| r.user_id == user_id && Some(r.role_id) == rule.role_id)
+                .map(|r| (r.priority, rule))
+        })
+        .collect();
+    ordered.sort_by(|(a_owner, a), (b_owner, b)| {
+        b_owner
+            .cmp(a_owner)
+            .then(a.role_id.cmp(&b.role_id))
+            .then(b.priority.cmp(&a.priority))
+            .then(a.id.cmp(&b.id))
+    });
+    ordered.into_iter().map(|(_, rule)| rule.clone()).collect()
+}
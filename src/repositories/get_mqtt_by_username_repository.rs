@@ -12,17 +12,44 @@ impl GetMqttByUsernameRepository {
         GetMqttByUsernameRepository { db }
     }
 
+    /// Fetches a live user; users in the trash are reported as not found.
     pub async fn get_mqtt_by_username(
         &self,
+        tenant_id: i32,
         username: &str,
+    ) -> Result<MqttEntity, MqttRepositoryError> {
+        self.find_by_username(tenant_id, username, false).await
+    }
+
+    /// Fetches a user that is in the trash.
+    pub async fn get_deleted_mqtt_by_username(
+        &self,
+        tenant_id: i32,
+        username: &str,
+    ) -> Result<MqttEntity, MqttRepositoryError> {
+        self.find_by_username(tenant_id, username, true).await
+    }
+
+    async fn find_by_username(
+        &self,
+        tenant_id: i32,
+        username: &str,
+        deleted: bool,
     ) -> Result<MqttEntity, MqttRepositoryError> {
         debug!(
-            "[Repository | GetByUsername] Fetching user MQTT for username: {}",
-            username
+            "[Repository | GetByUsername] Fetching user MQTT for username: {} (deleted={})",
+            username, deleted
         );
 
+        let deleted_filter = if deleted {
+            Column::DeletedAt.is_not_null()
+        } else {
+            Column::DeletedAt.is_null()
+        };
         let user = MqttUser::find()
+            .filter(Column::TenantId.eq(tenant_id))
             .filter(Column::Username.eq(username))
+            .filter(deleted_filter)
             .one(&self.db)
             .await
             .map_err(MqttRepositoryError::SeaOrm)?;
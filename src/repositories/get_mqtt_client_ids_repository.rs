@@ -0,0 +1,58 @@
+use crate::entities::user_client_id_entity::{self, Column, Entity as UserClientId};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::debug;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+
+pub struct GetMqttClientIdsRepository {
+    db: DatabaseConnection,
+}
+
+impl GetMqttClientIdsRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        GetMqttClientIdsRepository { db }
+    }
+
+    /// Client IDs a user is bound to, sorted.
+    pub async fn get_client_ids(&self, user_id: i32) -> Result<Vec<String>, MqttRepositoryError> {
+        debug!(
+            "[Repository | GetClientIds] Fetching client IDs of user id {}",
+            user_id
+        );
+        let rows = UserClientId::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::ClientId)
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        Ok(rows.into_iter().map(|row| row.client_id).collect())
+    }
+}
+
+/// Replaces the client ID bindings of a user; used inside the create, update and import
+/// transactions.
+pub(crate) async fn replace_client_ids<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    client_ids: &[String],
+) -> Result<(), DbErr> {
+    UserClientId::delete_many()
+        .filter(Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    if client_ids.is_empty() {
+        return Ok(());
+    }
+
+    UserClientId::insert_many(client_ids.iter().map(|client_id| {
+        user_client_id_entity::ActiveModel {
+            user_id: Set(user_id),
+            client_id: Set(client_id.clone()),
+        }
+    }))
+    .exec(conn)
+    .await?;
+    Ok(())
+}
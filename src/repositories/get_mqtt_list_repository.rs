@@ -1,7 +1,39 @@
-use crate::entities::mqtt_entity::{Entity as MqttUser, Model as MqttEntity};
+use crate::entities::mqtt_entity::{Column, Entity as MqttUser, Model as MqttEntity};
+use crate::entities::user_tag_entity::{self, Entity as UserTag};
 use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Duration, Utc};
 use log::debug;
-use sea_orm::{DatabaseConnection, EntityTrait};
+use sea_orm::sea_query::{Expr, LikeExpr, Query, SimpleExpr};
+use sea_orm::{
+    ColumnTrait, Condition, ConnectionTrait, DatabaseConnection, DbBackend, EntityTrait, Order,
+    PaginatorTrait, QueryFilter, QueryOrder,
+};
+
+/// Filters, sorting and paging for a user listing. `None` filters are not applied.
+pub struct MqttListFilter {
+    pub tenant_id: i32,
+    pub username_prefix: Option<String>,
+    pub is_superuser: Option<bool>,
+    pub is_enabled: Option<bool>,
+    pub expired: Option<bool>,
+    pub expiring_within: Option<Duration>,
+    /// Users without a login within this window, including those that never logged in
+    pub inactive_for: Option<Duration>,
+    /// Users created at or after this time
+    pub created_from: Option<DateTime<Utc>>,
+    /// Users created before this time
+    pub created_until: Option<DateTime<Utc>>,
+    /// List users in the trash instead of live ones
+    pub deleted: bool,
+    /// Users must carry every one of these tags
+    pub tags: Vec<String>,
+    /// `(key, value)` pairs matched against scalar metadata attributes
+    pub attributes: Vec<(String, String)>,
+    pub sort_column: Column,
+    pub sort_order: Order,
+    pub page: u64,
+    pub per_page: u64,
+}
 
 pub struct GetMqttListRepository {
     db: DatabaseConnection,
@@ -12,18 +44,130 @@ impl GetMqttListRepository {
         GetMqttListRepository { db }
     }
 
-    pub async fn get_mqtt_list(&self) -> Result<Vec<MqttEntity>, MqttRepositoryError> {
-        debug!("[Repository | GetMQTTList] Fetching all user MQTT records from MySQL");
+    /// Returns the requested page (1-based) together with the total item and page counts.
+    pub async fn get_mqtt_list(
+        &self,
+        filter: MqttListFilter,
+        now: DateTime<Utc>,
+    ) -> Result<(Vec<MqttEntity>, u64, u64), MqttRepositoryError> {
+        debug!(
+            "[Repository | GetMQTTList] Fetching user MQTT page {} (per_page={}) from MySQL",
+            filter.page, filter.per_page
+        );
 
-        let users = MqttUser::find()
-            .all(&self.db)
+        let deleted = if filter.deleted {
+            Column::DeletedAt.is_not_null()
+        } else {
+            Column::DeletedAt.is_null()
+        };
+        let mut condition = Condition::all()
+            .add(Column::TenantId.eq(filter.tenant_id))
+            .add(deleted);
+        if let Some(prefix) = filter.username_prefix {
+            condition = condition.add(Column::Username.like(prefix_pattern(&prefix)));
+        }
+        if let Some(is_superuser) = filter.is_superuser {
+            condition = condition.add(Column::IsSuperuser.eq(is_superuser));
+        }
+        if let Some(is_enabled) = filter.is_enabled {
+            condition = condition.add(Column::IsEnabled.eq(is_enabled));
+        }
+        match filter.expired {
+            Some(true) => condition = condition.add(Column::ValidUntil.lte(now)),
+            Some(false) => {
+                condition = condition.add(
+                    Condition::any()
+                        .add(Column::ValidUntil.is_null())
+                        .add(Column::ValidUntil.gt(now)),
+                )
+            }
+            None => {}
+        }
+        if let Some(window) = filter.expiring_within {
+            condition = condition
+                .add(Column::ValidUntil.gt(now))
+                .add(Column::ValidUntil.lte(now + window));
+        }
+        if let Some(window) = filter.inactive_for {
+            condition = condition.add(
+                Condition::any()
+                    .add(Column::LastLoginAt.is_null())
+                    .add(Column::LastLoginAt.lte(now - window)),
+            );
+        }
+        if let Some(from) = filter.created_from {
+            condition = condition.add(Column::CreatedAt.gte(from));
+        }
+        if let Some(until) = filter.created_until {
+            condition = condition.add(Column::CreatedAt.lt(until));
+        }
+        if !filter.tags.is_empty() {
+            let tag_count = filter.tags.len() as i64;
+            let tagged = Query::select()
+                .column(user_tag_entity::Column::UserId)
+                .from(UserTag)
+                .and_where(user_tag_entity::Column::Tag.is_in(filter.tags))
+                .group_by_col(user_tag_entity::Column::UserId)
+                .and_having(
+                    Expr::col(user_tag_entity::Column::Tag)
+                        .count_distinct()
+                        .eq(tag_count),
+                )
+                .to_owned();
+            condition = condition.add(Column::Id.in_subquery(tagged));
+        }
+        let backend = self.db.get_database_backend();
+        for (key, value) in filter.attributes {
+            condition = condition.add(attribute_equals(backend, key, value));
+        }
+
+        let mut query = MqttUser::find()
+            .filter(condition)
+            .order_by(filter.sort_column, filter.sort_order.clone());
+        if !matches!(filter.sort_column, Column::Id) {
+            // Tie-break on the primary key so pages stay stable.
+            query = query.order_by(Column::Id, filter.sort_order);
+        }
+
+        let paginator = query.paginate(&self.db, filter.per_page);
+        let totals = paginator
+            .num_items_and_pages()
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        let users = paginator
+            .fetch_page(filter.page.saturating_sub(1))
             .await
             .map_err(MqttRepositoryError::SeaOrm)?;
 
         debug!(
-            "[Repository | GetMQTTList] Successfully fetched {} user MQTT records",
-            users.len()
+            "[Repository | GetMQTTList] Successfully fetched {} of {} user MQTT records",
+            users.len(),
+            totals.number_of_items
         );
-        Ok(users)
+        Ok((users, totals.number_of_items, totals.number_of_pages))
+    }
+}
+
+/// `metadata.<key> = value` compared as text; `key` is restricted to `[A-Za-z0-9_-]`.
+fn attribute_equals(backend: DbBackend, key: String, value: String) -> SimpleExpr {
+    match backend {
+        DbBackend::Postgres => Expr::cust_with_values(r#""metadata" ->> $1 = $2"#, [key, value]),
+        _ => Expr::cust_with_values(
+            "JSON_UNQUOTE(JSON_EXTRACT(`metadata`, ?)) = ?",
+            [format!("$.\"{}\"", key), value],
+        ),
+    }
+}
+
+/// Builds a `LIKE 'prefix%'` pattern with wildcard characters in `prefix` escaped.
+fn prefix_pattern(prefix: &str) -> LikeExpr {
+    let mut pattern = String::with_capacity(prefix.len() + 1);
+    for c in prefix.chars() {
+        if matches!(c, '%' | '_' | '!') {
+            pattern.push('!');
+        }
+        pattern.push(c);
     }
+    pattern.push('%');
+    LikeExpr::new(pattern).escape('!')
 }
@@ -0,0 +1,75 @@
+use crate::entities::user_tag_entity::{self, Column, Entity as UserTag};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::debug;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, QueryOrder,
+    Set,
+};
+use std::collections::HashMap;
+
+pub struct GetMqttTagsRepository {
+    db: DatabaseConnection,
+}
+
+impl GetMqttTagsRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        GetMqttTagsRepository { db }
+    }
+
+    /// Tags of each given user, sorted; users without tags are absent from the map.
+    pub async fn get_tags_for_users(
+        &self,
+        user_ids: &[i32],
+    ) -> Result<HashMap<i32, Vec<String>>, MqttRepositoryError> {
+        debug!(
+            "[Repository | GetTags] Fetching tags of {} users",
+            user_ids.len()
+        );
+        let mut tags: HashMap<i32, Vec<String>> = HashMap::new();
+        if user_ids.is_empty() {
+            return Ok(tags);
+        }
+
+        let rows = UserTag::find()
+            .filter(Column::UserId.is_in(user_ids.iter().copied()))
+            .order_by_asc(Column::Tag)
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        for row in rows {
+            tags.entry(row.user_id).or_default().push(row.tag);
+        }
+        Ok(tags)
+    }
+
+    pub async fn get_tags(&self, user_id: i32) -> Result<Vec<String>, MqttRepositoryError> {
+        Ok(self
+            .get_tags_for_users(&[user_id])
+            .await?
+            .remove(&user_id)
+            .unwrap_or_default())
+    }
+}
+
+/// Replaces the tag set of a user; used inside the create and update transactions.
+pub(crate) async fn replace_tags<C: ConnectionTrait>(
+    conn: &C,
+    user_id: i32,
+    tags: &[String],
+) -> Result<(), DbErr> {
+    UserTag::delete_many()
+        .filter(Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    if tags.is_empty() {
+        return Ok(());
+    }
+
+    UserTag::insert_many(tags.iter().map(|tag| user_tag_entity::ActiveModel {
+        user_id: Set(user_id),
+        tag: Set(tag.clone()),
+    }))
+    .exec(conn)
+    .await?;
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use crate::entities::acl_rule_entity::{self, Entity as AclRule};
+use crate::entities::mqtt_entity::{self, Entity as MqttUser};
+use crate::entities::role_entity::{self, Entity as Role};
+use crate::entities::user_role_entity::{self, Entity as UserRole};
+use crate::repositories::get_mqtt_client_ids_repository::replace_client_ids;
+use crate::repositories::import_mqtt_repository::{
+    DuplicatePolicy, ImportOutcome, NewMqttUser, import_user,
+};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::{debug, error};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DatabaseTransaction, EntityTrait, QueryFilter, Set,
+    TransactionTrait,
+};
+use std::collections::HashMap;
+
+pub struct NewAclRule {
+    pub permission: String,
+    pub action: String,
+    pub topic: String,
+    pub priority: i32,
+}
+
+pub struct NewMqttRole {
+    pub name: String,
+    pub rules: Vec<NewAclRule>,
+}
+
+/// A user together with the names and priorities of the roles assigned to it and its client
+/// ID bindings.
+pub struct NewMosquittoClient {
+    pub user: NewMqttUser,
+    pub roles: Vec<(String, i32)>,
+    pub client_ids: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct MosquittoImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub roles: usize,
+    pub acl_rules: usize,
+}
+
+pub struct ImportMosquittoRepository {
+    db: DatabaseConnection,
+}
+
+impl ImportMosquittoRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ImportMosquittoRepository { db }
+    }
+
+    /// Writes roles, their rules and clients in one transaction.
+    ///
+    /// Roles are matched by name within the tenant and an existing role has its rules replaced. Clients follow
+    /// `on_duplicate`; a created or overwritten client gets exactly the given roles.
+    pub async fn import_mosquitto(
+        &self,
+        tenant_id: i32,
+        roles: Vec<NewMqttRole>,
+        clients: Vec<NewMosquittoClient>,
+        on_duplicate: DuplicatePolicy,
+    ) -> Result<MosquittoImportSummary, MqttRepositoryError> {
+        debug!(
+            "[Repository | ImportMosquitto] Importing {} roles and {} clients",
+            roles.len(),
+            clients.len()
+        );
+
+        let txn = self.db.begin().await.map_err(MqttRepositoryError::SeaOrm)?;
+        match write_import(&txn, tenant_id, roles, clients, on_duplicate).await {
+            Ok(summary) => {
+                txn.commit().await.map_err(MqttRepositoryError::SeaOrm)?;
+                debug!("[Repository | ImportMosquitto] Import committed");
+                Ok(summary)
+            }
+            Err(e) => {
+                error!("[Repository | ImportMosquitto] Rolling back import: {e}");
+                txn.rollback().await.map_err(MqttRepositoryError::SeaOrm)?;
+                Err(e)
+            }
+        }
+    }
+}
+
+async fn write_import(
+    txn: &DatabaseTransaction,
+    tenant_id: i32,
+    roles: Vec<NewMqttRole>,
+    clients: Vec<NewMosquittoClient>,
+    on_duplicate: DuplicatePolicy,
+) -> Result<MosquittoImportSummary, MqttRepositoryError> {
+    let mut summary = MosquittoImportSummary::default();
+    let mut role_ids = HashMap::new();
+
+    for role in roles {
+        let existing = Role::find()
+            .filter(role_entity::Column::TenantId.eq(tenant_id))
+            .filter(role_entity::Column::Name.eq(&role.name))
+            .one(txn)
+            .await?;
+        let role_id = match existing {
+            Some(existing) => {
+                AclRule::delete_many()
+                    .filter(acl_rule_entity::Column::RoleId.eq(existing.id))
+                    .exec(txn)
+                    .await?;
+                existing.id
+            }
+            None => {
+                Role::insert(role_entity::ActiveModel {
+                    tenant_id: Set(tenant_id),
+                    name: Set(role.name.clone()),
+                    ..Default::default()
+                })
+                .exec(txn)
+                .await?
+                .last_insert_id
+            }
+        };
+
+        summary.acl_rules += role.rules.len();
+        let rules: Vec<acl_rule_entity::ActiveModel> = role
+            .rules
+            .into_iter()
+            .map(|rule| acl_rule_entity::ActiveModel {
+                tenant_id: Set(tenant_id),
+                role_id: Set(Some(role_id)),
+                permission: Set(rule.permission),
+                action: Set(rule.action),
+                topic: Set(rule.topic),
+                priority: Set(rule.priority),
+                ..Default::default()
+            })
+            .collect();
+        if !rules.is_empty() {
+            AclRule::insert_many(rules).exec(txn).await?;
+        }
+        role_ids.insert(role.name, role_id);
+        summary.roles += 1;
+    }
+
+    for client in clients {
+        let username = client.user.username.clone();
+        let outcome = import_user(txn, client.user, on_duplicate).await?;
+        match outcome {
+            ImportOutcome::Skipped => {
+                summary.skipped += 1;
+                continue;
+            }
+            ImportOutcome::Created => summary.created += 1,
+            ImportOutcome::Updated => summary.updated += 1,
+        }
+
+        let user_id = MqttUser::find()
+            .filter(mqtt_entity::Column::TenantId.eq(tenant_id))
+            .filter(mqtt_entity::Column::Username.eq(&username))
+            .one(txn)
+            .await?
+            .ok_or(MqttRepositoryError::NotFound)?
+            .id;
+        UserRole::delete_many()
+            .filter(user_role_entity::Column::UserId.eq(user_id))
+            .exec(txn)
+            .await?;
+
+        let assignments: Vec<user_role_entity::ActiveModel> = client
+            .roles
+            .iter()
+            .filter_map(|(name, priority)| {
+                role_ids
+                    .get(name)
+                    .map(|role_id| user_role_entity::ActiveModel {
+                        user_id: Set(user_id),
+                        role_id: Set(*role_id),
+                        priority: Set(*priority),
+                    })
+            })
+            .collect();
+        if !assignments.is_empty() {
+            UserRole::insert_many(assignments).exec(txn).await?;
+        }
+        replace_client_ids(txn, user_id, &client.client_ids).await?;
+    }
+
+    Ok(summary)
+}
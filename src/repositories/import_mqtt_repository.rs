@@ -0,0 +1,225 @@
+use crate::entities::mqtt_entity::{ActiveModel, Column, Entity as MqttUser};
+use crate::entities::role_entity::{self, Entity as Role};
+use crate::entities::user_role_entity::{self, Entity as UserRole};
+use crate::repositories::get_mqtt_tags_repository::replace_tags;
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::{
+    ActiveValue::Unchanged, ColumnTrait, ConnectionTrait, DatabaseConnection, EntityTrait,
+    QueryFilter, Set, TransactionTrait,
+};
+use std::collections::HashMap;
+
+/// A user row ready to be written; the password is already encrypted.
+pub struct NewMqttUser {
+    pub tenant_id: i32,
+    pub username: String,
+    pub password: String,
+    pub is_superuser: bool,
+    pub is_enabled: bool,
+    pub metadata: Option<serde_json::Value>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub provision_batch_id: Option<String>,
+}
+
+/// An import row: the user with the ids of its roles and its tags, which replace the
+/// existing ones when the user is written.
+pub struct ImportedMqttUser {
+    pub user: NewMqttUser,
+    pub role_ids: Vec<i32>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum DuplicatePolicy {
+    Skip,
+    Fail,
+    Overwrite,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ImportOutcome {
+    Created,
+    Updated,
+    Skipped,
+}
+
+pub struct ImportMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl ImportMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ImportMqttRepository { db }
+    }
+
+    /// Ids of the tenant's roles by name.
+    pub async fn get_role_ids(
+        &self,
+        tenant_id: i32,
+    ) -> Result<HashMap<String, i32>, MqttRepositoryError> {
+        let roles = Role::find()
+            .filter(role_entity::Column::TenantId.eq(tenant_id))
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        Ok(roles.into_iter().map(|role| (role.name, role.id)).collect())
+    }
+
+    /// Writes `users` in order and returns one outcome per attempted row.
+    ///
+    /// With `atomic` every row shares one transaction: the first failing row rolls it back
+    /// and is the last entry returned. Otherwise each row is written in its own transaction.
+    pub async fn import_users(
+        &self,
+        users: Vec<ImportedMqttUser>,
+        on_duplicate: DuplicatePolicy,
+        atomic: bool,
+    ) -> Result<Vec<Result<ImportOutcome, MqttRepositoryError>>, MqttRepositoryError> {
+        debug!(
+            "[Repository | ImportMQTT] Importing {} user MQTT records (atomic={})",
+            users.len(),
+            atomic
+        );
+
+        let mut outcomes = Vec::with_capacity(users.len());
+        if !atomic {
+            for user in users {
+                let txn = self.db.begin().await.map_err(MqttRepositoryError::SeaOrm)?;
+                let outcome = import_row(&txn, user, on_duplicate).await;
+                match outcome {
+                    Ok(_) => txn.commit().await,
+                    Err(_) => txn.rollback().await,
+                }
+                .map_err(MqttRepositoryError::SeaOrm)?;
+                outcomes.push(outcome);
+            }
+            return Ok(outcomes);
+        }
+
+        let txn = self.db.begin().await.map_err(MqttRepositoryError::SeaOrm)?;
+        for user in users {
+            match import_row(&txn, user, on_duplicate).await {
+                Ok(outcome) => outcomes.push(Ok(outcome)),
+                Err(e) => {
+                    error!("[Repository | ImportMQTT] Rolling back import: {e}");
+                    txn.rollback().await.map_err(MqttRepositoryError::SeaOrm)?;
+                    outcomes.push(Err(e));
+                    return Ok(outcomes);
+                }
+            }
+        }
+        txn.commit().await.map_err(MqttRepositoryError::SeaOrm)?;
+
+        debug!(
+            "[Repository | ImportMQTT] Import of {} user MQTT records committed to MySQL",
+            outcomes.len()
+        );
+        Ok(outcomes)
+    }
+}
+
+/// Writes the user, then replaces its roles and tags unless the row was skipped.
+async fn import_row<C: ConnectionTrait>(
+    conn: &C,
+    row: ImportedMqttUser,
+    on_duplicate: DuplicatePolicy,
+) -> Result<ImportOutcome, MqttRepositoryError> {
+    let tenant_id = row.user.tenant_id;
+    let username = row.user.username.clone();
+    let outcome = import_user(conn, row.user, on_duplicate).await?;
+    if outcome == ImportOutcome::Skipped {
+        return Ok(outcome);
+    }
+
+    let user_id = MqttUser::find()
+        .filter(Column::TenantId.eq(tenant_id))
+        .filter(Column::Username.eq(&username))
+        .one(conn)
+        .await?
+        .ok_or(MqttRepositoryError::NotFound)?
+        .id;
+    UserRole::delete_many()
+        .filter(user_role_entity::Column::UserId.eq(user_id))
+        .exec(conn)
+        .await?;
+    if !row.role_ids.is_empty() {
+        UserRole::insert_many(
+            row.role_ids
+                .iter()
+                .map(|role_id| user_role_entity::ActiveModel {
+                    user_id: Set(user_id),
+                    role_id: Set(*role_id),
+                    priority: Set(0),
+                }),
+        )
+        .exec(conn)
+        .await?;
+    }
+    replace_tags(conn, user_id, &row.tags).await?;
+    Ok(outcome)
+}
+
+pub(crate) async fn import_user<C: ConnectionTrait>(
+    conn: &C,
+    user: NewMqttUser,
+    on_duplicate: DuplicatePolicy,
+) -> Result<ImportOutcome, MqttRepositoryError> {
+    let existing = MqttUser::find()
+        .filter(Column::TenantId.eq(user.tenant_id))
+        .filter(Column::Username.eq(&user.username))
+        .one(conn)
+        .await
+        .map_err(MqttRepositoryError::SeaOrm)?;
+
+    let now = Utc::now();
+    match (existing, on_duplicate) {
+        (None, _) => {
+            let new_user = ActiveModel {
+                tenant_id: Set(user.tenant_id),
+                username: Set(user.username),
+                password: Set(user.password),
+                is_superuser: Set(user.is_superuser),
+                is_enabled: Set(user.is_enabled),
+                metadata: Set(user.metadata),
+                valid_from: Set(user.valid_from),
+                valid_until: Set(user.valid_until),
+                provision_batch_id: Set(user.provision_batch_id),
+                created_at: Set(now),
+                updated_at: Set(now),
+                ..Default::default()
+            };
+            MqttUser::insert(new_user)
+                .exec(conn)
+                .await
+                .map_err(MqttRepositoryError::SeaOrm)?;
+            Ok(ImportOutcome::Created)
+        }
+        (Some(_), DuplicatePolicy::Skip) => Ok(ImportOutcome::Skipped),
+        (Some(_), DuplicatePolicy::Fail) => Err(MqttRepositoryError::AlreadyExists),
+        (Some(existing), DuplicatePolicy::Overwrite) => {
+            // Overwriting a user in the trash brings it back.
+            let replaced = ActiveModel {
+                id: Unchanged(existing.id),
+                password: Set(user.password),
+                is_superuser: Set(user.is_superuser),
+                is_enabled: Set(user.is_enabled),
+                metadata: Set(user.metadata),
+                valid_from: Set(user.valid_from),
+                valid_until: Set(user.valid_until),
+                previous_password: Set(None),
+                previous_password_expires_at: Set(None),
+                updated_at: Set(now),
+                deleted_at: Set(None),
+                ..Default::default()
+            };
+            MqttUser::update(replaced)
+                .exec(conn)
+                .await
+                .map_err(MqttRepositoryError::SeaOrm)?;
+            Ok(ImportOutcome::Updated)
+        }
+    }
+}
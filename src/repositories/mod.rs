@@ -1,5 +1,21 @@
+pub mod api_key_repository;
+pub mod audit_event_repository;
 pub mod create_mqtt_repository;
 pub mod delete_mqtt_repository;
+pub mod expire_mqtt_repository;
+pub mod export_mqtt_repository;
+pub mod get_mqtt_acl_rules_repository;
 pub mod get_mqtt_by_username_repository;
+pub mod get_mqtt_client_ids_repository;
 pub mod get_mqtt_list_repository;
+pub mod get_mqtt_tags_repository;
+pub mod import_mosquitto_repository;
+pub mod import_mqtt_repository;
+pub mod provision_mqtt_repository;
+pub mod record_login_repository;
 pub mod repository_error;
+pub mod restore_mqtt_repository;
+pub mod rotate_mqtt_password_repository;
+pub mod tenant_repository;
+pub mod tenant_usage_repository;
+pub mod update_mqtt_repository;
@@ -0,0 +1,168 @@
+use crate::entities::mqtt_entity::{self, ActiveModel, Column, Entity as MqttUser};
+use crate::entities::provision_batch_entity::{self, Entity as ProvisionBatch};
+use crate::entities::role_entity::{self, Entity as Role};
+use crate::entities::user_role_entity::{self, Entity as UserRole};
+use crate::repositories::import_mqtt_repository::NewMqttUser;
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::Utc;
+use log::{debug, error};
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, DbErr, EntityTrait, QueryFilter, Set, SqlErr, TransactionTrait,
+};
+use std::collections::HashMap;
+
+/// Rows written per INSERT and usernames looked up per IN clause.
+const CHUNK_SIZE: usize = 500;
+
+pub struct ProvisionMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl ProvisionMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        ProvisionMqttRepository { db }
+    }
+
+    pub async fn find_batch(
+        &self,
+        batch_id: &str,
+    ) -> Result<Option<provision_batch_entity::Model>, MqttRepositoryError> {
+        debug!(
+            "[Repository | ProvisionMQTT] Looking up provisioning batch {}",
+            batch_id
+        );
+        ProvisionBatch::find()
+            .filter(provision_batch_entity::Column::BatchId.eq(batch_id))
+            .one(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)
+    }
+
+    /// Records a new batch. Returns `AlreadyExists` if another request registered it first.
+    pub async fn create_batch(
+        &self,
+        batch_id: &str,
+        spec: serde_json::Value,
+        user_count: i32,
+    ) -> Result<(), MqttRepositoryError> {
+        let batch = provision_batch_entity::ActiveModel {
+            batch_id: Set(batch_id.to_owned()),
+            spec: Set(spec),
+            user_count: Set(user_count),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+        ProvisionBatch::insert(batch)
+            .exec(&self.db)
+            .await
+            .map_err(unique_violation_to_exists)?;
+
+        debug!(
+            "[Repository | ProvisionMQTT] Provisioning batch {} recorded",
+            batch_id
+        );
+        Ok(())
+    }
+
+    pub async fn find_users_by_usernames(
+        &self,
+        tenant_id: i32,
+        usernames: &[String],
+    ) -> Result<Vec<mqtt_entity::Model>, MqttRepositoryError> {
+        let mut users = Vec::new();
+        for chunk in usernames.chunks(CHUNK_SIZE) {
+            let found = MqttUser::find()
+                .filter(Column::TenantId.eq(tenant_id))
+                .filter(Column::Username.is_in(chunk.iter().cloned()))
+                .all(&self.db)
+                .await
+                .map_err(MqttRepositoryError::SeaOrm)?;
+            users.extend(found);
+        }
+        Ok(users)
+    }
+
+    pub async fn get_role_ids(
+        &self,
+        tenant_id: i32,
+    ) -> Result<HashMap<String, i32>, MqttRepositoryError> {
+        let roles = Role::find()
+            .filter(role_entity::Column::TenantId.eq(tenant_id))
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)?;
+        Ok(roles.into_iter().map(|role| (role.name, role.id)).collect())
+    }
+
+    /// Inserts `users` in chunks of `CHUNK_SIZE`; each chunk is a single multi-row INSERT
+    /// committed together with the `role_ids` of its users.
+    pub async fn insert_users(
+        &self,
+        users: Vec<NewMqttUser>,
+        role_ids: &[i32],
+    ) -> Result<usize, MqttRepositoryError> {
+        let total = users.len();
+        let mut users = users.into_iter().peekable();
+        let mut written = 0;
+        let now = Utc::now();
+        while users.peek().is_some() {
+            let chunk: Vec<ActiveModel> = users
+                .by_ref()
+                .take(CHUNK_SIZE)
+                .map(|user| ActiveModel {
+                    tenant_id: Set(user.tenant_id),
+                    username: Set(user.username),
+                    password: Set(user.password),
+                    is_superuser: Set(user.is_superuser),
+                    is_enabled: Set(user.is_enabled),
+                    metadata: Set(user.metadata),
+                    valid_from: Set(user.valid_from),
+                    valid_until: Set(user.valid_until),
+                    provision_batch_id: Set(user.provision_batch_id),
+                    created_at: Set(now),
+                    updated_at: Set(now),
+                    ..Default::default()
+                })
+                .collect();
+            let txn = self.db.begin().await.map_err(MqttRepositoryError::SeaOrm)?;
+            let inserted = MqttUser::insert_many(chunk)
+                .exec_with_returning_many(&txn)
+                .await
+                .map_err(|e| {
+                    error!(
+                        "[Repository | ProvisionMQTT] Failed to write chunk after {} of {} users: {e}",
+                        written, total
+                    );
+                    unique_violation_to_exists(e)
+                })?;
+            if !role_ids.is_empty() {
+                UserRole::insert_many(inserted.iter().flat_map(|user| {
+                    role_ids
+                        .iter()
+                        .map(|role_id| user_role_entity::ActiveModel {
+                            user_id: Set(user.id),
+                            role_id: Set(*role_id),
+                            priority: Set(0),
+                        })
+                }))
+                .exec(&txn)
+                .await
+                .map_err(MqttRepositoryError::SeaOrm)?;
+            }
+            txn.commit().await.map_err(MqttRepositoryError::SeaOrm)?;
+            written += inserted.len();
+            debug!(
+                "[Repository | ProvisionMQTT] Wrote {} of {} provisioned users",
+                written, total
+            );
+        }
+        Ok(written)
+    }
+}
+
+fn unique_violation_to_exists(e: DbErr) -> MqttRepositoryError {
+    match e.sql_err() {
+        Some(SqlErr::UniqueConstraintViolation(_)) => MqttRepositoryError::AlreadyExists,
+        _ => MqttRepositoryError::SeaOrm(e),
+    }
+}
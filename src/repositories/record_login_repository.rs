@@ -0,0 +1,55 @@
+use crate::entities::mqtt_entity::{Column, Entity as MqttUser};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::sea_query::Expr;
+use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, TransactionTrait};
+
+/// Latest successful authentication of a user.
+#[derive(Clone)]
+pub struct LoginActivity {
+    pub at: DateTime<Utc>,
+    pub ip: Option<String>,
+}
+
+pub struct RecordLoginRepository {
+    db: DatabaseConnection,
+}
+
+impl RecordLoginRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        RecordLoginRepository { db }
+    }
+
+    /// Writes buffered logins in one transaction. `updated_at` is left alone, it only tracks
+    /// changes to the account itself.
+    pub async fn record_logins(
+        &self,
+        logins: &[(i32, LoginActivity)],
+    ) -> Result<(), MqttRepositoryError> {
+        debug!(
+            "[Repository | RecordLogin] Writing {} buffered logins",
+            logins.len()
+        );
+
+        let result = async {
+            let txn = self.db.begin().await?;
+            for (user_id, login) in logins {
+                // Rows deleted since the login simply match nothing.
+                MqttUser::update_many()
+                    .col_expr(Column::LastLoginAt, Expr::value(login.at))
+                    .col_expr(Column::LastLoginIp, Expr::value(login.ip.clone()))
+                    .filter(Column::Id.eq(*user_id))
+                    .exec(&txn)
+                    .await?;
+            }
+            txn.commit().await
+        }
+        .await;
+
+        result.map_err(|e| {
+            error!("[Repository | RecordLogin] Failed to write buffered logins: {e}");
+            MqttRepositoryError::SeaOrm(e)
+        })
+    }
+}
@@ -7,4 +7,7 @@ pub enum MqttRepositoryError {
 
     #[error("User not found")]
     NotFound,
+
+    #[error("User already exists")]
+    AlreadyExists,
 }
@@ -0,0 +1,48 @@
+use crate::entities::mqtt_entity::{ActiveModel, Model as MqttEntity};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::Utc;
+use log::{debug, error};
+use sea_orm::{ActiveModelTrait, ActiveValue::Unchanged, DatabaseConnection, DbErr, Set};
+
+pub struct RestoreMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl RestoreMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        RestoreMqttRepository { db }
+    }
+
+    /// Takes a user out of the trash.
+    pub async fn restore_mqtt(&self, id: i32) -> Result<MqttEntity, MqttRepositoryError> {
+        debug!(
+            "[Repository | RestoreMQTT] Restoring user MQTT id {} from the trash",
+            id
+        );
+
+        let user = ActiveModel {
+            id: Unchanged(id),
+            deleted_at: Set(None),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        match user.update(&self.db).await {
+            Ok(restored) => {
+                debug!(
+                    "[Repository | RestoreMQTT] User MQTT {} restored in MySQL",
+                    restored.username
+                );
+                Ok(restored)
+            }
+            Err(DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | RestoreMQTT] Failed to restore user MQTT id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use crate::entities::mqtt_entity::{ActiveModel, Entity as MqttUser};
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::{DatabaseConnection, EntityTrait, Set};
+
+pub struct RotateMqttPasswordRepository {
+    db: DatabaseConnection,
+}
+
+impl RotateMqttPasswordRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        RotateMqttPasswordRepository { db }
+    }
+
+    pub async fn rotate_password(
+        &self,
+        id: i32,
+        new_password: &str,
+        previous_password: Option<String>,
+        previous_password_expires_at: Option<DateTime<Utc>>,
+    ) -> Result<(), MqttRepositoryError> {
+        debug!(
+            "[Repository | RotatePassword] Rotating password for user MQTT id {}",
+            id
+        );
+
+        let user = ActiveModel {
+            id: Set(id),
+            password: Set(new_password.to_owned()),
+            previous_password: Set(previous_password),
+            previous_password_expires_at: Set(previous_password_expires_at),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        match MqttUser::update(user).exec(&self.db).await {
+            Ok(_) => {
+                debug!(
+                    "[Repository | RotatePassword] Password for user MQTT id {} rotated in MySQL",
+                    id
+                );
+                Ok(())
+            }
+            Err(sea_orm::DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | RotatePassword] Failed to rotate password for user MQTT id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+}
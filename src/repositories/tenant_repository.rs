@@ -0,0 +1,118 @@
+use crate::dtos::tenant_dto::TenantQuotaDTO;
+use crate::entities::tenant_entity::{
+    ActiveModel, Column, Entity as Tenant, Model as TenantEntity,
+};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::{debug, error};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Unchanged, DatabaseConnection, DbErr, EntityTrait, QueryOrder,
+    Set, SqlErr,
+};
+
+pub struct TenantRepository {
+    db: DatabaseConnection,
+}
+
+impl TenantRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        TenantRepository { db }
+    }
+
+    pub async fn get_all(&self) -> Result<Vec<TenantEntity>, MqttRepositoryError> {
+        debug!("[Repository | Tenant] Loading all tenants");
+        Tenant::find()
+            .order_by_asc(Column::Id)
+            .all(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)
+    }
+
+    /// Inserts a tenant; a taken name or topic prefix is reported as `AlreadyExists`.
+    pub async fn create_tenant(
+        &self,
+        name: &str,
+        topic_prefix: &str,
+        api_key_hash: &str,
+        quota: TenantQuotaDTO,
+    ) -> Result<TenantEntity, MqttRepositoryError> {
+        debug!("[Repository | Tenant] Creating tenant {}", name);
+        let tenant = ActiveModel {
+            name: Set(name.to_owned()),
+            topic_prefix: Set(topic_prefix.to_owned()),
+            api_key_hash: Set(Some(api_key_hash.to_owned())),
+            created_at: Set(chrono::Utc::now()),
+            max_users: Set(quota.max_users),
+            max_superusers: Set(quota.max_superusers),
+            max_acl_rules: Set(quota.max_acl_rules),
+            max_auth_per_minute: Set(quota.max_auth_per_minute),
+            ..Default::default()
+        };
+
+        tenant.insert(&self.db).await.map_err(|e| {
+            if matches!(e.sql_err(), Some(SqlErr::UniqueConstraintViolation(_))) {
+                return MqttRepositoryError::AlreadyExists;
+            }
+            error!(
+                "[Repository | Tenant] Failed to create tenant {}: {e}",
+                name
+            );
+            MqttRepositoryError::SeaOrm(e)
+        })
+    }
+
+    pub async fn set_api_key_hash(
+        &self,
+        id: i32,
+        api_key_hash: &str,
+    ) -> Result<TenantEntity, MqttRepositoryError> {
+        debug!(
+            "[Repository | Tenant] Replacing API key of tenant id {}",
+            id
+        );
+        let tenant = ActiveModel {
+            id: Unchanged(id),
+            api_key_hash: Set(Some(api_key_hash.to_owned())),
+            ..Default::default()
+        };
+
+        match tenant.update(&self.db).await {
+            Ok(updated) => Ok(updated),
+            Err(DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | Tenant] Failed to replace API key of tenant id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+
+    pub async fn set_quota(
+        &self,
+        id: i32,
+        quota: TenantQuotaDTO,
+    ) -> Result<TenantEntity, MqttRepositoryError> {
+        debug!("[Repository | Tenant] Replacing quota of tenant id {}", id);
+        let tenant = ActiveModel {
+            id: Unchanged(id),
+            max_users: Set(quota.max_users),
+            max_superusers: Set(quota.max_superusers),
+            max_acl_rules: Set(quota.max_acl_rules),
+            max_auth_per_minute: Set(quota.max_auth_per_minute),
+            ..Default::default()
+        };
+
+        match tenant.update(&self.db).await {
+            Ok(updated) => Ok(updated),
+            Err(DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | Tenant] Failed to replace quota of tenant id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use crate::entities::acl_rule_entity::{self, Entity as AclRule};
+use crate::entities::mqtt_entity::{self, Entity as MqttUser};
+use crate::entities::role_entity::{self, Entity as Role};
+use crate::repositories::repository_error::MqttRepositoryError;
+use log::debug;
+use sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, PaginatorTrait, QueryFilter, QuerySelect,
+};
+use std::collections::HashMap;
+
+const CHUNK_SIZE: usize = 500;
+
+/// Row counts a tenant's quotas are checked against.
+pub struct TenantUsageRepository {
+    db: DatabaseConnection,
+}
+
+impl TenantUsageRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        TenantUsageRepository { db }
+    }
+
+    /// Users and superusers of the tenant, trashed ones included.
+    pub async fn count_users(&self, tenant_id: i32) -> Result<(u64, u64), MqttRepositoryError> {
+        debug!(
+            "[Repository | TenantUsage] Counting users of tenant id {}",
+            tenant_id
+        );
+        let users = MqttUser::find()
+            .filter(mqtt_entity::Column::TenantId.eq(tenant_id))
+            .count(&self.db)
+            .await?;
+        let superusers = MqttUser::find()
+            .filter(mqtt_entity::Column::TenantId.eq(tenant_id))
+            .filter(mqtt_entity::Column::IsSuperuser.eq(true))
+            .count(&self.db)
+            .await?;
+        Ok((users, superusers))
+    }
+
+    pub async fn count_acl_rules(&self, tenant_id: i32) -> Result<u64, MqttRepositoryError> {
+        debug!(
+            "[Repository | TenantUsage] Counting ACL rules of tenant id {}",
+            tenant_id
+        );
+        AclRule::find()
+            .filter(acl_rule_entity::Column::TenantId.eq(tenant_id))
+            .count(&self.db)
+            .await
+            .map_err(MqttRepositoryError::SeaOrm)
+    }
+
+    /// Rules of the named roles, which a Mosquitto import replaces.
+    pub async fn count_role_rules(
+        &self,
+        tenant_id: i32,
+        role_names: &[String],
+    ) -> Result<u64, MqttRepositoryError> {
+        let mut total = 0;
+        for chunk in role_names.chunks(CHUNK_SIZE) {
+            let role_ids: Vec<i32> = Role::find()
+                .select_only()
+                .column(role_entity::Column::Id)
+                .filter(role_entity::Column::TenantId.eq(tenant_id))
+                .filter(role_entity::Column::Name.is_in(chunk.iter().cloned()))
+                .into_tuple()
+                .all(&self.db)
+                .await?;
+            if role_ids.is_empty() {
+                continue;
+            }
+            total += AclRule::find()
+                .filter(acl_rule_entity::Column::RoleId.is_in(role_ids))
+                .count(&self.db)
+                .await?;
+        }
+        Ok(total)
+    }
+
+    /// Which of `usernames` exist in the tenant, mapped to whether they are superusers.
+    pub async fn existing_users(
+        &self,
+        tenant_id: i32,
+        usernames: &[&str],
+    ) -> Result<HashMap<String, bool>, MqttRepositoryError> {
+        let mut existing = HashMap::new();
+        for chunk in usernames.chunks(CHUNK_SIZE) {
+            let found: Vec<(String, bool)> = MqttUser::find()
+                .select_only()
+                .column(mqtt_entity::Column::Username)
+                .column(mqtt_entity::Column::IsSuperuser)
+                .filter(mqtt_entity::Column::TenantId.eq(tenant_id))
+                .filter(mqtt_entity::Column::Username.is_in(chunk.iter().copied()))
+                .into_tuple()
+                .all(&self.db)
+                .await?;
+            existing.extend(found);
+        }
+        Ok(existing)
+    }
+}
@@ -0,0 +1,102 @@
+use crate::entities::mqtt_entity::{ActiveModel, Model as MqttEntity};
+use crate::repositories::get_mqtt_client_ids_repository::replace_client_ids;
+use crate::repositories::get_mqtt_tags_repository::replace_tags;
+use crate::repositories::repository_error::MqttRepositoryError;
+use chrono::{DateTime, Utc};
+use log::{debug, error};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Unchanged, DatabaseConnection, DbErr, Set, TransactionTrait,
+};
+
+/// Fields to change on a user; `None` leaves the column untouched.
+#[derive(Default)]
+pub struct MqttUserChanges {
+    pub password: Option<String>,
+    pub is_superuser: Option<bool>,
+    pub is_enabled: Option<bool>,
+    pub metadata: Option<Option<serde_json::Value>>,
+    pub valid_from: Option<Option<DateTime<Utc>>>,
+    pub valid_until: Option<Option<DateTime<Utc>>>,
+    /// Replaces the whole tag set when present
+    pub tags: Option<Vec<String>>,
+    /// Replaces the whole set of client ID bindings when present
+    pub client_ids: Option<Vec<String>>,
+}
+
+pub struct UpdateMqttRepository {
+    db: DatabaseConnection,
+}
+
+impl UpdateMqttRepository {
+    pub fn new(db: DatabaseConnection) -> Self {
+        UpdateMqttRepository { db }
+    }
+
+    pub async fn update_mqtt(
+        &self,
+        id: i32,
+        changes: MqttUserChanges,
+    ) -> Result<MqttEntity, MqttRepositoryError> {
+        debug!("[Repository | UpdateMQTT] Updating user MQTT id {}", id);
+
+        // Tag and client ID changes count as an update of the user too.
+        let mut user = ActiveModel {
+            id: Unchanged(id),
+            updated_at: Set(Utc::now()),
+            ..Default::default()
+        };
+        if let Some(password) = changes.password {
+            // A direct password change ends any rotation grace period.
+            user.password = Set(password);
+            user.previous_password = Set(None);
+            user.previous_password_expires_at = Set(None);
+        }
+        if let Some(is_superuser) = changes.is_superuser {
+            user.is_superuser = Set(is_superuser);
+        }
+        if let Some(is_enabled) = changes.is_enabled {
+            user.is_enabled = Set(is_enabled);
+        }
+        if let Some(metadata) = changes.metadata {
+            user.metadata = Set(metadata);
+        }
+        if let Some(valid_from) = changes.valid_from {
+            user.valid_from = Set(valid_from);
+        }
+        if let Some(valid_until) = changes.valid_until {
+            user.valid_until = Set(valid_until);
+        }
+
+        let result = async {
+            let txn = self.db.begin().await?;
+            let updated = user.update(&txn).await?;
+            if let Some(tags) = &changes.tags {
+                replace_tags(&txn, id, tags).await?;
+            }
+            if let Some(client_ids) = &changes.client_ids {
+                replace_client_ids(&txn, id, client_ids).await?;
+            }
+            txn.commit().await?;
+            Ok::<_, DbErr>(updated)
+        }
+        .await;
+
+        match result {
+            Ok(updated) => {
+                debug!(
+                    "[Repository | UpdateMQTT] User MQTT {} successfully updated in MySQL",
+                    updated.username
+                );
+                Ok(updated)
+            }
+            Err(DbErr::RecordNotUpdated) => Err(MqttRepositoryError::NotFound),
+            Err(e) => {
+                error!(
+                    "[Repository | UpdateMQTT] Failed to update user MQTT id {}: {e}",
+                    id
+                );
+                Err(MqttRepositoryError::SeaOrm(e))
+            }
+        }
+    }
+}
@@ -1,22 +1,48 @@
-use actix_web::{App, HttpResponse, HttpServer, Responder, middleware, web};
+use actix_web::{App, HttpResponse, HttpServer, Responder, guard, middleware, web};
 use chrono::Local;
 use log::{error, info};
 use std::io::Write;
 use std::sync::Arc;
+use std::time::Duration;
 use utoipa::{Modify, OpenApi};
 use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::infrastructure::database::{DbType, close_db, init_db};
+use crate::infrastructure::database::{DbConfig, close_db};
+use crate::infrastructure::tls::{ReloadableCertResolver, TlsConfig, store_client_certificate};
+use crate::infrastructure::unix_socket::{parse_socket_mode, remove_stale_socket, set_socket_mode};
+use crate::jobs::account_expiry_job::spawn_account_expiry_job;
+use crate::jobs::api_key_reload_job::spawn_api_key_reload_job;
+use crate::jobs::deleted_user_purge_job::spawn_deleted_user_purge_job;
+use crate::jobs::login_activity_job::spawn_login_activity_job;
+use crate::jobs::tenant_reload_job::spawn_tenant_reload_job;
+use crate::jobs::tls_reload_job::spawn_tls_reload_job;
 use crate::middleware::api_key::ApiKeyMiddleware;
+use crate::middleware::ip_allow_list::IpAllowListMiddleware;
 use crate::middleware::logger_request::RequestLoggerMiddleware;
 use crate::middleware::powered_by::PoweredByMiddleware;
+use crate::middleware::rate_limit::RateLimitMiddleware;
+use crate::middleware::request_id::RequestIdMiddleware;
+use crate::middleware::require_scope::RequireScope;
 
+use crate::handler::api_key_handler::{
+    AppState as ApiKeyAppState, create_api_key_handler, get_api_key_list_handler,
+    revoke_api_key_handler,
+};
+use crate::handler::audit_handler::{AppState as AuditAppState, get_audit_events_handler};
 use crate::handler::create_mqtt_handler::{AppState as CreateMqttAppState, create_mqtt_handler};
+use crate::handler::export_mqtt_handler::{
+    AppState as ExportMqttAppState, export_mqtt_acl_handler, export_mqtt_users_handler,
+};
 use crate::handler::get_mqtt_credentials_handler::{
     AppState as GetCredentialsAppState, get_mqtt_credentials_handler,
 };
 use crate::handler::get_mqtt_list_handler::{AppState as GetListAppState, get_mqtt_list_handler};
+use crate::handler::get_mqtt_user_handler::{AppState as GetUserAppState, get_mqtt_user_handler};
+use crate::handler::import_mosquitto_handler::{
+    AppState as ImportMosquittoAppState, import_mosquitto_handler,
+};
+use crate::handler::import_mqtt_handler::{AppState as ImportMqttAppState, import_mqtt_handler};
 use crate::handler::mqtt_acl_handler::{AppState as MqttAclAppState, mqtt_acl_handler};
 use crate::handler::mqtt_login_handler::{
     AppState as MqttLoginAppState, login_with_credentials_handler,
@@ -24,18 +50,73 @@ use crate::handler::mqtt_login_handler::{
 use crate::handler::delete_mqtt_handler::{
     AppState as DeleteMqttAppState, delete_mqtt,
 };
+use crate::handler::provision_mqtt_handler::{
+    AppState as ProvisionMqttAppState, provision_mqtt_handler,
+};
+use crate::handler::restore_mqtt_handler::{
+    AppState as RestoreMqttAppState, restore_mqtt_handler,
+};
+use crate::handler::rotate_mqtt_password_handler::{
+    AppState as RotateMqttPasswordAppState, rotate_mqtt_password_handler,
+};
+use crate::handler::tenant_handler::{
+    AppState as TenantAppState, create_tenant_handler, get_tenant_list_handler,
+    get_tenant_usage_handler, rotate_tenant_api_key_handler, set_tenant_quota_handler,
+};
+use crate::handler::update_mqtt_handler::{AppState as UpdateMqttAppState, update_mqtt_handler};
 
+use crate::services::api_key_service::ApiKeyService;
+use crate::services::audit_service::AuditService;
 use crate::services::create_mqtt_service::CreateMqttService;
 use crate::services::get_mqtt_credentials_service::GetMqttCredentialsService;
 use crate::services::get_mqtt_list_service::GetMqttListService;
+use crate::services::get_mqtt_user_service::GetMqttUserService;
+use crate::services::import_mosquitto_service::ImportMosquittoService;
+use crate::services::import_mqtt_service::ImportMqttService;
+use crate::services::login_activity_service::LoginActivityService;
 use crate::services::mqtt_acl_service::MqttAclService;
 use crate::services::mqtt_login_service::MqttLoginService;
-use crate::services::delete_mqtt_service::DeleteMqttService;
+use crate::services::delete_mqtt_service::{DeleteMqttService, retention_period};
+use crate::services::expire_mqtt_service::{ExpireMqttService, ExpiryAction};
+use crate::services::export_mqtt_service::ExportMqttService;
+use crate::services::provision_mqtt_service::ProvisionMqttService;
+use crate::services::quota_service::QuotaService;
+use crate::services::ip_allow_list_service::IpAllowListService;
+use crate::services::rate_limit_service::RateLimitService;
+use crate::services::request_signing_service::RequestSigningService;
+use crate::services::restore_mqtt_service::RestoreMqttService;
+use crate::services::rotate_mqtt_password_service::{
+    RotateMqttPasswordService, grace_period_error,
+};
+use crate::services::tenant_service::TenantService;
+use crate::services::update_mqtt_service::UpdateMqttService;
 
+use crate::repositories::api_key_repository::ApiKeyRepository;
+use crate::repositories::audit_event_repository::AuditEventRepository;
 use crate::repositories::create_mqtt_repository::CreateMqttRepository;
+use crate::repositories::get_mqtt_acl_rules_repository::GetMqttAclRulesRepository;
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::repositories::record_login_repository::RecordLoginRepository;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::get_mqtt_client_ids_repository::GetMqttClientIdsRepository;
 use crate::repositories::get_mqtt_list_repository::GetMqttListRepository;
+use crate::repositories::import_mosquitto_repository::ImportMosquittoRepository;
+use crate::repositories::import_mqtt_repository::ImportMqttRepository;
 use crate::repositories::delete_mqtt_repository::DeleteMqttRepository;
+use crate::repositories::expire_mqtt_repository::ExpireMqttRepository;
+use crate::repositories::export_mqtt_repository::ExportMqttRepository;
+use crate::repositories::provision_mqtt_repository::ProvisionMqttRepository;
+use crate::repositories::restore_mqtt_repository::RestoreMqttRepository;
+use crate::repositories::rotate_mqtt_password_repository::RotateMqttPasswordRepository;
+use crate::repositories::tenant_repository::TenantRepository;
+use crate::repositories::tenant_usage_repository::TenantUsageRepository;
+use crate::repositories::update_mqtt_repository::UpdateMqttRepository;
+
+use crate::dtos::api_key_dto::ApiScope;
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::password_generator::{
+    PasswordAlphabet, PasswordGeneratorConfig, password_length_error,
+};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -43,27 +124,91 @@ use crate::repositories::delete_mqtt_repository::DeleteMqttRepository;
         crate::handler::create_mqtt_handler::create_mqtt_handler,
         crate::handler::get_mqtt_credentials_handler::get_mqtt_credentials_handler,
         crate::handler::get_mqtt_list_handler::get_mqtt_list_handler,
+        crate::handler::get_mqtt_user_handler::get_mqtt_user_handler,
         crate::handler::mqtt_acl_handler::mqtt_acl_handler,
         crate::handler::mqtt_login_handler::login_with_credentials_handler,
-        crate::handler::delete_mqtt_handler::delete_mqtt
+        crate::handler::delete_mqtt_handler::delete_mqtt,
+        crate::handler::restore_mqtt_handler::restore_mqtt_handler,
+        crate::handler::rotate_mqtt_password_handler::rotate_mqtt_password_handler,
+        crate::handler::update_mqtt_handler::update_mqtt_handler,
+        crate::handler::import_mqtt_handler::import_mqtt_handler,
+        crate::handler::provision_mqtt_handler::provision_mqtt_handler,
+        crate::handler::export_mqtt_handler::export_mqtt_users_handler,
+        crate::handler::export_mqtt_handler::export_mqtt_acl_handler,
+        crate::handler::import_mosquitto_handler::import_mosquitto_handler,
+        crate::handler::tenant_handler::create_tenant_handler,
+        crate::handler::tenant_handler::get_tenant_list_handler,
+        crate::handler::tenant_handler::rotate_tenant_api_key_handler,
+        crate::handler::tenant_handler::set_tenant_quota_handler,
+        crate::handler::tenant_handler::get_tenant_usage_handler,
+        crate::handler::api_key_handler::create_api_key_handler,
+        crate::handler::api_key_handler::get_api_key_list_handler,
+        crate::handler::api_key_handler::revoke_api_key_handler,
+        crate::handler::audit_handler::get_audit_events_handler
     ),
     components(
         schemas(
-            crate::dtos::mqtt_dto::MqttDTO,
             crate::dtos::mqtt_dto::GetMqttListDTO,
+            crate::dtos::mqtt_dto::PaginationDTO,
+            crate::dtos::mqtt_dto::MqttListSortField,
+            crate::dtos::mqtt_dto::SortOrder,
             crate::dtos::mqtt_dto::CreateMqttDTO,
+            crate::dtos::mqtt_dto::CreateMqttResultDTO,
+            crate::utils::password_generator::PasswordAlphabet,
             crate::dtos::mqtt_dto::MqttLoginDTO,
             crate::dtos::mqtt_dto::MqttJwtDTO,
+            crate::dtos::mqtt_dto::MqttLoginResultDTO,
+            crate::dtos::mqtt_dto::MatchedCredential,
             crate::dtos::mqtt_dto::AuthType,
             crate::dtos::mqtt_dto::MqttAclDTO,
             crate::dtos::mqtt_dto::DeleteMqttDTO,
             crate::dtos::mqtt_dto::MqttCredentialsDTO,
+            crate::dtos::mqtt_dto::RotateMqttPasswordDTO,
+            crate::dtos::mqtt_dto::RotateMqttPasswordResultDTO,
+            crate::dtos::mqtt_dto::UpdateMqttDTO,
+            crate::dtos::mqtt_dto::MqttUserDTO,
+            crate::dtos::mqtt_dto::MqttUserDetailDTO,
+            crate::dtos::mqtt_dto::MqttUserRoleDTO,
+            crate::dtos::mqtt_dto::MqttAclRuleDTO,
+            crate::dtos::mqtt_dto::ImportFormat,
+            crate::dtos::mqtt_dto::ImportMode,
+            crate::dtos::mqtt_dto::DuplicateHandling,
+            crate::dtos::mqtt_dto::ImportMqttRowDTO,
+            crate::dtos::mqtt_dto::ImportRowStatus,
+            crate::dtos::mqtt_dto::ImportRowResultDTO,
+            crate::dtos::mqtt_dto::ImportMqttResultDTO,
+            crate::dtos::mqtt_dto::ManifestFormat,
+            crate::dtos::mqtt_dto::ProvisionMqttDTO,
+            crate::dtos::mqtt_dto::ProvisionedCredentialDTO,
+            crate::dtos::mqtt_dto::ProvisionMqttResultDTO,
+            crate::dtos::mqtt_dto::ExportFormat,
+            crate::dtos::mqtt_dto::EmqxUserDTO,
+            crate::dtos::mqtt_dto::EmqxAclUserDTO,
+            crate::dtos::mqtt_dto::AclAction,
+            crate::dtos::mqtt_dto::MosquittoImportFormat,
+            crate::dtos::mqtt_dto::ImportMosquittoResultDTO,
+            crate::dtos::tenant_dto::CreateTenantDTO,
+            crate::dtos::tenant_dto::TenantDTO,
+            crate::dtos::tenant_dto::TenantApiKeyDTO,
+            crate::dtos::tenant_dto::TenantQuotaDTO,
+            crate::dtos::tenant_dto::TenantUsageDTO,
+            crate::dtos::api_key_dto::ApiScope,
+            crate::dtos::api_key_dto::CreateApiKeyDTO,
+            crate::dtos::api_key_dto::ApiKeyDTO,
+            crate::dtos::api_key_dto::CreatedApiKeyDTO,
+            crate::dtos::audit_dto::AuditAction,
+            crate::dtos::audit_dto::AuditOutcome,
+            crate::dtos::audit_dto::AuditEventDTO,
+            crate::dtos::audit_dto::GetAuditEventsDTO,
             crate::services::service_error::ValidationError,
             crate::dtos::response_dto::ErrorResponseValidation
         )
     ),
     tags(
-        (name = "MQTT", description = "MQTT Authentication API")
+        (name = "MQTT", description = "MQTT Authentication API"),
+        (name = "Tenants", description = "Tenant management, global API key only"),
+        (name = "API Keys", description = "Scoped API key management, admin scope only"),
+        (name = "Audit", description = "Admin action audit log, admin scope only")
     ),
     modifiers(&SecurityAddon)
 )]
@@ -87,6 +232,177 @@ async fn healthcheck() -> impl Responder {
         .body("OK")
 }
 
+/// Broker hook routes, served under `/mqtt` on the hook listener (or the main one without
+/// `HOOK_BIND_ADDRESS`).
+/// Parses a variable that must be valid when it is set; unset or empty gives `None`.
+fn parse_env<T: std::str::FromStr>(name: &str) -> Option<T> {
+    let value = std::env::var(name).ok().filter(|v| !v.trim().is_empty())?;
+    let parsed = value
+        .trim()
+        .parse()
+        .unwrap_or_else(|_| panic!("❌ {} has an invalid value: {}", name, value));
+    Some(parsed)
+}
+
+fn hook_routes(cfg: &mut web::ServiceConfig) {
+    cfg.route(
+        "/check",
+        web::post()
+            .to(login_with_credentials_handler)
+            .wrap(RequireScope(ApiScope::HookAuth)),
+    )
+    .route(
+        "/acl",
+        web::post()
+            .to(mqtt_acl_handler)
+            .wrap(RequireScope(ApiScope::HookAcl)),
+    );
+}
+
+/// Management routes, served under `/mqtt` on the main listener.
+fn admin_routes(cfg: &mut web::ServiceConfig, import_max_bytes: usize) {
+    cfg.route(
+        "/create",
+        web::post()
+            .to(create_mqtt_handler)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .service(
+        web::resource("/import")
+            .guard(guard::Post())
+            .app_data(web::PayloadConfig::new(import_max_bytes))
+            .route(
+                web::post()
+                    .to(import_mqtt_handler)
+                    .wrap(RequireScope(ApiScope::UsersWrite)),
+            ),
+    )
+    .service(
+        web::resource("/import/mosquitto")
+            .guard(guard::Post())
+            .app_data(web::PayloadConfig::new(import_max_bytes))
+            .route(
+                web::post()
+                    .to(import_mosquitto_handler)
+                    .wrap(RequireScope(ApiScope::UsersWrite)),
+            ),
+    )
+    .route(
+        "/provision",
+        web::post()
+            .to(provision_mqtt_handler)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .route(
+        "/export/users",
+        web::get()
+            .to(export_mqtt_users_handler)
+            .wrap(RequireScope(ApiScope::CredentialsReveal)),
+    )
+    .route(
+        "/export/acl",
+        web::get()
+            .to(export_mqtt_acl_handler)
+            .wrap(RequireScope(ApiScope::UsersRead)),
+    )
+    .route(
+        "/credentials/{username}",
+        web::get()
+            .to(get_mqtt_credentials_handler)
+            .wrap(RequireScope(ApiScope::CredentialsReveal)),
+    )
+    .route(
+        "/users/{username}",
+        web::get()
+            .to(get_mqtt_user_handler)
+            .wrap(RequireScope(ApiScope::UsersRead)),
+    )
+    .route(
+        "/tenants",
+        web::post()
+            .to(create_tenant_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/tenants",
+        web::get()
+            .to(get_tenant_list_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/tenants/{name}/key",
+        web::post()
+            .to(rotate_tenant_api_key_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/tenants/{name}/quota",
+        web::put()
+            .to(set_tenant_quota_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/tenants/{name}/usage",
+        web::get()
+            .to(get_tenant_usage_handler)
+            .wrap(RequireScope(ApiScope::UsersRead)),
+    )
+    .route(
+        "/keys",
+        web::post()
+            .to(create_api_key_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/keys",
+        web::get()
+            .to(get_api_key_list_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/keys/{id}",
+        web::delete()
+            .to(revoke_api_key_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/audit",
+        web::get()
+            .to(get_audit_events_handler)
+            .wrap(RequireScope(ApiScope::Admin)),
+    )
+    .route(
+        "/{username}",
+        web::delete()
+            .to(delete_mqtt)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .route(
+        "/{username}",
+        web::patch()
+            .to(update_mqtt_handler)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .route(
+        "/{username}/rotate",
+        web::post()
+            .to(rotate_mqtt_password_handler)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .route(
+        "/{username}/restore",
+        web::post()
+            .to(restore_mqtt_handler)
+            .wrap(RequireScope(ApiScope::UsersWrite)),
+    )
+    .route(
+        "",
+        web::get()
+            .to(get_mqtt_list_handler)
+            .wrap(RequireScope(ApiScope::UsersRead)),
+    );
+}
+
 pub async fn run_server() -> std::io::Result<()> {
     // =====================
     // 🌱 Load Environment Variables
@@ -96,21 +412,85 @@ pub async fn run_server() -> std::io::Result<()> {
     let secret_key =
         std::env::var("SECRET_KEY").expect("❌ Environment variable SECRET_KEY is not set");
 
-    let db_type_str = std::env::var("DB_TYPE").unwrap_or_else(|_| "mysql".to_string());
-    let db_type = DbType::from_str(&db_type_str);
-
-    let db_host = std::env::var(if matches!(db_type, DbType::Postgres) { "POSTGRES_HOST" } else { "MYSQL_HOST" })
-        .unwrap_or_else(|_| "127.0.0.1".to_string());
-    let db_port = std::env::var(if matches!(db_type, DbType::Postgres) { "POSTGRES_PORT" } else { "MYSQL_PORT" })
-        .unwrap_or_else(|_| if matches!(db_type, DbType::Postgres) { "5432" } else { "3306" }.to_string())
-        .parse::<u16>()
-        .unwrap_or(if matches!(db_type, DbType::Postgres) { 5432 } else { 3306 });
-    let db_user = std::env::var(if matches!(db_type, DbType::Postgres) { "POSTGRES_USER" } else { "MYSQL_USER" })
-        .expect("❌ Database user is not set");
-    let db_pass = std::env::var(if matches!(db_type, DbType::Postgres) { "POSTGRES_PASSWORD" } else { "MYSQL_PASSWORD" })
-        .expect("❌ Database password is not set");
-    let db_name = std::env::var(if matches!(db_type, DbType::Postgres) { "POSTGRES_DATABASE" } else { "MYSQL_DATABASE" })
-        .expect("❌ Database name is not set");
+    let db_config = DbConfig::from_env();
+
+    let expiry_action =
+        ExpiryAction::parse(&std::env::var("ACCOUNT_EXPIRY_ACTION").unwrap_or_default())
+            .unwrap_or_else(|e| panic!("❌ {}", e));
+    let expiry_interval_secs = std::env::var("ACCOUNT_EXPIRY_SWEEP_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(300);
+    let deleted_retention =
+        retention_period(parse_env::<i64>("DELETED_USER_RETENTION_SECS").unwrap_or(30 * 86400))
+            .unwrap_or_else(|e| panic!("❌ {}", e));
+    let deleted_purge_interval_secs = std::env::var("DELETED_USER_PURGE_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(3600);
+    let rotation_grace_secs = parse_env::<i64>("PASSWORD_ROTATION_GRACE_SECS").unwrap_or(86400);
+    if let Some(e) = grace_period_error(rotation_grace_secs) {
+        panic!("❌ PASSWORD_ROTATION_GRACE_SECS: {}", e);
+    }
+    let import_max_rows = std::env::var("IMPORT_MAX_ROWS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10_000);
+    let import_max_bytes = std::env::var("IMPORT_MAX_BYTES")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10 * 1024 * 1024);
+    let provision_max_users = std::env::var("PROVISION_MAX_USERS")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(10_000);
+    let login_flush_interval_secs = std::env::var("LOGIN_ACTIVITY_FLUSH_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(10);
+    let login_max_pending = std::env::var("LOGIN_ACTIVITY_MAX_PENDING")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(100_000);
+    let tenant_reload_interval_secs = std::env::var("TENANT_RELOAD_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    let api_key_reload_interval_secs = std::env::var("API_KEY_RELOAD_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(30);
+    let api_key_file = std::env::var("API_KEY_FILE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(std::path::PathBuf::from);
+    let bind_address = std::env::var("BIND_ADDRESS").unwrap_or_else(|_| "0.0.0.0:5500".to_string());
+    let hook_bind_address = std::env::var("HOOK_BIND_ADDRESS")
+        .ok()
+        .filter(|v| !v.trim().is_empty());
+    let hook_unix_socket = std::env::var("HOOK_UNIX_SOCKET")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(std::path::PathBuf::from);
+    let hook_unix_socket_mode =
+        parse_socket_mode(&std::env::var("HOOK_UNIX_SOCKET_MODE").unwrap_or_default())
+            .unwrap_or_else(|e| panic!("❌ {}", e));
+    let tls_config = TlsConfig::from_env();
+    let tls_reload_interval_secs = std::env::var("TLS_RELOAD_INTERVAL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(60);
+    let password_generator_length = parse_env::<usize>("PASSWORD_GENERATOR_LENGTH").unwrap_or(32);
+    if let Some(e) = password_length_error(password_generator_length) {
+        panic!("❌ PASSWORD_GENERATOR_LENGTH: {}", e);
+    }
+    let password_generator = PasswordGeneratorConfig {
+        length: password_generator_length,
+        alphabet: PasswordAlphabet::parse(
+            &std::env::var("PASSWORD_GENERATOR_ALPHABET").unwrap_or_default(),
+        )
+        .unwrap_or_else(|e| panic!("❌ {}", e)),
+    };
 
     // =====================
     // 🪵 Initialize logger with custom format + color
@@ -144,7 +524,8 @@ pub async fn run_server() -> std::io::Result<()> {
     // =====================
     // 🗄️ Database Initialization (Sea-ORM)
     // =====================
-    let db_conn = init_db(db_type, &db_host, db_port, &db_user, &db_pass, &db_name)
+    let db_conn = db_config
+        .connect()
         .await
         .map_err(|e| {
             error!("❌ Failed to initialize database via Sea-ORM: {}", e);
@@ -158,27 +539,249 @@ pub async fn run_server() -> std::io::Result<()> {
     let get_mqtt_list_repo = Arc::new(GetMqttListRepository::new(db_conn.clone()));
     let get_by_username_repo = Arc::new(GetMqttByUsernameRepository::new(db_conn.clone()));
     let delete_mqtt_repo = Arc::new(DeleteMqttRepository::new(db_conn.clone()));
+    let restore_mqtt_repo = Arc::new(RestoreMqttRepository::new(db_conn.clone()));
+    let expire_mqtt_repo = Arc::new(ExpireMqttRepository::new(db_conn.clone()));
+    let rotate_mqtt_password_repo = Arc::new(RotateMqttPasswordRepository::new(db_conn.clone()));
+    let update_mqtt_repo = Arc::new(UpdateMqttRepository::new(db_conn.clone()));
+    let import_mqtt_repo = Arc::new(ImportMqttRepository::new(db_conn.clone()));
+    let provision_mqtt_repo = Arc::new(ProvisionMqttRepository::new(db_conn.clone()));
+    let export_mqtt_repo = Arc::new(ExportMqttRepository::new(db_conn.clone()));
+    let get_acl_rules_repo = Arc::new(GetMqttAclRulesRepository::new(db_conn.clone()));
+    let get_tags_repo = Arc::new(GetMqttTagsRepository::new(db_conn.clone()));
+    let get_client_ids_repo = Arc::new(GetMqttClientIdsRepository::new(db_conn.clone()));
+    let record_login_repo = Arc::new(RecordLoginRepository::new(db_conn.clone()));
+    let import_mosquitto_repo = Arc::new(ImportMosquittoRepository::new(db_conn.clone()));
+    let tenant_repo = Arc::new(TenantRepository::new(db_conn.clone()));
+    let tenant_usage_repo = Arc::new(TenantUsageRepository::new(db_conn.clone()));
+    let api_key_repo = Arc::new(ApiKeyRepository::new(db_conn.clone()));
+    let audit_event_repo = Arc::new(AuditEventRepository::new(db_conn.clone()));
 
     // =====================
     // 🛠️ Service Layer
     // =====================
+    let credential_policy = Arc::new(CredentialPolicy::from_env());
+    let audit_service = Arc::new(AuditService::new(Arc::clone(&audit_event_repo)));
+    let tenant_service = Arc::new(TenantService::new(
+        Arc::clone(&tenant_repo),
+        Arc::clone(&audit_service),
+    ));
+    match tenant_service.reload().await {
+        Ok(count) => info!("🏢 Loaded {} tenants", count),
+        Err(e) => {
+            error!("❌ Failed to load tenants: {}", e);
+            return Err(std::io::Error::other("Failed to load tenants"));
+        }
+    }
+    let api_key_service = Arc::new(ApiKeyService::new(
+        Arc::clone(&api_key_repo),
+        Arc::clone(&tenant_service),
+        Arc::clone(&audit_service),
+        api_key_file,
+    ));
+    match api_key_service.reload().await {
+        Ok(count) => info!("🔑 Loaded {} API keys", count),
+        Err(e) => {
+            error!("❌ Failed to load API keys: {}", e);
+            return Err(std::io::Error::other("Failed to load API keys"));
+        }
+    }
+    let request_signing_service = Arc::new(RequestSigningService::from_env());
+    let rate_limit_service = Arc::new(RateLimitService::from_env());
+    let ip_allow_list_service = Arc::new(IpAllowListService::from_env());
+    let quota_service = Arc::new(QuotaService::new(Arc::clone(&tenant_usage_repo)));
     let create_mqtt_service = Arc::new(CreateMqttService::new(
         Arc::clone(&create_mqtt_repo),
         Arc::clone(&get_by_username_repo),
+        Arc::clone(&quota_service),
+        password_generator,
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
     ));
     let get_mqtt_credentials_service = Arc::new(GetMqttCredentialsService::new(
         Arc::clone(&get_by_username_repo),
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let get_mqtt_list_service = Arc::new(GetMqttListService::new(
+        Arc::clone(&get_mqtt_list_repo),
+        Arc::clone(&get_tags_repo),
+    ));
+    let get_mqtt_user_service = Arc::new(GetMqttUserService::new(
+        Arc::clone(&get_by_username_repo),
+        Arc::clone(&get_acl_rules_repo),
+        Arc::clone(&get_tags_repo),
+        Arc::clone(&get_client_ids_repo),
+        Arc::clone(&credential_policy),
+    ));
+    let login_activity_service = Arc::new(LoginActivityService::new(
+        Arc::clone(&record_login_repo),
+        login_max_pending,
     ));
-    let get_mqtt_list_service = Arc::new(GetMqttListService::new(Arc::clone(&get_mqtt_list_repo)));
     let mqtt_login_service = Arc::new(MqttLoginService::new(
         Arc::clone(&get_by_username_repo),
+        Arc::clone(&get_tags_repo),
+        Arc::clone(&login_activity_service),
+        Arc::clone(&tenant_service),
+        Arc::clone(&quota_service),
         secret_key,
+        Arc::clone(&credential_policy),
+    ));
+    let mqtt_acl_service = Arc::new(MqttAclService::new(
+        Arc::clone(&get_by_username_repo),
+        Arc::clone(&get_acl_rules_repo),
+        Arc::clone(&tenant_service),
+        Arc::clone(&credential_policy),
     ));
-    let mqtt_acl_service = Arc::new(MqttAclService::new(Arc::clone(&get_by_username_repo)));
     let delete_mqtt_service = Arc::new(DeleteMqttService::new(
         Arc::clone(&get_by_username_repo),
         Arc::clone(&delete_mqtt_repo),
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+        deleted_retention,
+    ));
+    let restore_mqtt_service = Arc::new(RestoreMqttService::new(
+        Arc::clone(&get_by_username_repo),
+        Arc::clone(&restore_mqtt_repo),
+        Arc::clone(&get_tags_repo),
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let rotate_mqtt_password_service = Arc::new(RotateMqttPasswordService::new(
+        Arc::clone(&get_by_username_repo),
+        Arc::clone(&rotate_mqtt_password_repo),
+        chrono::Duration::seconds(rotation_grace_secs),
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let update_mqtt_service = Arc::new(UpdateMqttService::new(
+        Arc::clone(&get_by_username_repo),
+        Arc::clone(&update_mqtt_repo),
+        Arc::clone(&get_tags_repo),
+        Arc::clone(&get_client_ids_repo),
+        Arc::clone(&quota_service),
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let import_mqtt_service = Arc::new(ImportMqttService::new(
+        Arc::clone(&import_mqtt_repo),
+        Arc::clone(&quota_service),
+        password_generator,
+        import_max_rows,
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let provision_mqtt_service = Arc::new(ProvisionMqttService::new(
+        Arc::clone(&provision_mqtt_repo),
+        Arc::clone(&quota_service),
+        password_generator,
+        provision_max_users,
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
+    ));
+    let export_mqtt_service = Arc::new(ExportMqttService::new(
+        Arc::clone(&export_mqtt_repo),
+        Arc::clone(&get_acl_rules_repo),
+    ));
+    let import_mosquitto_service = Arc::new(ImportMosquittoService::new(
+        Arc::clone(&import_mosquitto_repo),
+        Arc::clone(&quota_service),
+        password_generator,
+        import_max_rows,
+        Arc::clone(&credential_policy),
+        Arc::clone(&audit_service),
     ));
+    let expire_mqtt_service = Arc::new(ExpireMqttService::new(
+        Arc::clone(&expire_mqtt_repo),
+        expiry_action,
+    ));
+
+    // =====================
+    // 🔒 TLS
+    // =====================
+    let tls_resolver = match &tls_config {
+        Some(config) => Some(Arc::new(
+            ReloadableCertResolver::load(&config.cert_path, &config.key_path)
+                .map_err(std::io::Error::other)?,
+        )),
+        None => None,
+    };
+    let tls_server_config = match (&tls_config, &tls_resolver) {
+        (Some(config), Some(resolver)) => Some(
+            config
+                .server_config(Arc::clone(resolver))
+                .map_err(std::io::Error::other)?,
+        ),
+        _ => None,
+    };
+    let client_cert_scopes = Arc::new(
+        tls_config
+            .as_ref()
+            .map(|config| config.client_cert_scopes.clone())
+            .unwrap_or_default(),
+    );
+
+    // =====================
+    // ⏰ Background Jobs
+    // =====================
+    let expiry_job = spawn_account_expiry_job(
+        expire_mqtt_service,
+        Duration::from_secs(expiry_interval_secs.max(1)),
+    );
+    let purge_job = spawn_deleted_user_purge_job(
+        Arc::clone(&delete_mqtt_service),
+        Duration::from_secs(deleted_purge_interval_secs.max(1)),
+    );
+    let login_activity_job = spawn_login_activity_job(
+        Arc::clone(&login_activity_service),
+        Duration::from_secs(login_flush_interval_secs.max(1)),
+    );
+    let tenant_reload_job = spawn_tenant_reload_job(
+        Arc::clone(&tenant_service),
+        Duration::from_secs(tenant_reload_interval_secs.max(1)),
+    );
+    let api_key_reload_job = spawn_api_key_reload_job(
+        Arc::clone(&api_key_service),
+        Duration::from_secs(api_key_reload_interval_secs.max(1)),
+    );
+    let tls_reload_job = tls_resolver.as_ref().map(|resolver| {
+        spawn_tls_reload_job(
+            Arc::clone(resolver),
+            Duration::from_secs(tls_reload_interval_secs.max(1)),
+        )
+    });
+
+    // Reload tenants, API keys and the TLS certificate on SIGHUP, e.g. after rotating the keys
+    // in API_KEY_FILE
+    let reload_signal_task = {
+        let tenant_service = Arc::clone(&tenant_service);
+        let api_key_service = Arc::clone(&api_key_service);
+        let tls_resolver = tls_resolver.clone();
+        tokio::spawn(async move {
+            let mut hangup =
+                match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup()) {
+                    Ok(hangup) => hangup,
+                    Err(e) => {
+                        error!("❌ Failed to listen for SIGHUP: {}", e);
+                        return;
+                    }
+                };
+            while hangup.recv().await.is_some() {
+                info!("SIGHUP received, reloading tenants and API keys...");
+                if let Err(e) = tenant_service.reload().await {
+                    error!("❌ Tenant reload failed: {}", e);
+                }
+                match api_key_service.reload().await {
+                    Ok(count) => info!("🔑 Reloaded {} API keys", count),
+                    Err(e) => error!("❌ API key reload failed: {}", e),
+                }
+                if let Some(resolver) = &tls_resolver
+                    && let Err(e) = resolver.reload(true)
+                {
+                    error!("❌ TLS certificate reload failed: {}", e);
+                }
+            }
+        })
+    };
 
     // =====================
     // 🚀 App State
@@ -189,6 +792,9 @@ pub async fn run_server() -> std::io::Result<()> {
     let get_mqtt_list_state = web::Data::new(GetListAppState {
         get_mqtt_list_service,
     });
+    let get_mqtt_user_state = web::Data::new(GetUserAppState {
+        get_mqtt_user_service,
+    });
     let get_mqtt_credentials_state = web::Data::new(GetCredentialsAppState {
         get_mqtt_credentials_service,
     });
@@ -197,24 +803,80 @@ pub async fn run_server() -> std::io::Result<()> {
     let delete_mqtt_state = web::Data::new(DeleteMqttAppState {
         delete_mqtt_service,
     });
+    let restore_mqtt_state = web::Data::new(RestoreMqttAppState {
+        restore_mqtt_service,
+    });
+    let rotate_mqtt_password_state = web::Data::new(RotateMqttPasswordAppState {
+        rotate_mqtt_password_service,
+    });
+    let update_mqtt_state = web::Data::new(UpdateMqttAppState {
+        update_mqtt_service,
+    });
+    let import_mqtt_state = web::Data::new(ImportMqttAppState {
+        import_mqtt_service,
+    });
+    let provision_mqtt_state = web::Data::new(ProvisionMqttAppState {
+        provision_mqtt_service,
+    });
+    let export_mqtt_state = web::Data::new(ExportMqttAppState {
+        export_mqtt_service,
+    });
+    let import_mosquitto_state = web::Data::new(ImportMosquittoAppState {
+        import_mosquitto_service,
+    });
+    let tenant_state = web::Data::new(TenantAppState {
+        tenant_service: Arc::clone(&tenant_service),
+        quota_service,
+    });
+    let api_key_state = web::Data::new(ApiKeyAppState {
+        api_key_service: Arc::clone(&api_key_service),
+    });
+    let audit_state = web::Data::new(AuditAppState { audit_service });
+    let api_key_middleware_service = Arc::clone(&api_key_service);
     let mysql_data = web::Data::new(db_conn.clone());
 
     // =====================
     // 🌐 Start Server
     // =====================
-    info!("🚀 Actix server running on http://0.0.0.0:5500");
-    let server = HttpServer::new(move || {
+    let scheme = if tls_server_config.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let split_hooks = hook_bind_address.is_some() || hook_unix_socket.is_some();
+    info!("🚀 Actix server running on {}://{}", scheme, bind_address);
+    let admin_tenant_service = Arc::clone(&tenant_service);
+    let admin_api_key_service = Arc::clone(&api_key_middleware_service);
+    let admin_signing_service = Arc::clone(&request_signing_service);
+    let admin_rate_limit_service = Arc::clone(&rate_limit_service);
+    let admin_ip_allow_list_service = Arc::clone(&ip_allow_list_service);
+    let admin_client_cert_scopes = Arc::clone(&client_cert_scopes);
+    let hook_login_state = mqtt_login_state.clone();
+    let hook_acl_state = mqtt_acl_state.clone();
+    let admin_server = HttpServer::new(move || {
         App::new()
             .app_data(create_mqtt_state.clone())
             .app_data(get_mqtt_credentials_state.clone())
             .app_data(get_mqtt_list_state.clone())
+            .app_data(get_mqtt_user_state.clone())
             .app_data(mqtt_login_state.clone())
             .app_data(mqtt_acl_state.clone())
             .app_data(delete_mqtt_state.clone())
+            .app_data(restore_mqtt_state.clone())
+            .app_data(rotate_mqtt_password_state.clone())
+            .app_data(update_mqtt_state.clone())
+            .app_data(import_mqtt_state.clone())
+            .app_data(provision_mqtt_state.clone())
+            .app_data(export_mqtt_state.clone())
+            .app_data(import_mosquitto_state.clone())
+            .app_data(tenant_state.clone())
+            .app_data(api_key_state.clone())
+            .app_data(audit_state.clone())
             .app_data(mysql_data.clone())
             .wrap(PoweredByMiddleware)
             .wrap(RequestLoggerMiddleware)
             .wrap(middleware::Compress::default())
+            .wrap(RequestIdMiddleware)
             // 🩺 Root API — health check
             .route("/", web::get().to(healthcheck))
             // 📚 Swagger UI
@@ -225,20 +887,88 @@ pub async fn run_server() -> std::io::Result<()> {
             // 👥 Mqtt endpoints
             .service(
                 web::scope("/mqtt")
-                    .wrap(ApiKeyMiddleware)
-                    .route("/create", web::post().to(create_mqtt_handler))
-                    .route("/check", web::post().to(login_with_credentials_handler))
-                    .route("/credentials/{username}", web::get().to(get_mqtt_credentials_handler))
-                    .route("/acl", web::post().to(mqtt_acl_handler))
-                    .route("/{username}", web::delete().to(delete_mqtt))
-                    // Development only
-                    .route("", web::get().to(get_mqtt_list_handler)),
+                    .wrap(RateLimitMiddleware::per_key(Arc::clone(
+                        &admin_rate_limit_service,
+                    )))
+                    .wrap(ApiKeyMiddleware::new(
+                        Arc::clone(&admin_tenant_service),
+                        Arc::clone(&admin_api_key_service),
+                        Arc::clone(&admin_signing_service),
+                    ))
+                    .wrap(RateLimitMiddleware::per_ip(Arc::clone(
+                        &admin_rate_limit_service,
+                    )))
+                    .wrap(IpAllowListMiddleware::new(Arc::clone(
+                        &admin_ip_allow_list_service,
+                    )))
+                    .configure(|cfg| {
+                        if !split_hooks {
+                            hook_routes(cfg);
+                        }
+                    })
+                    .configure(|cfg| admin_routes(cfg, import_max_bytes)),
             )
     })
-    .bind(("0.0.0.0", 5500))?
+    .on_connect(store_client_certificate(admin_client_cert_scopes));
+
+    let admin_server = match tls_server_config.clone() {
+        Some(config) => admin_server.bind_rustls_0_23(bind_address.as_str(), config)?,
+        None => admin_server.bind(bind_address.as_str())?,
+    }
     .run();
 
-    let server_handle = server.handle();
+    // 🪝 Broker hooks on their own listener, so the management API can be firewalled away
+    // from the broker network. A co-located broker can use the Unix socket instead of TCP.
+    let hook_server = if split_hooks {
+        let mut hook_server = HttpServer::new(move || {
+            App::new()
+                .app_data(hook_login_state.clone())
+                .app_data(hook_acl_state.clone())
+                .wrap(PoweredByMiddleware)
+                .wrap(RequestLoggerMiddleware)
+                .wrap(RequestIdMiddleware)
+                // 🩺 Root API — health check
+                .route("/", web::get().to(healthcheck))
+                .service(
+                    web::scope("/mqtt")
+                        .wrap(RateLimitMiddleware::per_key(Arc::clone(
+                            &rate_limit_service,
+                        )))
+                        .wrap(ApiKeyMiddleware::new(
+                            Arc::clone(&tenant_service),
+                            Arc::clone(&api_key_middleware_service),
+                            Arc::clone(&request_signing_service),
+                        ))
+                        .wrap(RateLimitMiddleware::per_ip(Arc::clone(&rate_limit_service)))
+                        .wrap(IpAllowListMiddleware::new(Arc::clone(
+                            &ip_allow_list_service,
+                        )))
+                        .configure(hook_routes),
+                )
+        })
+        .on_connect(store_client_certificate(client_cert_scopes));
+
+        if let Some(hook_bind_address) = &hook_bind_address {
+            info!("🪝 Hook API running on {}://{}", scheme, hook_bind_address);
+            hook_server = match tls_server_config {
+                Some(config) => hook_server.bind_rustls_0_23(hook_bind_address.as_str(), config)?,
+                None => hook_server.bind(hook_bind_address.as_str())?,
+            };
+        }
+        if let Some(socket) = &hook_unix_socket {
+            remove_stale_socket(socket)?;
+            hook_server = hook_server.bind_uds(socket)?;
+            set_socket_mode(socket, hook_unix_socket_mode)?;
+            info!("🪝 Hook API running on unix:{}", socket.display());
+        }
+        Some(hook_server.run())
+    } else {
+        None
+    };
+
+    let server_handles = std::iter::once(admin_server.handle())
+        .chain(hook_server.as_ref().map(|server| server.handle()))
+        .collect::<Vec<_>>();
 
     // Handle graceful shutdown signals
     tokio::spawn(async move {
@@ -246,19 +976,60 @@ pub async fn run_server() -> std::io::Result<()> {
             .await
             .expect("Failed to listen for ctrl-c");
         info!("Signals received, starting graceful shutdown...");
-        server_handle.stop(true).await;
+        for server_handle in server_handles {
+            server_handle.stop(true).await;
+        }
     });
 
-    let server_result = server.await.map_err(|e| {
-        error!("❌ Server error: {}", e);
-        e
-    });
+    let hook_server = async move {
+        match hook_server {
+            Some(hook_server) => hook_server.await,
+            None => Ok(()),
+        }
+    };
+    let server_result = tokio::try_join!(admin_server, hook_server)
+        .map(|_| ())
+        .map_err(|e| {
+            error!("❌ Server error: {}", e);
+            e
+        });
 
     // =====================
     // 🧹 Cleanup
     // =====================
     info!("Shutting down server...");
 
+    if let Some(job) = expiry_job {
+        job.abort();
+    }
+
+    purge_job.abort();
+    login_activity_job.abort();
+    tenant_reload_job.abort();
+    api_key_reload_job.abort();
+    reload_signal_task.abort();
+    if let Some(job) = tls_reload_job {
+        job.abort();
+    }
+    info!("Flushing buffered login activity...");
+    if let Err(e) = login_activity_service.flush().await {
+        error!("❌ Failed to flush login activity: {}", e);
+    }
+
+    if let Err(e) = api_key_service.flush_last_used().await {
+        error!("❌ Failed to flush API key last use: {}", e);
+    }
+
+    if let Some(socket) = &hook_unix_socket
+        && let Err(e) = std::fs::remove_file(socket)
+    {
+        error!(
+            "❌ Failed to remove Unix socket {}: {}",
+            socket.display(),
+            e
+        );
+    }
+
     info!("Closing database connection...");
     close_db(db_conn).await;
 
@@ -0,0 +1,444 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+
+use crate::dtos::api_key_dto::{
+    ApiKeyContext, ApiKeyDTO, ApiScope, CreateApiKeyDTO, CreatedApiKeyDTO,
+};
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::api_key_entity::Model as ApiKeyEntity;
+use crate::repositories::api_key_repository::{ApiKeyRepository, NewApiKey};
+use crate::repositories::repository_error::MqttRepositoryError;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::services::tenant_service::TenantService;
+use crate::utils::api_key::{digest_api_key, find_api_key, generate_api_key, hash_api_key};
+
+/// A cache miss reloads the keys at most this often, so unknown keys cannot hammer the database.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_API_KEY_NAME_LENGTH: usize = 64;
+
+/// A database API key that authorized a request.
+pub struct AuthorizedApiKey {
+    pub context: ApiKeyContext,
+    /// Tenant the key is limited to
+    pub tenant_id: Option<i32>,
+}
+
+/// API keys for `ApiKeyMiddleware`: the static keys granting every scope, and the keys stored
+/// in `mqtt_api_keys`, cached in memory by hash.
+///
+/// Static keys come from `API_KEY_FILE` when set, so they can be rotated at runtime, and from
+/// `API_KEY` / `API_KEY_SECONDARY` otherwise. The first one is the primary key; the others
+/// are accepted too while clients move over. Both sources are re-read by `reload`.
+///
+/// Last-used times are buffered and written by the API key reload job.
+pub struct ApiKeyService {
+    repo: Arc<ApiKeyRepository>,
+    tenants: Arc<TenantService>,
+    audit: Arc<AuditService>,
+    static_keys_file: Option<PathBuf>,
+    static_keys: RwLock<Vec<[u8; 32]>>,
+    cache: RwLock<HashMap<String, ApiKeyEntity>>,
+    last_reload: Mutex<Option<Instant>>,
+    last_used: Mutex<HashMap<i32, DateTime<Utc>>>,
+}
+
+impl ApiKeyService {
+    pub fn new(
+        repo: Arc<ApiKeyRepository>,
+        tenants: Arc<TenantService>,
+        audit: Arc<AuditService>,
+        static_keys_file: Option<PathBuf>,
+    ) -> Self {
+        Self {
+            repo,
+            tenants,
+            audit,
+            static_keys_file,
+            static_keys: RwLock::new(Vec::new()),
+            cache: RwLock::new(HashMap::new()),
+            last_reload: Mutex::new(None),
+            last_used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Loads every API key into the cache and re-reads the static keys.
+    pub async fn reload(&self) -> Result<usize, MqttServiceError> {
+        self.reload_static_keys()?;
+        let keys = self.repo.get_all().await?;
+        let cache: HashMap<String, ApiKeyEntity> = keys
+            .into_iter()
+            .map(|key| (key.key_hash.clone(), key))
+            .collect();
+
+        let count = cache.len();
+        *self.cache.write().unwrap_or_else(|e| e.into_inner()) = cache;
+        *self.last_reload.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        debug!("[Service | ApiKey] Loaded {} API keys", count);
+        Ok(count)
+    }
+
+    /// Re-reads the static keys. On failure the previous ones stay in use.
+    fn reload_static_keys(&self) -> Result<usize, MqttServiceError> {
+        let keys = match &self.static_keys_file {
+            Some(path) => {
+                let content = std::fs::read_to_string(path).map_err(|e| {
+                    MqttServiceError::InternalError(format!(
+                        "Failed to read API key file {}: {}",
+                        path.display(),
+                        e
+                    ))
+                })?;
+                content
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_string)
+                    .collect::<Vec<_>>()
+            }
+            None => {
+                let primary = std::env::var("API_KEY").unwrap_or_default();
+                let secondary = std::env::var("API_KEY_SECONDARY").unwrap_or_default();
+                std::iter::once(primary.trim().to_string())
+                    .chain(secondary.split(',').map(|key| key.trim().to_string()))
+                    .filter(|key| !key.is_empty())
+                    .collect::<Vec<_>>()
+            }
+        };
+
+        let digests: Vec<[u8; 32]> = keys.iter().map(|key| digest_api_key(key)).collect();
+        let count = digests.len();
+        *self.static_keys.write().unwrap_or_else(|e| e.into_inner()) = digests;
+        debug!("[Service | ApiKey] Loaded {} static API keys", count);
+        Ok(count)
+    }
+
+    /// Reloads after a cache miss, so keys created through another instance are picked up.
+    async fn reload_after_miss(&self) -> bool {
+        {
+            let mut last_reload = self.last_reload.lock().unwrap_or_else(|e| e.into_inner());
+            if last_reload.is_some_and(|at| at.elapsed() < MIN_RELOAD_INTERVAL) {
+                return false;
+            }
+            *last_reload = Some(Instant::now());
+        }
+        self.reload().await.is_ok()
+    }
+
+    /// Puts a key just written to the database into the cache. A full reload could fail after
+    /// the row is committed, and the caller must still get the result of the write.
+    fn cache_key(&self, key: &ApiKeyEntity) {
+        self.cache
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.key_hash.clone(), key.clone());
+    }
+
+    fn cached(&self, hash: &str) -> Option<ApiKeyEntity> {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        cache.get(hash).cloned()
+    }
+
+    /// Static key, or active (neither revoked nor expired) database key, matching `api_key`.
+    /// The use of a database key is buffered for `last_used_at`.
+    pub async fn authenticate(&self, api_key: &str) -> Option<AuthorizedApiKey> {
+        if api_key.is_empty() {
+            return None;
+        }
+        let static_match = {
+            let static_keys = self.static_keys.read().unwrap_or_else(|e| e.into_inner());
+            find_api_key(api_key, &static_keys)
+        };
+        if let Some(index) = static_match {
+            let name = match index {
+                0 => "static:primary".to_string(),
+                n => format!("static:secondary-{}", n),
+            };
+            return Some(AuthorizedApiKey {
+                context: ApiKeyContext {
+                    name,
+                    scopes: vec![ApiScope::Admin],
+                },
+                tenant_id: None,
+            });
+        }
+
+        // Database keys are looked up by hash; a random 256-bit key cannot be guessed
+        // byte by byte from the timing of a hash map lookup.
+        let hash = hash_api_key(api_key);
+        let key = match self.cached(&hash) {
+            Some(key) => key,
+            None if self.reload_after_miss().await => self.cached(&hash)?,
+            None => return None,
+        };
+
+        let now = Utc::now();
+        if key.revoked_at.is_some() || key.expires_at.is_some_and(|at| at <= now) {
+            debug!(
+                "[Service | ApiKey] API key {} is revoked or expired",
+                key.name
+            );
+            return None;
+        }
+
+        self.last_used
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.id, now);
+        Some(AuthorizedApiKey {
+            context: ApiKeyContext {
+                name: key.name,
+                scopes: ApiScope::parse_list(&key.scopes),
+            },
+            tenant_id: key.tenant_id,
+        })
+    }
+
+    /// Writes the buffered last-used times. On failure they are put back, unless the key was
+    /// used again in the meantime.
+    pub async fn flush_last_used(&self) -> Result<usize, MqttServiceError> {
+        let batch: Vec<(i32, DateTime<Utc>)> = {
+            let mut last_used = self.last_used.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *last_used).into_iter().collect()
+        };
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = self.repo.record_last_used(&batch).await {
+            let mut last_used = self.last_used.lock().unwrap_or_else(|e| e.into_inner());
+            for (id, at) in batch {
+                last_used.entry(id).or_insert(at);
+            }
+            return Err(e.into());
+        }
+        Ok(batch.len())
+    }
+
+    /// Keys visible to the caller: all of them, or only its own tenant's for a tenant-scoped key.
+    pub async fn list_keys(
+        &self,
+        tenant: &TenantContext,
+    ) -> Result<Vec<ApiKeyDTO>, MqttServiceError> {
+        let keys = self.repo.get_all().await?;
+        let mut dtos = Vec::with_capacity(keys.len());
+        for key in keys {
+            if tenant.scoped && key.tenant_id != Some(tenant.id) {
+                continue;
+            }
+            dtos.push(ApiKeyDTO {
+                tenant: self.tenant_name(key.tenant_id).await,
+                scopes: ApiScope::parse_list(&key.scopes),
+                id: key.id,
+                name: key.name,
+                expires_at: key.expires_at,
+                last_used_at: key.last_used_at,
+                revoked_at: key.revoked_at,
+                created_at: key.created_at,
+            });
+        }
+        Ok(dtos)
+    }
+
+    pub async fn create_key(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        mut dto: CreateApiKeyDTO,
+    ) -> Result<CreatedApiKeyDTO, MqttServiceError> {
+        dto.name = dto.name.trim().to_string();
+        let name = dto.name.clone();
+        let result = self.create(tenant, dto).await;
+        let change = result.as_ref().map(AuditChange::created);
+        self.audit
+            .record(actor, AuditAction::ApiKeyCreate, Some(&name), change)
+            .await;
+        result
+    }
+
+    async fn create(
+        &self,
+        tenant: &TenantContext,
+        mut dto: CreateApiKeyDTO,
+    ) -> Result<CreatedApiKeyDTO, MqttServiceError> {
+        let mut scopes = Vec::with_capacity(dto.scopes.len());
+        for scope in dto.scopes.drain(..) {
+            if !scopes.contains(&scope) {
+                scopes.push(scope);
+            }
+        }
+        dto.scopes = scopes;
+        self.create_key_validation(&dto)?;
+
+        // A tenant-scoped caller can only create keys for its own tenant.
+        let target = if tenant.scoped || dto.tenant.is_some() {
+            Some(
+                self.tenants
+                    .resolve_tenant(tenant, dto.tenant.as_deref())
+                    .await?,
+            )
+        } else {
+            None
+        };
+
+        let api_key = generate_api_key();
+        let created = match self
+            .repo
+            .create_api_key(NewApiKey {
+                name: dto.name,
+                tenant_id: target.as_ref().map(|t| t.id),
+                key_hash: hash_api_key(&api_key),
+                scopes: ApiScope::join(&dto.scopes),
+                expires_at: dto.expires_at,
+            })
+            .await
+        {
+            Ok(created) => created,
+            Err(MqttRepositoryError::AlreadyExists) => {
+                return Err(MqttServiceError::Conflict(
+                    "API key name already exists".into(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.cache_key(&created);
+
+        info!(
+            "🔑 API key {} created with scopes {}",
+            created.name, created.scopes
+        );
+        Ok(CreatedApiKeyDTO {
+            id: created.id,
+            name: created.name,
+            tenant: target.map(|t| t.name),
+            scopes: ApiScope::parse_list(&created.scopes),
+            expires_at: created.expires_at,
+            api_key,
+        })
+    }
+
+    /// Revokes a key; it stops working immediately on this instance.
+    pub async fn revoke_key(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        id: i32,
+    ) -> Result<ApiKeyDTO, MqttServiceError> {
+        let result = self.revoke(tenant, id).await;
+        let target = match &result {
+            Ok((_, revoked)) => revoked.name.clone(),
+            Err(_) => id.to_string(),
+        };
+        let change = result
+            .as_ref()
+            .map(|(before, after)| AuditChange::updated(before, after));
+        self.audit
+            .record(actor, AuditAction::ApiKeyRevoke, Some(&target), change)
+            .await;
+        let (_, revoked) = result?;
+
+        info!("🔑 API key {} revoked", revoked.name);
+        Ok(ApiKeyDTO {
+            tenant: self.tenant_name(revoked.tenant_id).await,
+            scopes: ApiScope::parse_list(&revoked.scopes),
+            id: revoked.id,
+            name: revoked.name,
+            expires_at: revoked.expires_at,
+            last_used_at: revoked.last_used_at,
+            revoked_at: revoked.revoked_at,
+            created_at: revoked.created_at,
+        })
+    }
+
+    /// Returns the key before and after the revocation.
+    async fn revoke(
+        &self,
+        tenant: &TenantContext,
+        id: i32,
+    ) -> Result<(ApiKeyEntity, ApiKeyEntity), MqttServiceError> {
+        let existing = {
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            cache.values().find(|key| key.id == id).cloned()
+        };
+        let existing = match existing {
+            Some(key) => Some(key),
+            None => {
+                self.reload().await?;
+                let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+                cache.values().find(|key| key.id == id).cloned()
+            }
+        };
+        let Some(existing) =
+            existing.filter(|key| !tenant.scoped || key.tenant_id == Some(tenant.id))
+        else {
+            return Err(MqttServiceError::MqttNotFound("API key not found".into()));
+        };
+        if existing.revoked_at.is_some() {
+            return Err(MqttServiceError::Conflict(
+                "API key is already revoked".into(),
+            ));
+        }
+
+        let revoked = match self.repo.revoke(id, Utc::now()).await {
+            Ok(revoked) => revoked,
+            Err(MqttRepositoryError::NotFound) => {
+                return Err(MqttServiceError::MqttNotFound("API key not found".into()));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.cache_key(&revoked);
+        Ok((existing, revoked))
+    }
+
+    async fn tenant_name(&self, tenant_id: Option<i32>) -> Option<String> {
+        match tenant_id {
+            Some(id) => self.tenants.by_id(id).await.map(|t| t.name),
+            None => None,
+        }
+    }
+
+    fn create_key_validation(&self, dto: &CreateApiKeyDTO) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if dto.name.is_empty()
+            || dto.name.len() > MAX_API_KEY_NAME_LENGTH
+            || !dto
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            errors.push(ValidationError {
+                field: "name".to_string(),
+                message: format!(
+                    "name must be 1-{} characters of letters, digits, '-', '_' and '.'",
+                    MAX_API_KEY_NAME_LENGTH
+                ),
+            });
+        }
+
+        if dto.scopes.is_empty() {
+            errors.push(ValidationError {
+                field: "scopes".to_string(),
+                message: "scopes cannot be empty".to_string(),
+            });
+        }
+
+        if dto.expires_at.is_some_and(|at| at <= Utc::now()) {
+            errors.push(ValidationError {
+                field: "expires_at".to_string(),
+                message: "expires_at must be in the future".to_string(),
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+        Ok(true)
+    }
+}
@@ -0,0 +1,328 @@
+use log::{debug, error};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{
+    AuditAction, AuditContext, AuditEventDTO, AuditOutcome, GetAuditEventsDTO,
+    GetAuditEventsQueryDTO,
+};
+use crate::dtos::mqtt_dto::PaginationDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::audit_event_repository::{
+    AuditEventFilter, AuditEventRepository, NewAuditEvent,
+};
+use crate::services::get_mqtt_list_service::page_error;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 500;
+const MAX_ERROR_LEN: usize = 255;
+
+/// Fields never written to the audit log; a change to one shows as `[redacted]`.
+const SECRET_FIELDS: [&str; 7] = [
+    "password",
+    "previous_password",
+    "api_key",
+    "api_key_hash",
+    "key_hash",
+    "secret",
+    "token",
+];
+const REDACTED: &str = "[redacted]";
+
+/// State of the target before and after a successful action, as JSON objects.
+pub struct AuditChange {
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl AuditChange {
+    /// An action that changes nothing, e.g. revealing credentials.
+    pub fn none() -> Self {
+        Self {
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn created(after: &impl Serialize) -> Self {
+        Self {
+            before: None,
+            after: serde_json::to_value(after).ok(),
+        }
+    }
+
+    pub fn updated(before: &impl Serialize, after: &impl Serialize) -> Self {
+        Self {
+            before: serde_json::to_value(before).ok(),
+            after: serde_json::to_value(after).ok(),
+        }
+    }
+
+    pub fn deleted(before: &impl Serialize) -> Self {
+        Self {
+            before: serde_json::to_value(before).ok(),
+            after: None,
+        }
+    }
+
+    /// Adds a field kept apart from the serialized state, e.g. tags stored in their own table.
+    pub fn with_field(
+        mut self,
+        field: &str,
+        before: &impl Serialize,
+        after: &impl Serialize,
+    ) -> Self {
+        let before = serde_json::to_value(before).ok();
+        let after = serde_json::to_value(after).ok();
+        for (state, value) in [(&mut self.before, before), (&mut self.after, after)] {
+            if let Some(Value::Object(map)) = state
+                && let Some(value) = value
+            {
+                map.insert(field.to_string(), value);
+            }
+        }
+        self
+    }
+
+    /// `{"field": {"before": ..., "after": ...}}` for every field that differs, secrets redacted.
+    fn diff(self) -> Option<Value> {
+        let before = as_object(self.before);
+        let after = as_object(self.after);
+        let fields: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+
+        let mut changes = Map::new();
+        for field in fields {
+            let old = before.get(field).unwrap_or(&Value::Null);
+            let new = after.get(field).unwrap_or(&Value::Null);
+            if old == new {
+                continue;
+            }
+            let mut change = Map::new();
+            change.insert("before".to_string(), redact(field, old));
+            change.insert("after".to_string(), redact(field, new));
+            changes.insert(field.clone(), Value::Object(change));
+        }
+        (!changes.is_empty()).then_some(Value::Object(changes))
+    }
+}
+
+/// Records who did what to which user, tenant or API key, and lists the records.
+pub struct AuditService {
+    repo: Arc<AuditEventRepository>,
+}
+
+impl AuditService {
+    pub fn new(repo: Arc<AuditEventRepository>) -> Self {
+        Self { repo }
+    }
+
+    /// Records an action with its outcome. A failure to write the event is logged and does not
+    /// fail the action, which has already happened.
+    pub async fn record(
+        &self,
+        context: &AuditContext,
+        action: AuditAction,
+        target: Option<&str>,
+        result: Result<AuditChange, &MqttServiceError>,
+    ) {
+        let (outcome, changes, error) = match result {
+            Ok(change) => (AuditOutcome::Success, change.diff(), None),
+            Err(e) => (
+                AuditOutcome::Failure,
+                None,
+                Some(e.to_string().chars().take(MAX_ERROR_LEN).collect()),
+            ),
+        };
+        let event = NewAuditEvent {
+            tenant_id: context.tenant_id,
+            actor: context.actor.clone(),
+            action: action.as_str().to_string(),
+            target: target.map(str::to_string),
+            request_id: context.request_id.clone(),
+            source_ip: context.source_ip.clone(),
+            changes,
+            outcome: outcome.as_str().to_string(),
+            error,
+        };
+        if let Err(e) = self.repo.insert(event).await {
+            error!(
+                "❌ Failed to record audit event {} by {}: {}",
+                action.as_str(),
+                context.actor,
+                e
+            );
+        }
+    }
+
+    /// Events visible to the caller: all of them, or only its own tenant's for a tenant-scoped key.
+    pub async fn list_events(
+        &self,
+        tenant: &TenantContext,
+        query: GetAuditEventsQueryDTO,
+    ) -> Result<GetAuditEventsDTO, MqttServiceError> {
+        self.list_events_validation(&query)?;
+
+        let page = query.page.unwrap_or(1);
+        let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        let filter = AuditEventFilter {
+            tenant_id: tenant.scoped.then_some(tenant.id),
+            actor: query.actor.filter(|v| !v.is_empty()),
+            action: query.action.filter(|v| !v.is_empty()),
+            target: query.target.filter(|v| !v.is_empty()),
+            outcome: query.outcome.map(|outcome| outcome.as_str().to_string()),
+            request_id: query.request_id.filter(|v| !v.is_empty()),
+            from: query.from,
+            until: query.until,
+            page,
+            per_page,
+        };
+
+        let (events, total_items, total_pages) = self.repo.get_audit_events(filter).await?;
+        debug!("[Service | Audit] Audit events retrieved successfully.");
+        Ok(GetAuditEventsDTO {
+            events: events.into_iter().map(AuditEventDTO::from).collect(),
+            pagination: PaginationDTO {
+                page,
+                per_page,
+                total_items,
+                total_pages,
+            },
+        })
+    }
+
+    fn list_events_validation(
+        &self,
+        query: &GetAuditEventsQueryDTO,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if let Some(message) = query
+            .page
+            .and_then(|page| page_error(page, query.per_page.unwrap_or(DEFAULT_PER_PAGE)))
+        {
+            errors.push(ValidationError {
+                field: "page".to_string(),
+                message,
+            });
+        }
+
+        if query
+            .per_page
+            .is_some_and(|per_page| per_page == 0 || per_page > MAX_PER_PAGE)
+        {
+            errors.push(ValidationError {
+                field: "per_page".to_string(),
+                message: format!("per_page must be between 1 and {}", MAX_PER_PAGE),
+            });
+        }
+
+        if let Some(action) = query.action.as_deref().filter(|v| !v.is_empty())
+            && AuditAction::parse(action).is_none()
+        {
+            errors.push(ValidationError {
+                field: "action".to_string(),
+                message: format!("unknown action `{}`", action),
+            });
+        }
+
+        if let (Some(from), Some(until)) = (query.from, query.until)
+            && until <= from
+        {
+            errors.push(ValidationError {
+                field: "until".to_string(),
+                message: "until must be later than from".to_string(),
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        Ok(true)
+    }
+}
+
+fn as_object(value: Option<Value>) -> Map<String, Value> {
+    match value {
+        Some(Value::Object(map)) => map,
+        Some(Value::Null) | None => Map::new(),
+        Some(other) => Map::from_iter([("value".to_string(), other)]),
+    }
+}
+
+/// The value of `field` as written to the log: secrets replaced, nested ones included.
+fn redact(field: &str, value: &Value) -> Value {
+    if SECRET_FIELDS.contains(&field) && !value.is_null() {
+        return Value::String(REDACTED.to_string());
+    }
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), redact(key, value)))
+                .collect(),
+        ),
+        Value::Array(items) => Value::Array(items.iter().map(|item| redact("", item)).collect()),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn diffs_only_changed_fields() {
+        let change = AuditChange::updated(
+            &json!({"username": "a", "is_enabled": true}),
+            &json!({"username": "a", "is_enabled": false}),
+        )
+        .with_field("tags", &["x"], &["x", "y"]);
+        assert_eq!(
+            change.diff(),
+            Some(json!({
+                "is_enabled": {"before": true, "after": false},
+                "tags": {"before": ["x"], "after": ["x", "y"]},
+            }))
+        );
+        assert_eq!(AuditChange::none().diff(), None);
+        assert_eq!(
+            AuditChange::updated(&json!({"a": 1}), &json!({"a": 1})).diff(),
+            None
+        );
+    }
+
+    #[test]
+    fn redacts_secrets() {
+        let change = AuditChange::created(&json!({"username": "a", "password": "hunter2"}));
+        assert_eq!(
+            change.diff(),
+            Some(json!({
+                "password": {"before": null, "after": REDACTED},
+                "username": {"before": null, "after": "a"},
+            }))
+        );
+
+        // Clearing a secret shows that it was set, not its value.
+        let change = AuditChange::updated(
+            &json!({"previous_password": "old"}),
+            &json!({"previous_password": null}),
+        );
+        assert_eq!(
+            change.diff(),
+            Some(json!({"previous_password": {"before": REDACTED, "after": null}}))
+        );
+    }
+
+    #[test]
+    fn redacts_nested_secrets() {
+        let value = json!({"keys": [{"name": "k", "key_hash": "abc"}], "extra": {"token": "t"}});
+        assert_eq!(
+            redact("changes", &value),
+            json!({"keys": [{"name": "k", "key_hash": REDACTED}], "extra": {"token": REDACTED}})
+        );
+        assert_eq!(redact("secret", &json!(["a"])), json!(REDACTED));
+    }
+}
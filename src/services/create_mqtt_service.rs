@@ -1,34 +1,86 @@
 use log::debug;
 use std::sync::Arc;
 
-use crate::dtos::mqtt_dto::CreateMqttDTO;
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{CreateMqttDTO, CreateMqttResultDTO};
+use crate::dtos::tenant_dto::TenantContext;
 use crate::repositories::create_mqtt_repository::CreateMqttRepository;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::import_mqtt_repository::NewMqttUser;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::quota_service::QuotaService;
 use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::attributes::{
+    client_id_errors, metadata_error, normalize_client_ids, normalize_tags, tag_errors,
+};
+use crate::utils::credential_policy::CredentialPolicy;
 use crate::utils::encryption::encrypt_password;
+use crate::utils::password_generator::{PasswordGeneratorConfig, password_length_error};
 
 pub struct CreateMqttService {
     repo_create: Arc<CreateMqttRepository>,
     repo_get: Arc<GetMqttByUsernameRepository>,
+    quota: Arc<QuotaService>,
+    password_generator: PasswordGeneratorConfig,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
 }
 
 impl CreateMqttService {
     pub fn new(
         repo_create: Arc<CreateMqttRepository>,
         repo_get: Arc<GetMqttByUsernameRepository>,
+        quota: Arc<QuotaService>,
+        password_generator: PasswordGeneratorConfig,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
     ) -> Self {
         Self {
             repo_create,
             repo_get,
+            quota,
+            password_generator,
+            policy,
+            audit,
         }
     }
 
-    pub async fn create_mqtt(&self, dto: CreateMqttDTO) -> Result<bool, MqttServiceError> {
+    pub async fn create_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        mut dto: CreateMqttDTO,
+    ) -> Result<CreateMqttResultDTO, MqttServiceError> {
+        dto.username = self.policy.normalize_username(&dto.username);
+        let username = dto.username.clone();
+        match self.create(tenant, dto).await {
+            Ok((created, change)) => {
+                self.audit
+                    .record(actor, AuditAction::UserCreate, Some(&username), Ok(change))
+                    .await;
+                Ok(created)
+            }
+            Err(e) => {
+                self.audit
+                    .record(actor, AuditAction::UserCreate, Some(&username), Err(&e))
+                    .await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Returns the created user along with its state for the audit log, taken before the
+    /// request is consumed.
+    async fn create(
+        &self,
+        tenant: &TenantContext,
+        dto: CreateMqttDTO,
+    ) -> Result<(CreateMqttResultDTO, AuditChange), MqttServiceError> {
         self.create_mqtt_validation(&dto)?;
 
         if self
             .repo_get
-            .get_mqtt_by_username(&dto.username)
+            .get_mqtt_by_username(tenant.id, &dto.username)
             .await
             .is_ok()
         {
@@ -37,15 +89,58 @@ impl CreateMqttService {
             ));
         }
 
-        let encrypted = encrypt_password(&dto.password).map_err(|e| MqttServiceError::InternalError(e))?;
+        if self
+            .repo_get
+            .get_deleted_mqtt_by_username(tenant.id, &dto.username)
+            .await
+            .is_ok()
+        {
+            return Err(MqttServiceError::Conflict(
+                "MQTT user is in the trash; restore it or delete it with hard=true first".into(),
+            ));
+        }
+
+        self.quota
+            .check_users(tenant, &[(dto.username.as_str(), dto.is_superuser)], false)
+            .await?;
+
+        let (password, generated) = match dto.password {
+            Some(ref password) if !dto.generate_password => (password.clone(), false),
+            _ => (
+                self.password_generator
+                    .generate(dto.password_length, dto.password_alphabet),
+                true,
+            ),
+        };
+        let change = AuditChange::created(&dto);
+        let encrypted = encrypt_password(&password).map_err(MqttServiceError::InternalError)?;
+        let user = NewMqttUser {
+            tenant_id: tenant.id,
+            username: dto.username.clone(),
+            password: encrypted,
+            is_superuser: dto.is_superuser,
+            is_enabled: true,
+            metadata: dto.metadata,
+            valid_from: dto.valid_from,
+            valid_until: dto.valid_until,
+            provision_batch_id: None,
+        };
         self.repo_create
-            .create_mqtt(&dto.username, &encrypted, dto.is_superuser)
+            .create_mqtt(
+                user,
+                &normalize_tags(&dto.tags),
+                &normalize_client_ids(&dto.client_ids),
+            )
             .await?;
         debug!(
-            "[Service | CreateMQTT] User MQTT created successfully: {}",
-            &dto.username
+            "[Service | CreateMQTT] User MQTT created successfully: {} (tenant {})",
+            &dto.username, tenant.name
         );
-        Ok(true)
+        let created = CreateMqttResultDTO {
+            username: dto.username,
+            password: generated.then_some(password),
+        };
+        Ok((created, change))
     }
 
     fn create_mqtt_validation(&self, dto: &CreateMqttDTO) -> Result<bool, MqttServiceError> {
@@ -55,12 +150,75 @@ impl CreateMqttService {
                 field: "username".to_string(),
                 message: "username cannot be empty".to_string(),
             });
+        } else {
+            for message in self.policy.username_errors(&dto.username) {
+                errors.push(ValidationError {
+                    field: "username".to_string(),
+                    message,
+                });
+            }
         }
 
-        if dto.password.trim().is_empty() {
-            errors.push(ValidationError {
+        match (&dto.password, dto.generate_password) {
+            (Some(_), true) => errors.push(ValidationError {
+                field: "password".to_string(),
+                message: "password cannot be set together with generate_password".to_string(),
+            }),
+            (Some(password), false) if password.trim().is_empty() => {
+                errors.push(ValidationError {
+                    field: "password".to_string(),
+                    message: "password cannot be empty".to_string(),
+                })
+            }
+            (Some(password), false) => {
+                for message in self.policy.password_errors(password) {
+                    errors.push(ValidationError {
+                        field: "password".to_string(),
+                        message,
+                    });
+                }
+            }
+            (None, false) => errors.push(ValidationError {
                 field: "password".to_string(),
-                message: "password cannot be empty".to_string(),
+                message: "password is required unless generate_password is true".to_string(),
+            }),
+            _ => {}
+        }
+
+        if let Some(message) = dto.password_length.and_then(password_length_error) {
+            errors.push(ValidationError {
+                field: "password_length".to_string(),
+                message,
+            });
+        }
+
+        if let (Some(from), Some(until)) = (dto.valid_from, dto.valid_until)
+            && until <= from
+        {
+            errors.push(ValidationError {
+                field: "valid_until".to_string(),
+                message: "valid_until must be later than valid_from".to_string(),
+            });
+        }
+
+        if let Some(message) = dto.metadata.as_ref().and_then(metadata_error) {
+            errors.push(ValidationError {
+                field: "metadata".to_string(),
+                message,
+            });
+        }
+
+        for message in tag_errors(&dto.tags) {
+            errors.push(ValidationError {
+                field: "tags".to_string(),
+                message,
+            });
+        }
+
+        for message in client_id_errors(&dto.client_ids) {
+            errors.push(ValidationError {
+                field: "client_ids".to_string(),
+                message,
             });
         }
 
@@ -1,46 +1,129 @@
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model as MqttEntity;
 use crate::repositories::delete_mqtt_repository::DeleteMqttRepository;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::services::audit_service::{AuditChange, AuditService};
 use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::credential_policy::CredentialPolicy;
+use chrono::{Duration, Utc};
 use log::debug;
 use std::sync::Arc;
 
+/// Ten years; longer retention periods are rejected at startup.
+pub const MAX_RETENTION_SECS: i64 = 10 * 365 * 86400;
+
 pub struct DeleteMqttService {
     repo_get: Arc<GetMqttByUsernameRepository>,
     repo_delete: Arc<DeleteMqttRepository>,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+    retention: Duration,
 }
 
 impl DeleteMqttService {
     pub fn new(
         repo_get: Arc<GetMqttByUsernameRepository>,
         repo_delete: Arc<DeleteMqttRepository>,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+        retention: Duration,
     ) -> DeleteMqttService {
         Self {
             repo_get,
             repo_delete,
+            policy,
+            audit,
+            retention,
         }
     }
 
-    pub async fn delete_mqtt(&self, username: &str) -> Result<bool, MqttServiceError> {
+    /// How long trashed users are kept before the purge job removes them.
+    pub fn retention(&self) -> Duration {
+        self.retention
+    }
+
+    /// Moves the user to the trash, or removes it for good when `hard` is set. A hard delete
+    /// also applies to users already in the trash.
+    pub async fn delete_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        username: &str,
+        hard: bool,
+    ) -> Result<bool, MqttServiceError> {
+        let username = self.policy.normalize_username(username);
+        let result = self.delete(tenant, &username, hard).await;
+        let action = if hard {
+            AuditAction::UserPurge
+        } else {
+            AuditAction::UserDelete
+        };
+        let change = result.as_ref().map(|(before, after)| match after {
+            Some(after) => AuditChange::updated(before, after),
+            None => AuditChange::deleted(before),
+        });
+        self.audit
+            .record(actor, action, Some(&username), change)
+            .await;
+        result.map(|_| true)
+    }
+
+    /// Returns the user before the delete and, for a soft delete, the trashed user.
+    async fn delete(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+        hard: bool,
+    ) -> Result<(MqttEntity, Option<MqttEntity>), MqttServiceError> {
         self.validate_username(username)?;
 
-        // Check if user exists first
-        let _ = match self.repo_get.get_mqtt_by_username(username).await {
-            Ok(u) => u,
-            Err(_) => {
-                debug!(
-                    "[Service | DeleteMQTT] User MQTT not found: {}",
-                    username
-                );
-                return Err(MqttServiceError::MqttNotFound("User MQTT not found".into()));
-            }
+        let mqtt = match self
+            .repo_get
+            .get_mqtt_by_username(tenant.id, username)
+            .await
+        {
+            Ok(u) => Some(u),
+            Err(_) if hard => self
+                .repo_get
+                .get_deleted_mqtt_by_username(tenant.id, username)
+                .await
+                .ok(),
+            Err(_) => None,
+        };
+        let Some(mqtt) = mqtt else {
+            debug!("[Service | DeleteMQTT] User MQTT not found: {}", username);
+            return Err(MqttServiceError::MqttNotFound("User MQTT not found".into()));
         };
 
-        self.repo_delete.delete_mqtt(username).await?;
+        let trashed = if hard {
+            self.repo_delete.delete_mqtt(mqtt.id).await?;
+            None
+        } else {
+            let deleted_at = Utc::now();
+            self.repo_delete
+                .soft_delete_mqtt(mqtt.id, deleted_at)
+                .await?;
+            Some(MqttEntity {
+                deleted_at: Some(deleted_at),
+                ..mqtt.clone()
+            })
+        };
         debug!(
-            "[Service | DeleteMQTT] Successfully deleted user MQTT: {}",
-            username
+            "[Service | DeleteMQTT] Successfully deleted user MQTT: {} (hard={})",
+            username, hard
         );
-        Ok(true)
+        Ok((mqtt, trashed))
+    }
+
+    /// Hard-deletes users that have been in the trash longer than the retention period.
+    pub async fn purge_deleted(&self) -> Result<u64, MqttServiceError> {
+        let cutoff = Utc::now()
+            .checked_sub_signed(self.retention)
+            .ok_or_else(|| {
+                MqttServiceError::InternalError("Retention period is out of range".to_string())
+            })?;
+        Ok(self.repo_delete.purge_deleted(cutoff).await?)
     }
 
     fn validate_username(&self, username: &str) -> Result<bool, MqttServiceError> {
@@ -61,3 +144,36 @@ impl DeleteMqttService {
         Ok(true)
     }
 }
+
+/// The trash retention period for `secs`, which must be between 0 and `MAX_RETENTION_SECS`.
+pub fn retention_period(secs: i64) -> Result<Duration, String> {
+    (0..=MAX_RETENTION_SECS)
+        .contains(&secs)
+        .then(|| Duration::try_seconds(secs))
+        .flatten()
+        .ok_or_else(|| {
+            format!(
+                "DELETED_USER_RETENTION_SECS must be between 0 and {}",
+                MAX_RETENTION_SECS
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_retention_periods_in_range() {
+        assert_eq!(retention_period(0), Ok(Duration::zero()));
+        assert_eq!(retention_period(86400), Ok(Duration::days(1)));
+        assert!(retention_period(MAX_RETENTION_SECS).is_ok());
+    }
+
+    #[test]
+    fn rejects_retention_periods_out_of_range() {
+        for secs in [-1, MAX_RETENTION_SECS + 1, i64::MAX, i64::MIN] {
+            assert!(retention_period(secs).is_err(), "{secs}");
+        }
+    }
+}
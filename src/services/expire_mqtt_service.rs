@@ -0,0 +1,85 @@
+use crate::repositories::expire_mqtt_repository::ExpireMqttRepository;
+use crate::services::service_error::MqttServiceError;
+use chrono::Utc;
+use log::debug;
+use std::sync::Arc;
+
+/// What the expiry sweeper does with accounts whose `valid_until` has passed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExpiryAction {
+    None,
+    Disable,
+    Purge,
+}
+
+impl ExpiryAction {
+    /// Parses `ACCOUNT_EXPIRY_ACTION`; an empty value is `none`.
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "none" => Ok(ExpiryAction::None),
+            "disable" => Ok(ExpiryAction::Disable),
+            "purge" | "delete" => Ok(ExpiryAction::Purge),
+            other => Err(format!(
+                "unknown ACCOUNT_EXPIRY_ACTION {:?}, expected none, disable or purge",
+                other
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            ExpiryAction::None => "none",
+            ExpiryAction::Disable => "disable",
+            ExpiryAction::Purge => "purge",
+        }
+    }
+}
+
+pub struct ExpireMqttService {
+    repo: Arc<ExpireMqttRepository>,
+    action: ExpiryAction,
+}
+
+impl ExpireMqttService {
+    pub fn new(repo: Arc<ExpireMqttRepository>, action: ExpiryAction) -> Self {
+        Self { repo, action }
+    }
+
+    pub fn action(&self) -> ExpiryAction {
+        self.action
+    }
+
+    pub async fn sweep_expired(&self) -> Result<u64, MqttServiceError> {
+        let now = Utc::now();
+        let affected = match self.action {
+            ExpiryAction::None => 0,
+            ExpiryAction::Disable => self.repo.disable_expired(now).await?,
+            ExpiryAction::Purge => self.repo.purge_expired(now).await?,
+        };
+        debug!(
+            "[Service | ExpireMQTT] Sweep finished (action={}, affected={})",
+            self.action.as_str(),
+            affected
+        );
+        Ok(affected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_expiry_actions() {
+        assert_eq!(ExpiryAction::parse(""), Ok(ExpiryAction::None));
+        assert_eq!(ExpiryAction::parse("none"), Ok(ExpiryAction::None));
+        assert_eq!(ExpiryAction::parse("Disable"), Ok(ExpiryAction::Disable));
+        assert_eq!(ExpiryAction::parse("purge"), Ok(ExpiryAction::Purge));
+        assert_eq!(ExpiryAction::parse("delete"), Ok(ExpiryAction::Purge));
+    }
+
+    #[test]
+    fn rejects_unknown_expiry_actions() {
+        assert!(ExpiryAction::parse("disabel").is_err());
+    }
+}
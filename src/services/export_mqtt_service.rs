@@ -0,0 +1,122 @@
+use chrono::Utc;
+use log::{debug, warn};
+use std::sync::Arc;
+
+use crate::dtos::mqtt_dto::{EmqxAclUserDTO, EmqxUserDTO, MqttAclRuleDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model;
+use crate::repositories::export_mqtt_repository::ExportMqttRepository;
+use crate::repositories::get_mqtt_acl_rules_repository::GetMqttAclRulesRepository;
+use crate::services::service_error::MqttServiceError;
+use crate::utils::encryption::decrypt_password;
+use crate::utils::password_hash::{generate_salt, is_password_hash, sha256_salted};
+
+pub struct ExportMqttService {
+    repo_export: Arc<ExportMqttRepository>,
+    repo_rules: Arc<GetMqttAclRulesRepository>,
+}
+
+impl ExportMqttService {
+    pub fn new(
+        repo_export: Arc<ExportMqttRepository>,
+        repo_rules: Arc<GetMqttAclRulesRepository>,
+    ) -> Self {
+        Self {
+            repo_export,
+            repo_rules,
+        }
+    }
+
+    /// Users in EMQX's built-in database import format, hashed with a fresh salt per user.
+    pub async fn export_users(
+        &self,
+        tenant: &TenantContext,
+        include_inactive: bool,
+    ) -> Result<Vec<EmqxUserDTO>, MqttServiceError> {
+        let users = self.load_users(tenant, include_inactive).await?;
+        let mut exported = Vec::with_capacity(users.len());
+        for user in users {
+            if is_password_hash(&user.password) {
+                warn!(
+                    "[Service | ExportMQTT] Skipping {}: its password is a one-way hash",
+                    user.username
+                );
+                continue;
+            }
+            let password =
+                decrypt_password(&user.password).map_err(MqttServiceError::InternalError)?;
+            let salt = generate_salt();
+            exported.push(EmqxUserDTO {
+                password_hash: sha256_salted(&password, &salt),
+                salt,
+                user_id: user.username,
+                is_superuser: user.is_superuser,
+            });
+        }
+
+        debug!("[Service | ExportMQTT] Exported {} users", exported.len());
+        Ok(exported)
+    }
+
+    /// ACL rules in EMQX's built-in database authorization format. Superusers have no rules.
+    pub async fn export_acl(
+        &self,
+        tenant: &TenantContext,
+        include_inactive: bool,
+    ) -> Result<Vec<EmqxAclUserDTO>, MqttServiceError> {
+        let mut stored_rules = self.repo_rules.get_all_rules().await?;
+        let exported: Vec<EmqxAclUserDTO> = self
+            .load_users(tenant, include_inactive)
+            .await?
+            .into_iter()
+            .filter_map(|user| {
+                let stored = stored_rules.remove(&user.id).unwrap_or_default();
+                let rules = MqttAclRuleDTO::for_user(&user, stored, &tenant.topic_prefix);
+                (!rules.is_empty()).then_some(EmqxAclUserDTO {
+                    username: user.username,
+                    rules,
+                })
+            })
+            .collect();
+
+        debug!(
+            "[Service | ExportMQTT] Exported ACL rules of {} users",
+            exported.len()
+        );
+        Ok(exported)
+    }
+
+    async fn load_users(
+        &self,
+        tenant: &TenantContext,
+        include_inactive: bool,
+    ) -> Result<Vec<Model>, MqttServiceError> {
+        let now = Utc::now();
+        let users = self.repo_export.get_all_mqtt(tenant.id).await?;
+        Ok(users
+            .into_iter()
+            .filter(|user| include_inactive || user.is_active_at(now))
+            .collect())
+    }
+}
+
+/// Renders users as EMQX's CSV import file (`user_id,password_hash,salt,is_superuser`).
+pub fn emqx_users_csv(users: &[EmqxUserDTO]) -> Result<Vec<u8>, MqttServiceError> {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["user_id", "password_hash", "salt", "is_superuser"])
+        .map_err(|e| MqttServiceError::InternalError(e.to_string()))?;
+    for user in users {
+        writer
+            .write_record([
+                user.user_id.as_str(),
+                user.password_hash.as_str(),
+                user.salt.as_str(),
+                if user.is_superuser { "true" } else { "false" },
+            ])
+            .map_err(|e| MqttServiceError::InternalError(e.to_string()))?;
+    }
+    writer
+        .into_inner()
+        .map_err(|e| MqttServiceError::InternalError(e.to_string()))
+}
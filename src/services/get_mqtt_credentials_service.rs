@@ -1,22 +1,62 @@
 use log::debug;
 use std::sync::Arc;
 
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
 use crate::dtos::mqtt_dto::MqttCredentialsDTO;
+use crate::dtos::tenant_dto::TenantContext;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::services::audit_service::{AuditChange, AuditService};
 use crate::services::service_error::MqttServiceError;
+use crate::utils::credential_policy::CredentialPolicy;
 use crate::utils::encryption::decrypt_password;
+use crate::utils::password_hash::is_password_hash;
 
 pub struct GetMqttCredentialsService {
     repo: Arc<GetMqttByUsernameRepository>,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
 }
 
 impl GetMqttCredentialsService {
-    pub fn new(repo: Arc<GetMqttByUsernameRepository>) -> Self {
-        Self { repo }
+    pub fn new(
+        repo: Arc<GetMqttByUsernameRepository>,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo,
+            policy,
+            audit,
+        }
     }
 
-    pub async fn get_credentials(&self, username: &str) -> Result<MqttCredentialsDTO, MqttServiceError> {
-        let mqtt = match self.repo.get_mqtt_by_username(username).await {
+    /// Decrypts the password of a user. Every reveal is audited, whether or not it succeeds.
+    pub async fn get_credentials(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        username: &str,
+    ) -> Result<MqttCredentialsDTO, MqttServiceError> {
+        let username = self.policy.normalize_username(username);
+        let result = self.reveal(tenant, &username).await;
+        let change = result.as_ref().map(|_| AuditChange::none());
+        self.audit
+            .record(
+                actor,
+                AuditAction::UserRevealCredentials,
+                Some(&username),
+                change,
+            )
+            .await;
+        result
+    }
+
+    async fn reveal(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+    ) -> Result<MqttCredentialsDTO, MqttServiceError> {
+        let mqtt = match self.repo.get_mqtt_by_username(tenant.id, username).await {
             Ok(u) => u,
             Err(_) => {
                 debug!("[Service | GetMqttCredentials] User MQTT not found: {}", username);
@@ -24,9 +64,15 @@ impl GetMqttCredentialsService {
             }
         };
 
+        if is_password_hash(&mqtt.password) {
+            debug!("[Service | GetMqttCredentials] Password of {} is a one-way hash", username);
+            return Err(MqttServiceError::Conflict(
+                "Password of user MQTT is stored as a one-way hash and cannot be retrieved".into(),
+            ));
+        }
 
         let decrypted_password = decrypt_password(&mqtt.password)
-            .map_err(|e| MqttServiceError::InternalError(e))?;
+            .map_err(MqttServiceError::InternalError)?;
 
         debug!("[Service | GetMqttCredentials] Credentials retrieved and decrypted for: {}", username);
 
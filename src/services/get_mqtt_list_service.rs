@@ -1,29 +1,229 @@
-use crate::dtos::mqtt_dto::MqttDTO;
-use crate::repositories::get_mqtt_list_repository::GetMqttListRepository;
-use crate::services::service_error::MqttServiceError;
+use crate::dtos::mqtt_dto::{
+    GetMqttListDTO, GetMqttListQueryDTO, MqttListSortField, MqttUserDTO, PaginationDTO, SortOrder,
+};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Column;
+use crate::repositories::get_mqtt_list_repository::{GetMqttListRepository, MqttListFilter};
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::attributes::{is_attribute_key, normalize_tags};
+use chrono::{TimeDelta, Utc};
 use log::debug;
+use sea_orm::Order;
 use std::sync::Arc;
 
+const DEFAULT_PER_PAGE: u64 = 50;
+const MAX_PER_PAGE: u64 = 500;
+/// Longest `expiring_within_secs` / `inactive_for_secs` window, 100 years.
+const MAX_WINDOW_SECS: i64 = 100 * 365 * 24 * 3600;
+
 pub struct GetMqttListService {
     repo: Arc<GetMqttListRepository>,
+    repo_tags: Arc<GetMqttTagsRepository>,
 }
 
 impl GetMqttListService {
-    pub fn new(repo: Arc<GetMqttListRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: Arc<GetMqttListRepository>, repo_tags: Arc<GetMqttTagsRepository>) -> Self {
+        Self { repo, repo_tags }
     }
 
-    pub async fn get_mqtt_list(&self) -> Result<Vec<MqttDTO>, MqttServiceError> {
-        let mqtts = self.repo.get_mqtt_list().await?;
-        let dto_mqtts: Vec<MqttDTO> = mqtts
+    pub async fn get_mqtt_list(
+        &self,
+        tenant: &TenantContext,
+        query: GetMqttListQueryDTO,
+    ) -> Result<GetMqttListDTO, MqttServiceError> {
+        self.get_mqtt_list_validation(&query)?;
+        let tags = split_list(query.tags.as_deref());
+        let attributes = split_list(query.attributes.as_deref())
+            .into_iter()
+            .filter_map(|pair| {
+                let (key, value) = pair.split_once(':')?;
+                Some((key.trim().to_string(), value.trim().to_string()))
+            })
+            .collect();
+
+        let page = query.page.unwrap_or(1);
+        let per_page = query.per_page.unwrap_or(DEFAULT_PER_PAGE);
+        let filter = MqttListFilter {
+            tenant_id: tenant.id,
+            username_prefix: query.username_prefix.filter(|p| !p.is_empty()),
+            is_superuser: query.is_superuser,
+            is_enabled: query.is_enabled,
+            expired: query.expired,
+            expiring_within: query.expiring_within_secs.and_then(TimeDelta::try_seconds),
+            inactive_for: query.inactive_for_secs.and_then(TimeDelta::try_seconds),
+            created_from: query.created_from,
+            created_until: query.created_until,
+            deleted: query.deleted.unwrap_or(false),
+            tags: normalize_tags(&tags),
+            attributes,
+            sort_column: match query.sort_by.unwrap_or_default() {
+                MqttListSortField::Username => Column::Username,
+                MqttListSortField::Id => Column::Id,
+                MqttListSortField::ValidUntil => Column::ValidUntil,
+                MqttListSortField::CreatedAt => Column::CreatedAt,
+                MqttListSortField::LastLoginAt => Column::LastLoginAt,
+            },
+            sort_order: match query.order.unwrap_or_default() {
+                SortOrder::Asc => Order::Asc,
+                SortOrder::Desc => Order::Desc,
+            },
+            page,
+            per_page,
+        };
+
+        let (mqtts, total_items, total_pages) =
+            self.repo.get_mqtt_list(filter, Utc::now()).await?;
+        let ids: Vec<i32> = mqtts.iter().map(|m| m.id).collect();
+        let mut tags = self.repo_tags.get_tags_for_users(&ids).await?;
+        let users: Vec<MqttUserDTO> = mqtts
             .into_iter()
-            .map(|mqtt| MqttDTO {
-                username: mqtt.username,
-                password: mqtt.password,
-                is_superuser: mqtt.is_superuser,
+            .map(|m| {
+                let user_tags = tags.remove(&m.id).unwrap_or_default();
+                MqttUserDTO::new(m, user_tags)
             })
             .collect();
         debug!("[Service | GetMQTTList] User MQTT list retrieved successfully.");
-        Ok(dto_mqtts)
+        Ok(GetMqttListDTO {
+            users,
+            pagination: PaginationDTO {
+                page,
+                per_page,
+                total_items,
+                total_pages,
+            },
+        })
+    }
+
+    fn get_mqtt_list_validation(
+        &self,
+        query: &GetMqttListQueryDTO,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if let Some(message) = query
+            .page
+            .and_then(|page| page_error(page, query.per_page.unwrap_or(DEFAULT_PER_PAGE)))
+        {
+            errors.push(ValidationError {
+                field: "page".to_string(),
+                message,
+            });
+        }
+
+        if query
+            .per_page
+            .is_some_and(|per_page| per_page == 0 || per_page > MAX_PER_PAGE)
+        {
+            errors.push(ValidationError {
+                field: "per_page".to_string(),
+                message: format!("per_page must be between 1 and {}", MAX_PER_PAGE),
+            });
+        }
+
+        for (field, secs) in [
+            ("expiring_within_secs", query.expiring_within_secs),
+            ("inactive_for_secs", query.inactive_for_secs),
+        ] {
+            if let Some(message) = secs.and_then(|secs| window_error(field, secs)) {
+                errors.push(ValidationError {
+                    field: field.to_string(),
+                    message,
+                });
+            }
+        }
+
+        if let (Some(from), Some(until)) = (query.created_from, query.created_until)
+            && until <= from
+        {
+            errors.push(ValidationError {
+                field: "created_until".to_string(),
+                message: "created_until must be later than created_from".to_string(),
+            });
+        }
+
+        for pair in split_list(query.attributes.as_deref()) {
+            let valid = pair
+                .split_once(':')
+                .is_some_and(|(key, _)| is_attribute_key(key.trim()));
+            if !valid {
+                errors.push(ValidationError {
+                    field: "attributes".to_string(),
+                    message: format!(
+                        "`{}` must be `key:value` with a key of letters, digits, `_` or `-`",
+                        pair
+                    ),
+                });
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        Ok(true)
+    }
+}
+
+/// Non-empty entries of a comma-separated query value.
+fn split_list(raw: Option<&str>) -> Vec<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Why a page is not accepted, if it is not: pages start at 1, and the rows skipped to reach
+/// it must fit the database's signed 64-bit OFFSET.
+pub(crate) fn page_error(page: u64, per_page: u64) -> Option<String> {
+    if page == 0 {
+        return Some("page must be greater than zero".to_string());
+    }
+    (page - 1)
+        .checked_mul(per_page)
+        .and_then(|offset| i64::try_from(offset).ok())
+        .is_none()
+        .then(|| "page is out of range".to_string())
+}
+
+/// Why a time window filter is not accepted, if it is not.
+fn window_error(field: &str, secs: i64) -> Option<String> {
+    (!(1..=MAX_WINDOW_SECS).contains(&secs))
+        .then(|| format!("{} must be between 1 and {}", field, MAX_WINDOW_SECS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_windows_in_range() {
+        assert_eq!(window_error("inactive_for_secs", 1), None);
+        assert_eq!(window_error("inactive_for_secs", MAX_WINDOW_SECS), None);
+    }
+
+    #[test]
+    fn rejects_windows_out_of_range() {
+        assert!(window_error("expiring_within_secs", 0).is_some());
+        assert!(window_error("expiring_within_secs", -5).is_some());
+        assert!(window_error("expiring_within_secs", i64::MAX).is_some());
+    }
+
+    #[test]
+    fn accepts_pages_with_a_representable_offset() {
+        assert_eq!(page_error(1, MAX_PER_PAGE), None);
+        assert_eq!(page_error(1, 0), None);
+        assert_eq!(
+            page_error(i64::MAX as u64 / MAX_PER_PAGE, MAX_PER_PAGE),
+            None
+        );
+    }
+
+    #[test]
+    fn rejects_pages_out_of_range() {
+        assert!(page_error(0, DEFAULT_PER_PAGE).is_some());
+        assert!(page_error(u64::MAX, MAX_PER_PAGE).is_some());
+        assert!(page_error(i64::MAX as u64 / MAX_PER_PAGE + 2, MAX_PER_PAGE).is_some());
     }
 }
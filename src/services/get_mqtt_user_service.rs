@@ -0,0 +1,86 @@
+use chrono::Utc;
+use log::debug;
+use std::sync::Arc;
+
+use crate::dtos::mqtt_dto::{MqttAclRuleDTO, MqttUserDetailDTO, MqttUserRoleDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::get_mqtt_acl_rules_repository::GetMqttAclRulesRepository;
+use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::get_mqtt_client_ids_repository::GetMqttClientIdsRepository;
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::services::service_error::MqttServiceError;
+use crate::utils::credential_policy::CredentialPolicy;
+
+pub struct GetMqttUserService {
+    repo: Arc<GetMqttByUsernameRepository>,
+    repo_rules: Arc<GetMqttAclRulesRepository>,
+    repo_tags: Arc<GetMqttTagsRepository>,
+    repo_client_ids: Arc<GetMqttClientIdsRepository>,
+    policy: Arc<CredentialPolicy>,
+}
+
+impl GetMqttUserService {
+    pub fn new(
+        repo: Arc<GetMqttByUsernameRepository>,
+        repo_rules: Arc<GetMqttAclRulesRepository>,
+        repo_tags: Arc<GetMqttTagsRepository>,
+        repo_client_ids: Arc<GetMqttClientIdsRepository>,
+        policy: Arc<CredentialPolicy>,
+    ) -> Self {
+        Self {
+            repo,
+            repo_rules,
+            repo_tags,
+            repo_client_ids,
+            policy,
+        }
+    }
+
+    pub async fn get_mqtt_user(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+    ) -> Result<MqttUserDetailDTO, MqttServiceError> {
+        let username = &self.policy.normalize_username(username);
+        let mqtt = match self.repo.get_mqtt_by_username(tenant.id, username).await {
+            Ok(u) => u,
+            Err(_) => {
+                debug!("[Service | GetMqttUser] User MQTT not found: {}", username);
+                return Err(MqttServiceError::MqttNotFound("User MQTT not found".into()));
+            }
+        };
+
+        let rules = self.repo_rules.get_rules_for_user(mqtt.id).await?;
+        let acl_rules = MqttAclRuleDTO::for_user(&mqtt, rules, &tenant.topic_prefix);
+        let roles = self
+            .repo_rules
+            .get_roles_for_user(mqtt.id)
+            .await?
+            .into_iter()
+            .map(|(name, priority)| MqttUserRoleDTO { name, priority })
+            .collect();
+        let tags = self.repo_tags.get_tags(mqtt.id).await?;
+        let client_ids = self.repo_client_ids.get_client_ids(mqtt.id).await?;
+
+        debug!("[Service | GetMqttUser] User MQTT detail retrieved for: {}", username);
+        Ok(MqttUserDetailDTO {
+            previous_password_expires_at: mqtt
+                .previous_password_at(Utc::now())
+                .and(mqtt.previous_password_expires_at),
+            username: mqtt.username,
+            is_superuser: mqtt.is_superuser,
+            roles,
+            is_enabled: mqtt.is_enabled,
+            valid_from: mqtt.valid_from,
+            valid_until: mqtt.valid_until,
+            metadata: mqtt.metadata,
+            tags,
+            client_ids,
+            created_at: mqtt.created_at,
+            updated_at: mqtt.updated_at,
+            last_login_at: mqtt.last_login_at,
+            last_login_ip: mqtt.last_login_ip,
+            acl_rules,
+        })
+    }
+}
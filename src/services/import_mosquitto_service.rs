@@ -0,0 +1,406 @@
+use log::debug;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{DuplicateHandling, ImportMosquittoResultDTO, MosquittoImportFormat};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::import_mosquitto_repository::{
+    ImportMosquittoRepository, NewAclRule, NewMosquittoClient, NewMqttRole,
+};
+use crate::repositories::import_mqtt_repository::{DuplicatePolicy, NewMqttUser};
+use crate::repositories::repository_error::MqttRepositoryError;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::quota_service::QuotaService;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::attributes::{client_id_errors, normalize_client_ids};
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::encryption::encrypt_password;
+use crate::utils::password_generator::PasswordGeneratorConfig;
+use crate::utils::password_hash::{
+    is_password_hash, mosquitto_pbkdf2_hash, validate_password_hash,
+};
+
+/// Iteration count Mosquitto uses when a dynsec client omits it.
+const DEFAULT_DYNSEC_ITERATIONS: u32 = 101;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DynsecConfig {
+    #[serde(default)]
+    clients: Vec<DynsecClient>,
+    #[serde(default)]
+    groups: Vec<DynsecGroup>,
+    #[serde(default)]
+    roles: Vec<DynsecRole>,
+}
+
+#[derive(Deserialize)]
+struct DynsecClient {
+    username: String,
+    password: Option<String>,
+    salt: Option<String>,
+    iterations: Option<u32>,
+    clientid: Option<String>,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    roles: Vec<DynsecRoleRef>,
+}
+
+#[derive(Deserialize)]
+struct DynsecRoleRef {
+    rolename: String,
+    #[serde(default = "default_role_priority")]
+    priority: i32,
+}
+
+#[derive(Deserialize)]
+struct DynsecGroup {
+    #[serde(default)]
+    roles: Vec<DynsecRoleRef>,
+    #[serde(default)]
+    clients: Vec<DynsecGroupMember>,
+}
+
+#[derive(Deserialize)]
+struct DynsecGroupMember {
+    username: String,
+}
+
+#[derive(Deserialize)]
+struct DynsecRole {
+    rolename: String,
+    #[serde(default)]
+    acls: Vec<DynsecAcl>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DynsecAcl {
+    acltype: String,
+    topic: String,
+    #[serde(default)]
+    priority: i32,
+    #[serde(default)]
+    allow: bool,
+}
+
+fn default_role_priority() -> i32 {
+    -1
+}
+
+pub struct ImportMosquittoService {
+    repo_import: Arc<ImportMosquittoRepository>,
+    quota: Arc<QuotaService>,
+    password_generator: PasswordGeneratorConfig,
+    max_rows: usize,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+impl ImportMosquittoService {
+    pub fn new(
+        repo_import: Arc<ImportMosquittoRepository>,
+        quota: Arc<QuotaService>,
+        password_generator: PasswordGeneratorConfig,
+        max_rows: usize,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_import,
+            quota,
+            password_generator,
+            max_rows,
+            policy,
+            audit,
+        }
+    }
+
+    /// Imports a Mosquitto password file or dynsec configuration in one transaction.
+    ///
+    /// Hashed passwords are stored as-is and verified natively at login; plaintext entries
+    /// are encrypted like any other password.
+    pub async fn import_mosquitto(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        body: &[u8],
+        format: MosquittoImportFormat,
+        on_duplicate: DuplicateHandling,
+    ) -> Result<ImportMosquittoResultDTO, MqttServiceError> {
+        let result = self.import(tenant, body, format, on_duplicate).await;
+        let change = result.as_ref().map(|report| {
+            AuditChange::created(&serde_json::json!({
+                "created": report.created,
+                "updated": report.updated,
+                "skipped": report.skipped,
+                "roles": report.roles,
+                "acl_rules": report.acl_rules,
+            }))
+        });
+        self.audit
+            .record(actor, AuditAction::UserImportMosquitto, None, change)
+            .await;
+        result
+    }
+
+    async fn import(
+        &self,
+        tenant: &TenantContext,
+        body: &[u8],
+        format: MosquittoImportFormat,
+        on_duplicate: DuplicateHandling,
+    ) -> Result<ImportMosquittoResultDTO, MqttServiceError> {
+        let mut warnings = Vec::new();
+        let (roles, mut clients) = match format {
+            MosquittoImportFormat::Passwd => (Vec::new(), parse_passwd(tenant.id, body)?),
+            MosquittoImportFormat::Dynsec => self.parse_dynsec(tenant.id, body, &mut warnings)?,
+        };
+
+        if clients.len() > self.max_rows {
+            return Err(body_error(format!(
+                "import cannot exceed {} clients",
+                self.max_rows
+            )));
+        }
+
+        let mut errors = Vec::new();
+        for client in &mut clients {
+            client.user.username = self.policy.normalize_username(&client.user.username);
+            for message in self.policy.username_errors(&client.user.username) {
+                errors.push(ValidationError {
+                    field: "username".to_string(),
+                    message: format!("{}: {}", client.user.username, message),
+                });
+            }
+        }
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        let mut seen = HashSet::new();
+        if let Some(duplicate) = clients
+            .iter()
+            .find(|c| !seen.insert(c.user.username.as_str()))
+        {
+            return Err(body_error(format!(
+                "username {} appears more than once",
+                duplicate.user.username
+            )));
+        }
+
+        let policy = match on_duplicate {
+            DuplicateHandling::Skip => DuplicatePolicy::Skip,
+            DuplicateHandling::Fail => DuplicatePolicy::Fail,
+            DuplicateHandling::Overwrite => DuplicatePolicy::Overwrite,
+        };
+        let quota_rows: Vec<(&str, bool)> = clients
+            .iter()
+            .map(|c| (c.user.username.as_str(), c.user.is_superuser))
+            .collect();
+        self.quota
+            .check_users(tenant, &quota_rows, policy == DuplicatePolicy::Overwrite)
+            .await?;
+        let role_names: Vec<String> = roles.iter().map(|r| r.name.clone()).collect();
+        let rule_count: usize = roles.iter().map(|r| r.rules.len()).sum();
+        self.quota
+            .check_acl_rules(tenant, rule_count as u64, &role_names)
+            .await?;
+
+        let summary = self
+            .repo_import
+            .import_mosquitto(tenant.id, roles, clients, policy)
+            .await
+            .map_err(|e| match e {
+                MqttRepositoryError::AlreadyExists => MqttServiceError::Conflict(
+                    "A client already exists; import rolled back".to_string(),
+                ),
+                e => e.into(),
+            })?;
+
+        debug!(
+            "[Service | ImportMosquitto] Imported {} clients, {} roles, {} ACL rules ({} warnings)",
+            summary.created + summary.updated,
+            summary.roles,
+            summary.acl_rules,
+            warnings.len()
+        );
+        Ok(ImportMosquittoResultDTO {
+            created: summary.created,
+            updated: summary.updated,
+            skipped: summary.skipped,
+            roles: summary.roles,
+            acl_rules: summary.acl_rules,
+            warnings,
+        })
+    }
+
+    fn parse_dynsec(
+        &self,
+        tenant_id: i32,
+        body: &[u8],
+        warnings: &mut Vec<String>,
+    ) -> Result<(Vec<NewMqttRole>, Vec<NewMosquittoClient>), MqttServiceError> {
+        let config: DynsecConfig = serde_json::from_slice(body)
+            .map_err(|e| body_error(format!("invalid dynamic security JSON: {}", e)))?;
+
+        let mut roles = Vec::with_capacity(config.roles.len());
+        for role in config.roles {
+            let mut rules = Vec::with_capacity(role.acls.len());
+            for acl in role.acls {
+                let action = match acl.acltype.as_str() {
+                    "publishClientSend" => "publish",
+                    "subscribeLiteral" | "subscribePattern" => "subscribe",
+                    other => {
+                        warnings.push(format!(
+                            "role {}: {} ACL on {} is not supported and was ignored",
+                            role.rolename, other, acl.topic
+                        ));
+                        continue;
+                    }
+                };
+                rules.push(NewAclRule {
+                    permission: if acl.allow { "allow" } else { "deny" }.to_string(),
+                    action: action.to_string(),
+                    topic: acl.topic,
+                    priority: acl.priority,
+                });
+            }
+            roles.push(NewMqttRole {
+                name: role.rolename,
+                rules,
+            });
+        }
+        let role_names: HashSet<&str> = roles.iter().map(|r| r.name.as_str()).collect();
+
+        // Groups are flattened: every member inherits the group's roles.
+        let mut group_roles: HashMap<&str, Vec<&DynsecRoleRef>> = HashMap::new();
+        for group in &config.groups {
+            for member in &group.clients {
+                group_roles
+                    .entry(member.username.as_str())
+                    .or_default()
+                    .extend(group.roles.iter());
+            }
+        }
+
+        let mut clients = Vec::with_capacity(config.clients.len());
+        for client in &config.clients {
+            let password = match (&client.password, &client.salt) {
+                (Some(hash), Some(salt)) => {
+                    let stored = mosquitto_pbkdf2_hash(
+                        client.iterations.unwrap_or(DEFAULT_DYNSEC_ITERATIONS),
+                        salt,
+                        hash,
+                    );
+                    validate_password_hash(&stored)
+                        .map_err(|e| body_error(format!("client {}: {}", client.username, e)))?;
+                    stored
+                }
+                _ => {
+                    warnings.push(format!(
+                        "client {} has no password; a random one was set and must be rotated",
+                        client.username
+                    ));
+                    encrypt_password(&self.password_generator.generate(None, None))
+                        .map_err(MqttServiceError::InternalError)?
+                }
+            };
+            let client_ids: Vec<String> = client.clientid.iter().cloned().collect();
+            if let Some(message) = client_id_errors(&client_ids).into_iter().next() {
+                return Err(body_error(format!("client {}: {}", client.username, message)));
+            }
+
+            let mut assigned: Vec<(String, i32)> = Vec::new();
+            let inherited = group_roles.get(client.username.as_str());
+            for role in client
+                .roles
+                .iter()
+                .chain(inherited.into_iter().flatten().copied())
+            {
+                if !role_names.contains(role.rolename.as_str()) {
+                    warnings.push(format!(
+                        "client {}: role {} is not defined and was ignored",
+                        client.username, role.rolename
+                    ));
+                    continue;
+                }
+                match assigned.iter_mut().find(|(name, _)| *name == role.rolename) {
+                    Some((_, priority)) => *priority = (*priority).max(role.priority),
+                    None => assigned.push((role.rolename.clone(), role.priority)),
+                }
+            }
+
+            clients.push(NewMosquittoClient {
+                user: NewMqttUser {
+                    tenant_id,
+                    username: client.username.clone(),
+                    password,
+                    is_superuser: false,
+                    is_enabled: !client.disabled,
+                    metadata: None,
+                    valid_from: None,
+                    valid_until: None,
+                    provision_batch_id: None,
+                },
+                roles: assigned,
+                client_ids: normalize_client_ids(&client_ids),
+            });
+        }
+
+        Ok((roles, clients))
+    }
+}
+
+fn parse_passwd(tenant_id: i32, body: &[u8]) -> Result<Vec<NewMosquittoClient>, MqttServiceError> {
+    let mut clients = Vec::new();
+    for (index, line) in String::from_utf8_lossy(body).lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let (username, secret) = line
+            .split_once(':')
+            .filter(|(username, secret)| !username.is_empty() && !secret.is_empty())
+            .ok_or_else(|| body_error(format!("line {}: expected username:password", index + 1)))?;
+        let password = if is_password_hash(secret) {
+            validate_password_hash(secret)
+                .map_err(|e| body_error(format!("line {}: {}", index + 1, e)))?;
+            secret.to_string()
+        } else {
+            encrypt_password(secret).map_err(MqttServiceError::InternalError)?
+        };
+
+        clients.push(NewMosquittoClient {
+            user: NewMqttUser {
+                tenant_id,
+                username: username.to_string(),
+                password,
+                is_superuser: false,
+                is_enabled: true,
+                metadata: None,
+                valid_from: None,
+                valid_until: None,
+                provision_batch_id: None,
+            },
+            roles: Vec::new(),
+            client_ids: Vec::new(),
+        });
+    }
+
+    if clients.is_empty() {
+        return Err(body_error("password file contains no entries".to_string()));
+    }
+    Ok(clients)
+}
+
+fn body_error(message: String) -> MqttServiceError {
+    MqttServiceError::BadRequest(vec![ValidationError {
+        field: "body".to_string(),
+        message,
+    }])
+}
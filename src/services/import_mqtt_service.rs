@@ -0,0 +1,429 @@
+use log::debug;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{
+    DuplicateHandling, ImportFormat, ImportMode, ImportMqttResultDTO, ImportMqttRowDTO,
+    ImportRowResultDTO, ImportRowStatus,
+};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::import_mqtt_repository::{
+    DuplicatePolicy, ImportMqttRepository, ImportOutcome, ImportedMqttUser, NewMqttUser,
+};
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::quota_service::QuotaService;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::attributes::{metadata_error, normalize_tags, tag_errors};
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::encryption::encrypt_password;
+use crate::utils::password_generator::{
+    PasswordAlphabet, PasswordGeneratorConfig, password_length_error,
+};
+
+/// CSV columns accepted by the importer; `metadata` holds a JSON object, `roles` and `tags`
+/// are separated by `;`.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CsvImportRow {
+    username: String,
+    password: Option<String>,
+    is_superuser: Option<bool>,
+    metadata: Option<String>,
+    valid_from: Option<DateTime<Utc>>,
+    valid_until: Option<DateTime<Utc>>,
+    roles: Option<String>,
+    tags: Option<String>,
+}
+
+struct ParsedRow {
+    line: usize,
+    row: Result<ImportMqttRowDTO, String>,
+}
+
+pub struct ImportMqttService {
+    repo_import: Arc<ImportMqttRepository>,
+    quota: Arc<QuotaService>,
+    password_generator: PasswordGeneratorConfig,
+    max_rows: usize,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+/// Options of a single import request.
+pub struct ImportOptions {
+    pub format: ImportFormat,
+    pub mode: ImportMode,
+    pub on_duplicate: DuplicateHandling,
+    pub password_length: Option<usize>,
+    pub password_alphabet: Option<PasswordAlphabet>,
+}
+
+impl ImportMqttService {
+    pub fn new(
+        repo_import: Arc<ImportMqttRepository>,
+        quota: Arc<QuotaService>,
+        password_generator: PasswordGeneratorConfig,
+        max_rows: usize,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_import,
+            quota,
+            password_generator,
+            max_rows,
+            policy,
+            audit,
+        }
+    }
+
+    /// Imports the users and records one audit event with the counts of the report.
+    pub async fn import_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        body: &[u8],
+        options: ImportOptions,
+    ) -> Result<ImportMqttResultDTO, MqttServiceError> {
+        let result = self.import(tenant, body, options).await;
+        let change = result.as_ref().map(|report| {
+            AuditChange::created(&serde_json::json!({
+                "committed": report.committed,
+                "total": report.total,
+                "created": report.created,
+                "updated": report.updated,
+                "skipped": report.skipped,
+                "failed": report.failed,
+            }))
+        });
+        self.audit
+            .record(actor, AuditAction::UserImport, None, change)
+            .await;
+        result
+    }
+
+    async fn import(
+        &self,
+        tenant: &TenantContext,
+        body: &[u8],
+        options: ImportOptions,
+    ) -> Result<ImportMqttResultDTO, MqttServiceError> {
+        let parsed = match options.format {
+            ImportFormat::Ndjson => parse_ndjson(body),
+            ImportFormat::Csv => parse_csv(body)?,
+        };
+        self.import_options_validation(parsed.len(), &options)?;
+        let role_ids = self.repo_import.get_role_ids(tenant.id).await?;
+
+        let mut seen = HashSet::new();
+        let mut results = Vec::with_capacity(parsed.len());
+        let mut pending = Vec::new();
+        let mut generated = Vec::new();
+        for ParsedRow { line, row } in parsed {
+            let username = row.as_ref().map(|r| r.username.clone()).unwrap_or_default();
+            let row = row.and_then(|mut row| {
+                row.username = self.policy.normalize_username(&row.username);
+                self.import_row_validation(&row, &role_ids)?;
+                if !seen.insert(row.username.clone()) {
+                    return Err("username appears more than once in this import".to_string());
+                }
+                Ok(row)
+            });
+
+            match row {
+                Ok(row) => {
+                    let (password, was_generated) = match row.password {
+                        Some(password) => (password, false),
+                        None => (
+                            self.password_generator
+                                .generate(options.password_length, options.password_alphabet),
+                            true,
+                        ),
+                    };
+                    let encrypted =
+                        encrypt_password(&password).map_err(MqttServiceError::InternalError)?;
+                    results.push(ImportRowResultDTO {
+                        line,
+                        username: row.username.clone(),
+                        status: ImportRowStatus::RolledBack,
+                        error: None,
+                        password: None,
+                    });
+                    generated.push(was_generated.then_some(password));
+                    let mut row_role_ids: Vec<i32> = row
+                        .roles
+                        .iter()
+                        .filter_map(|name| role_ids.get(name.trim()).copied())
+                        .collect();
+                    row_role_ids.sort_unstable();
+                    row_role_ids.dedup();
+                    pending.push((
+                        results.len() - 1,
+                        ImportedMqttUser {
+                            user: NewMqttUser {
+                                tenant_id: tenant.id,
+                                username: row.username,
+                                password: encrypted,
+                                is_superuser: row.is_superuser,
+                                is_enabled: true,
+                                metadata: row.metadata,
+                                valid_from: row.valid_from,
+                                valid_until: row.valid_until,
+                                provision_batch_id: None,
+                            },
+                            role_ids: row_role_ids,
+                            tags: normalize_tags(&row.tags),
+                        },
+                    ));
+                }
+                Err(message) => results.push(ImportRowResultDTO {
+                    line,
+                    username,
+                    status: ImportRowStatus::Failed,
+                    error: Some(message),
+                    password: None,
+                }),
+            }
+        }
+
+        let atomic = options.mode == ImportMode::Atomic;
+        let has_invalid_rows = results.iter().any(|r| r.status == ImportRowStatus::Failed);
+        if atomic && has_invalid_rows {
+            debug!("[Service | ImportMQTT] Atomic import rejected: invalid rows present");
+            return Ok(build_report(false, results));
+        }
+
+        let (indices, users): (Vec<usize>, Vec<ImportedMqttUser>) = pending.into_iter().unzip();
+        let policy = match options.on_duplicate {
+            DuplicateHandling::Skip => DuplicatePolicy::Skip,
+            DuplicateHandling::Fail => DuplicatePolicy::Fail,
+            DuplicateHandling::Overwrite => DuplicatePolicy::Overwrite,
+        };
+        // The quota covers the whole import, so a best-effort import is rejected up front too.
+        let quota_rows: Vec<(&str, bool)> = users
+            .iter()
+            .map(|u| (u.user.username.as_str(), u.user.is_superuser))
+            .collect();
+        self.quota
+            .check_users(tenant, &quota_rows, policy == DuplicatePolicy::Overwrite)
+            .await?;
+        let outcomes = self.repo_import.import_users(users, policy, atomic).await?;
+
+        let committed = !atomic || outcomes.iter().all(Result::is_ok);
+        for ((index, outcome), password) in indices.into_iter().zip(outcomes).zip(generated) {
+            let result = &mut results[index];
+            match outcome {
+                Err(e) => {
+                    result.status = ImportRowStatus::Failed;
+                    result.error = Some(e.to_string());
+                }
+                Ok(_) if !committed => {}
+                Ok(outcome) => {
+                    result.status = match outcome {
+                        ImportOutcome::Created => ImportRowStatus::Created,
+                        ImportOutcome::Updated => ImportRowStatus::Updated,
+                        ImportOutcome::Skipped => ImportRowStatus::Skipped,
+                    };
+                    if outcome != ImportOutcome::Skipped {
+                        result.password = password;
+                    }
+                }
+            }
+        }
+
+        let report = build_report(committed, results);
+        debug!(
+            "[Service | ImportMQTT] Import finished (committed={}, created={}, updated={}, skipped={}, failed={})",
+            report.committed, report.created, report.updated, report.skipped, report.failed
+        );
+        Ok(report)
+    }
+
+    fn import_options_validation(
+        &self,
+        rows: usize,
+        options: &ImportOptions,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if rows == 0 {
+            errors.push(ValidationError {
+                field: "body".to_string(),
+                message: "import contains no rows".to_string(),
+            });
+        }
+
+        if rows > self.max_rows {
+            errors.push(ValidationError {
+                field: "body".to_string(),
+                message: format!("import cannot exceed {} rows", self.max_rows),
+            });
+        }
+
+        if let Some(message) = options.password_length.and_then(password_length_error) {
+            errors.push(ValidationError {
+                field: "password_length".to_string(),
+                message,
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        Ok(true)
+    }
+
+    fn import_row_validation(
+        &self,
+        row: &ImportMqttRowDTO,
+        role_ids: &HashMap<String, i32>,
+    ) -> Result<(), String> {
+        if row.username.trim().is_empty() {
+            return Err("username cannot be empty".to_string());
+        }
+
+        let mut errors = self.policy.username_errors(&row.username);
+        match &row.password {
+            Some(password) if password.trim().is_empty() => {
+                errors.push("password cannot be empty".to_string())
+            }
+            Some(password) => errors.extend(self.policy.password_errors(password)),
+            None => {}
+        }
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        if let Some(message) = row.metadata.as_ref().and_then(metadata_error) {
+            return Err(message);
+        }
+
+        if let Some(role) = row
+            .roles
+            .iter()
+            .find(|role| !role_ids.contains_key(role.trim()))
+        {
+            return Err(format!("role `{}` does not exist", role.trim()));
+        }
+
+        let errors = tag_errors(&row.tags);
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+
+        if let (Some(from), Some(until)) = (row.valid_from, row.valid_until)
+            && until <= from
+        {
+            return Err("valid_until must be later than valid_from".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_ndjson(body: &[u8]) -> Vec<ParsedRow> {
+    String::from_utf8_lossy(body)
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| ParsedRow {
+            line: index + 1,
+            row: serde_json::from_str(line).map_err(|e| e.to_string()),
+        })
+        .collect()
+}
+
+fn parse_csv(body: &[u8]) -> Result<Vec<ParsedRow>, MqttServiceError> {
+    let mut reader = csv::ReaderBuilder::new().trim(csv::Trim::All).from_reader(body);
+    let headers = reader.headers().map_err(|e| {
+        MqttServiceError::BadRequest(vec![ValidationError {
+            field: "body".to_string(),
+            message: format!("invalid CSV header: {}", e),
+        }])
+    })?;
+    if !headers.iter().any(|h| h == "username") {
+        return Err(MqttServiceError::BadRequest(vec![ValidationError {
+            field: "body".to_string(),
+            message: "CSV header must include a username column".to_string(),
+        }]));
+    }
+
+    let mut rows = Vec::new();
+    for (index, record) in reader.deserialize::<CsvImportRow>().enumerate() {
+        // Record numbers are shifted by one for the header line.
+        let line = index + 2;
+        let row = record.map_err(|e| e.to_string()).and_then(|r| {
+            let metadata = match r.metadata.filter(|m| !m.is_empty()) {
+                Some(raw) => Some(
+                    serde_json::from_str(&raw).map_err(|e| format!("invalid metadata: {}", e))?,
+                ),
+                None => None,
+            };
+            Ok(ImportMqttRowDTO {
+                username: r.username,
+                password: r.password.filter(|p| !p.is_empty()),
+                is_superuser: r.is_superuser.unwrap_or(false),
+                metadata,
+                valid_from: r.valid_from,
+                valid_until: r.valid_until,
+                roles: split_list(r.roles),
+                tags: split_list(r.tags),
+            })
+        });
+        rows.push(ParsedRow { line, row });
+    }
+    Ok(rows)
+}
+
+/// Entries of a `;`-separated CSV cell; empty entries are dropped.
+fn split_list(cell: Option<String>) -> Vec<String> {
+    cell.iter()
+        .flat_map(|cell| cell.split(';'))
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+fn build_report(committed: bool, rows: Vec<ImportRowResultDTO>) -> ImportMqttResultDTO {
+    let count = |status: ImportRowStatus| rows.iter().filter(|r| r.status == status).count();
+    ImportMqttResultDTO {
+        committed,
+        total: rows.len(),
+        created: count(ImportRowStatus::Created),
+        updated: count(ImportRowStatus::Updated),
+        skipped: count(ImportRowStatus::Skipped),
+        failed: count(ImportRowStatus::Failed),
+        rows,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_list_cells() {
+        assert_eq!(
+            split_list(Some(" sensors; ;gateways ".to_string())),
+            vec!["sensors".to_string(), "gateways".to_string()]
+        );
+        assert!(split_list(Some(String::new())).is_empty());
+        assert!(split_list(None).is_empty());
+    }
+
+    #[test]
+    fn parses_csv_roles_and_tags() {
+        let body = b"username,roles,tags\nsensor-001,sensors;gateways,factory\nsensor-002,,\n";
+        let rows = parse_csv(body).unwrap_or_else(|_| panic!("valid CSV"));
+        let first = rows[0].row.as_ref().unwrap();
+        assert_eq!(first.roles, vec!["sensors", "gateways"]);
+        assert_eq!(first.tags, vec!["factory"]);
+        let second = rows[1].row.as_ref().unwrap();
+        assert!(second.roles.is_empty() && second.tags.is_empty());
+    }
+}
@@ -0,0 +1,65 @@
+use ipnet::IpNet;
+use log::info;
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::middleware::route_group::RouteGroup;
+use crate::utils::client_ip::{contains, parse_networks, resolve_client_ip};
+
+/// Network allow-lists per route group, set with `IP_ALLOW_LIST_HOOK` and `IP_ALLOW_LIST_ADMIN`
+/// as comma-separated CIDR networks. A group without a list accepts every address.
+/// `X-Forwarded-For` is honoured only from the `TRUSTED_PROXIES` networks.
+pub struct IpAllowListService {
+    trusted_proxies: Vec<IpNet>,
+    allowed: HashMap<RouteGroup, Vec<IpNet>>,
+}
+
+impl IpAllowListService {
+    /// Panics on a malformed list, rather than starting with a wider one than intended.
+    pub fn from_env() -> Self {
+        let networks = |name: &str| {
+            let value = std::env::var(name).unwrap_or_default();
+            parse_networks(&value).unwrap_or_else(|e| panic!("❌ Invalid {}: {}", name, e))
+        };
+        let trusted_proxies = networks("TRUSTED_PROXIES");
+        let allowed: HashMap<RouteGroup, Vec<IpNet>> = RouteGroup::ALL
+            .into_iter()
+            .map(|group| {
+                (
+                    group,
+                    networks(&format!("IP_ALLOW_LIST_{}", group.env_name())),
+                )
+            })
+            .filter(|(_, networks)| !networks.is_empty())
+            .collect();
+
+        for (group, networks) in &allowed {
+            info!(
+                "🧱 {} routes restricted to {} networks",
+                group.as_str(),
+                networks.len()
+            );
+        }
+        if !trusted_proxies.is_empty() {
+            info!(
+                "🧱 X-Forwarded-For trusted from {} proxy networks",
+                trusted_proxies.len()
+            );
+        }
+        Self {
+            trusted_proxies,
+            allowed,
+        }
+    }
+
+    pub fn client_ip(&self, peer: IpAddr, forwarded_for: Option<&str>) -> IpAddr {
+        resolve_client_ip(peer, forwarded_for, &self.trusted_proxies)
+    }
+
+    pub fn is_allowed(&self, group: RouteGroup, client_ip: IpAddr) -> bool {
+        match self.allowed.get(&group) {
+            Some(networks) => contains(networks, client_ip),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use log::{debug, warn};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+use chrono::Utc;
+
+use crate::repositories::record_login_repository::{LoginActivity, RecordLoginRepository};
+use crate::services::service_error::MqttServiceError;
+
+/// Write-behind buffer for `last_login_at` / `last_login_ip`.
+///
+/// `/mqtt/check` only records into memory; `flush` is called periodically by the login
+/// activity job and once more on shutdown. Only the latest login per user is kept.
+pub struct LoginActivityService {
+    repo: Arc<RecordLoginRepository>,
+    pending: Mutex<HashMap<i32, LoginActivity>>,
+    max_pending: usize,
+}
+
+impl LoginActivityService {
+    pub fn new(repo: Arc<RecordLoginRepository>, max_pending: usize) -> Self {
+        Self {
+            repo,
+            pending: Mutex::new(HashMap::new()),
+            max_pending,
+        }
+    }
+
+    /// Buffers a successful login. When the buffer is full, logins of users not already
+    /// buffered are dropped until the next flush.
+    pub fn record(&self, user_id: i32, ip: Option<IpAddr>) {
+        let login = LoginActivity {
+            at: Utc::now(),
+            ip: ip.map(|ip| ip.to_string()),
+        };
+        let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+        if pending.len() >= self.max_pending && !pending.contains_key(&user_id) {
+            warn!(
+                "[Service | LoginActivity] Buffer full ({} users); dropping login of user MQTT id {}",
+                self.max_pending, user_id
+            );
+            return;
+        }
+        pending.insert(user_id, login);
+    }
+
+    /// Writes all buffered logins. On failure they are put back, unless a newer login of the
+    /// same user was recorded in the meantime.
+    pub async fn flush(&self) -> Result<usize, MqttServiceError> {
+        let batch: Vec<(i32, LoginActivity)> = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            std::mem::take(&mut *pending).into_iter().collect()
+        };
+        if batch.is_empty() {
+            return Ok(0);
+        }
+
+        if let Err(e) = self.repo.record_logins(&batch).await {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            for (user_id, login) in batch {
+                pending.entry(user_id).or_insert(login);
+            }
+            return Err(e.into());
+        }
+
+        debug!(
+            "[Service | LoginActivity] Flushed {} buffered logins",
+            batch.len()
+        );
+        Ok(batch.len())
+    }
+}
@@ -1,7 +1,24 @@
+pub mod api_key_service;
+pub mod audit_service;
 pub mod create_mqtt_service;
 pub mod delete_mqtt_service;
+pub mod expire_mqtt_service;
+pub mod export_mqtt_service;
 pub mod get_mqtt_credentials_service;
 pub mod get_mqtt_list_service;
+pub mod get_mqtt_user_service;
+pub mod import_mosquitto_service;
+pub mod import_mqtt_service;
+pub mod ip_allow_list_service;
+pub mod login_activity_service;
 pub mod mqtt_acl_service;
 pub mod mqtt_login_service;
+pub mod provision_mqtt_service;
+pub mod quota_service;
+pub mod rate_limit_service;
+pub mod request_signing_service;
+pub mod restore_mqtt_service;
+pub mod rotate_mqtt_password_service;
 pub mod service_error;
+pub mod tenant_service;
+pub mod update_mqtt_service;
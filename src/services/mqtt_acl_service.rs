@@ -1,22 +1,58 @@
 use crate::dtos::mqtt_dto::MqttAclDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::get_mqtt_acl_rules_repository::GetMqttAclRulesRepository;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
 use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::services::tenant_service::TenantService;
+use crate::utils::attributes::render_topic_template;
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::topic::topic_matches;
+use chrono::Utc;
 use log::debug;
 use std::sync::Arc;
 
 pub struct MqttAclService {
     repo: Arc<GetMqttByUsernameRepository>,
+    repo_rules: Arc<GetMqttAclRulesRepository>,
+    tenant_service: Arc<TenantService>,
+    policy: Arc<CredentialPolicy>,
 }
 
 impl MqttAclService {
-    pub fn new(repo: Arc<GetMqttByUsernameRepository>) -> MqttAclService {
-        Self { repo }
+    pub fn new(
+        repo: Arc<GetMqttByUsernameRepository>,
+        repo_rules: Arc<GetMqttAclRulesRepository>,
+        tenant_service: Arc<TenantService>,
+        policy: Arc<CredentialPolicy>,
+    ) -> MqttAclService {
+        Self {
+            repo,
+            repo_rules,
+            tenant_service,
+            policy,
+        }
     }
 
-    pub async fn check_acl_permission(&self, dto: MqttAclDTO) -> Result<bool, MqttServiceError> {
+    /// Topics are checked against the tenant's `topic_prefix`: anything outside it is denied,
+    /// and rules and the default scheme apply to the rest of the topic. Superusers of the
+    /// default tenant are the only users not confined to a prefix.
+    pub async fn check_acl_permission(
+        &self,
+        tenant: &TenantContext,
+        mut dto: MqttAclDTO,
+    ) -> Result<bool, MqttServiceError> {
         self.mqtt_input_acl_validation(&dto)?;
+        dto.username = self.policy.normalize_username(&dto.username);
+        let tenant = self
+            .tenant_service
+            .resolve_tenant(tenant, dto.tenant.as_deref())
+            .await?;
 
-        let mqtt = match self.repo.get_mqtt_by_username(&dto.username).await {
+        let mqtt = match self
+            .repo
+            .get_mqtt_by_username(tenant.id, &dto.username)
+            .await
+        {
             Ok(u) => u,
             Err(_) => {
                 debug!(
@@ -27,6 +63,25 @@ impl MqttAclService {
             }
         };
 
+        if !mqtt.is_active_at(Utc::now()) {
+            debug!(
+                "[Service | CheckMQTTACL] User MQTT `{}` is disabled or outside its validity window → access denied",
+                dto.username
+            );
+            return Ok(false);
+        }
+
+        let Some(topic) = dto
+            .topic
+            .strip_prefix(&tenant.topic_prefix)
+            .filter(|t| !t.is_empty())
+        else {
+            debug!(
+                "[Service | CheckMQTTACL] Topic `{}` is outside the prefix `{}` of tenant {} → access denied",
+                dto.topic, tenant.topic_prefix, tenant.name
+            );
+            return Ok(false);
+        };
 
         if mqtt.is_superuser {
             debug!(
@@ -36,11 +91,40 @@ impl MqttAclService {
             return Ok(true);
         }
 
+        if tenant.topic_prefix.is_empty() && self.tenant_service.is_tenant_topic(topic) {
+            debug!(
+                "[Service | CheckMQTTACL] Topic `{}` belongs to another tenant → access denied",
+                dto.topic
+            );
+            return Ok(false);
+        }
+
+        // Stored rules (own, then by role priority) win over the default topic scheme.
+        // Rule topics are templates; a rule whose attribute is missing never matches.
+        let rules = self.repo_rules.get_rules_for_user(mqtt.id).await?;
+        let matched = rules.iter().find(|rule| {
+            (rule.action == "all" || dto.action.is_some_and(|a| a.as_str() == rule.action))
+                && render_topic_template(&rule.topic, &mqtt.username, mqtt.metadata.as_ref())
+                    .is_some_and(|filter| topic_matches(&filter, topic))
+        });
+        if let Some(rule) = matched {
+            let allowed = rule.permission == "allow";
+            debug!(
+                "[Service | CheckMQTTACL] Rule `{} {} {}` matched for user `{}` → access {}",
+                rule.permission,
+                rule.action,
+                rule.topic,
+                dto.username,
+                if allowed { "granted" } else { "denied" }
+            );
+            return Ok(allowed);
+        }
+
         let expected_prefix = format!("users/{}/", dto.username);
-        if !dto.topic.starts_with(&expected_prefix) {
+        if !topic.starts_with(&expected_prefix) {
             debug!(
-                "[Service | CheckMQTTACL] Topic `{}` does not start with expected prefix `{}` → access denied",
-                dto.topic, expected_prefix
+                "[Service | CheckMQTTACL] Topic `{}` does not start with expected prefix `{}{}` → access denied",
+                dto.topic, tenant.topic_prefix, expected_prefix
             );
             return Ok(false);
         }
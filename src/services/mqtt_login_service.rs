@@ -1,28 +1,89 @@
-use crate::dtos::mqtt_dto::{AuthType, MqttLoginDTO};
+use crate::dtos::mqtt_dto::{AuthType, MatchedCredential, MqttLoginDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model as MqttEntity;
 use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::services::login_activity_service::LoginActivityService;
+use crate::services::quota_service::QuotaService;
 use crate::services::service_error::{MqttServiceError, ValidationError};
-use crate::utils::encryption::decrypt_password;
+use crate::services::tenant_service::TenantService;
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::password_hash::verify_password;
 use crate::utils::jwt_sign::create_jwt;
+use chrono::{DateTime, Utc};
 use log::debug;
+use std::net::IpAddr;
 use std::sync::Arc;
 
+pub enum MqttLoginOutcome {
+    Credentials(MatchedCredential),
+    Jwt(String),
+}
+
 pub struct MqttLoginService {
     repo: Arc<GetMqttByUsernameRepository>,
+    repo_tags: Arc<GetMqttTagsRepository>,
+    login_activity: Arc<LoginActivityService>,
+    tenant_service: Arc<TenantService>,
+    quota: Arc<QuotaService>,
     secret_key: String,
+    policy: Arc<CredentialPolicy>,
 }
 
 impl MqttLoginService {
-    pub fn new(repo: Arc<GetMqttByUsernameRepository>, secret_key: String) -> Self {
-        Self { repo, secret_key }
+    pub fn new(
+        repo: Arc<GetMqttByUsernameRepository>,
+        repo_tags: Arc<GetMqttTagsRepository>,
+        login_activity: Arc<LoginActivityService>,
+        tenant_service: Arc<TenantService>,
+        quota: Arc<QuotaService>,
+        secret_key: String,
+        policy: Arc<CredentialPolicy>,
+    ) -> Self {
+        Self {
+            repo,
+            repo_tags,
+            login_activity,
+            tenant_service,
+            quota,
+            secret_key,
+            policy,
+        }
     }
 
     pub async fn login_with_credentials(
         &self,
+        tenant: &TenantContext,
         dto: MqttLoginDTO,
-    ) -> Result<(bool, String), MqttServiceError> {
+    ) -> Result<MqttLoginOutcome, MqttServiceError> {
+        let peerhost = dto
+            .peerhost
+            .as_deref()
+            .and_then(|ip| ip.trim().parse::<IpAddr>().ok());
+        let (user_id, outcome) = self.authenticate(tenant, dto).await?;
+        // Buffered; the login activity job writes it to the database.
+        self.login_activity.record(user_id, peerhost);
+        Ok(outcome)
+    }
+
+    async fn authenticate(
+        &self,
+        tenant: &TenantContext,
+        mut dto: MqttLoginDTO,
+    ) -> Result<(i32, MqttLoginOutcome), MqttServiceError> {
         self.mqtt_input_credentials_validation(&dto)?;
+        dto.username = self.policy.normalize_username(&dto.username);
+        let tenant = self
+            .tenant_service
+            .resolve_tenant(tenant, dto.tenant.as_deref())
+            .await?;
+        self.quota.check_auth_rate(&tenant)?;
 
-        let mqtt = match self.repo.get_mqtt_by_username(&dto.username).await {
+        let mqtt = match self
+            .repo
+            .get_mqtt_by_username(tenant.id, &dto.username)
+            .await
+        {
             Ok(u) => u,
             Err(_) => {
                 debug!(
@@ -33,14 +94,25 @@ impl MqttLoginService {
             }
         };
 
-
+        // Whether a user is disabled or expired is only revealed to a caller that proved the
+        // password, so the 403 cannot be used to probe usernames.
+        let now = Utc::now();
         match dto.method.unwrap() {
             AuthType::Credentials => {
-                let decrypted_stored = decrypt_password(&mqtt.password)
-                    .map_err(|e| MqttServiceError::InternalError(e))?;
-                
-                let is_valid = dto.password == decrypted_stored;
-                if !is_valid {
+                let matched = if verify_password(&mqtt.password, &dto.password)
+                    .map_err(MqttServiceError::InternalError)?
+                {
+                    MatchedCredential::Current
+                } else if let Some(previous) = mqtt.previous_password_at(now)
+                    && verify_password(previous, &dto.password)
+                        .map_err(MqttServiceError::InternalError)?
+                {
+                    debug!(
+                        "[Service | CheckMQTTActive] User MQTT {} authenticated with its previous password",
+                        dto.username
+                    );
+                    MatchedCredential::Previous
+                } else {
                     debug!(
                         "[Service | CheckMQTTActive] Invalid credentials for user MQTT: {}",
                         dto.username
@@ -48,22 +120,43 @@ impl MqttLoginService {
                     return Err(MqttServiceError::InvalidCredentials(
                         "Invalid credentials".into(),
                     ));
-                }
+                };
 
-                Ok((true, String::new()))
+                self.check_active(&mqtt, &dto.username, now)?;
+                Ok((mqtt.id, MqttLoginOutcome::Credentials(matched)))
             }
             AuthType::Jwt => {
-                let token = create_jwt(&dto.username, &self.secret_key)
+                self.check_active(&mqtt, &dto.username, now)?;
+                let tags = self.repo_tags.get_tags(mqtt.id).await?;
+                let token = create_jwt(&dto.username, mqtt.metadata, tags, &self.secret_key)
                     .map_err(|e| MqttServiceError::JwtError(e.to_string()))?;
                 debug!(
                     "[Service | CheckMQTTActive] JWT token created for user MQTT: {}",
                     dto.username
                 );
-                Ok((true, token))
+                Ok((mqtt.id, MqttLoginOutcome::Jwt(token)))
             }
         }
     }
 
+    fn check_active(
+        &self,
+        mqtt: &MqttEntity,
+        username: &str,
+        now: DateTime<Utc>,
+    ) -> Result<(), MqttServiceError> {
+        if mqtt.is_active_at(now) {
+            return Ok(());
+        }
+        debug!(
+            "[Service | CheckMQTTActive] User MQTT {} is disabled or outside its validity window",
+            username
+        );
+        Err(MqttServiceError::Forbidden(
+            "User MQTT is disabled or expired".into(),
+        ))
+    }
+
     fn mqtt_input_credentials_validation(
         &self,
         dto: &MqttLoginDTO,
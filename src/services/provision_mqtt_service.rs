@@ -0,0 +1,329 @@
+use log::debug;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{ProvisionMqttDTO, ProvisionMqttResultDTO, ProvisionedCredentialDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::repositories::import_mqtt_repository::NewMqttUser;
+use crate::repositories::provision_mqtt_repository::ProvisionMqttRepository;
+use crate::repositories::repository_error::MqttRepositoryError;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::quota_service::QuotaService;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::services::tenant_service::DEFAULT_TENANT;
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::encryption::{decrypt_password, encrypt_password};
+use crate::utils::name_pattern::NamePattern;
+use crate::utils::password_generator::{PasswordGeneratorConfig, password_length_error};
+
+const MAX_BATCH_ID_LENGTH: usize = 64;
+
+pub struct ProvisionMqttService {
+    repo_provision: Arc<ProvisionMqttRepository>,
+    quota: Arc<QuotaService>,
+    password_generator: PasswordGeneratorConfig,
+    max_users: usize,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+impl ProvisionMqttService {
+    pub fn new(
+        repo_provision: Arc<ProvisionMqttRepository>,
+        quota: Arc<QuotaService>,
+        password_generator: PasswordGeneratorConfig,
+        max_users: usize,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_provision,
+            quota,
+            password_generator,
+            max_users,
+            policy,
+            audit,
+        }
+    }
+
+    /// Creates the users of a batch and returns their credentials.
+    ///
+    /// Re-running a batch with identical parameters creates whatever an earlier run did not
+    /// get to and returns the full manifest again; different parameters are a conflict.
+    pub async fn provision_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        dto: ProvisionMqttDTO,
+    ) -> Result<ProvisionMqttResultDTO, MqttServiceError> {
+        let batch_id = dto.batch_id.clone();
+        let result = self.provision(tenant, dto).await;
+        let change = result.as_ref().map(|manifest| {
+            AuditChange::created(&serde_json::json!({
+                "total": manifest.total,
+                "created": manifest.created,
+                "existing": manifest.existing,
+            }))
+        });
+        self.audit
+            .record(actor, AuditAction::UserProvision, Some(&batch_id), change)
+            .await;
+        result
+    }
+
+    async fn provision(
+        &self,
+        tenant: &TenantContext,
+        dto: ProvisionMqttDTO,
+    ) -> Result<ProvisionMqttResultDTO, MqttServiceError> {
+        let usernames = self.provision_mqtt_validation(&dto)?;
+        let role_ids = self.role_ids(tenant, &dto.roles).await?;
+        let mut spec = serde_json::to_value(&dto)
+            .map_err(|e| MqttServiceError::InternalError(e.to_string()))?;
+        // Batch ids are shared by all tenants, so the same id from another tenant is a
+        // conflict. Batches of the default tenant keep the spec they had before tenants.
+        if tenant.name != DEFAULT_TENANT {
+            spec["tenant"] = serde_json::Value::String(tenant.name.clone());
+        }
+
+        match self.repo_provision.find_batch(&dto.batch_id).await? {
+            Some(batch) if batch.spec != spec => return Err(batch_conflict(&dto.batch_id)),
+            Some(_) => debug!("[Service | ProvisionMQTT] Resuming batch {}", dto.batch_id),
+            None => match self
+                .repo_provision
+                .create_batch(&dto.batch_id, spec.clone(), usernames.len() as i32)
+                .await
+            {
+                Ok(()) => {}
+                Err(MqttRepositoryError::AlreadyExists) => {
+                    let batch = self.repo_provision.find_batch(&dto.batch_id).await?;
+                    if batch.is_none_or(|batch| batch.spec != spec) {
+                        return Err(batch_conflict(&dto.batch_id));
+                    }
+                }
+                Err(e) => return Err(e.into()),
+            },
+        }
+
+        let existing = self
+            .repo_provision
+            .find_users_by_usernames(tenant.id, &usernames)
+            .await?;
+        let foreign: Vec<&str> = existing
+            .iter()
+            .filter(|user| user.provision_batch_id.as_deref() != Some(dto.batch_id.as_str()))
+            .map(|user| user.username.as_str())
+            .collect();
+        if let Some(first) = foreign.first() {
+            return Err(MqttServiceError::Conflict(format!(
+                "{} username(s) of this batch already exist outside it, e.g. {}",
+                foreign.len(),
+                first
+            )));
+        }
+
+        let mut passwords = HashMap::with_capacity(usernames.len());
+        for user in &existing {
+            let password =
+                decrypt_password(&user.password).map_err(MqttServiceError::InternalError)?;
+            passwords.insert(user.username.clone(), password);
+        }
+
+        let existing_names: HashSet<&str> = existing.iter().map(|u| u.username.as_str()).collect();
+        let mut pending = Vec::new();
+        for username in usernames
+            .iter()
+            .filter(|u| !existing_names.contains(u.as_str()))
+        {
+            let password = self
+                .password_generator
+                .generate(dto.password_length, dto.password_alphabet);
+            pending.push(NewMqttUser {
+                tenant_id: tenant.id,
+                username: username.clone(),
+                password: encrypt_password(&password).map_err(MqttServiceError::InternalError)?,
+                is_superuser: dto.is_superuser,
+                is_enabled: true,
+                metadata: dto.metadata.clone(),
+                valid_from: dto.valid_from,
+                valid_until: dto.valid_until,
+                provision_batch_id: Some(dto.batch_id.clone()),
+            });
+            passwords.insert(username.clone(), password);
+        }
+
+        let quota_rows: Vec<(&str, bool)> = pending
+            .iter()
+            .map(|u| (u.username.as_str(), u.is_superuser))
+            .collect();
+        self.quota.check_users(tenant, &quota_rows, false).await?;
+
+        let created = match self.repo_provision.insert_users(pending, &role_ids).await {
+            Ok(created) => created,
+            Err(MqttRepositoryError::AlreadyExists) => {
+                return Err(MqttServiceError::Conflict(
+                    "A username of this batch was created concurrently, retry the batch"
+                        .to_string(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        debug!(
+            "[Service | ProvisionMQTT] Batch {} provisioned ({} created, {} existing)",
+            dto.batch_id,
+            created,
+            existing.len()
+        );
+
+        let credentials = usernames
+            .into_iter()
+            .map(|username| {
+                let password = passwords.remove(&username).unwrap_or_default();
+                ProvisionedCredentialDTO { username, password }
+            })
+            .collect::<Vec<_>>();
+        Ok(ProvisionMqttResultDTO {
+            batch_id: dto.batch_id,
+            total: credentials.len(),
+            created,
+            existing: existing.len(),
+            credentials,
+        })
+    }
+
+    /// Resolves the role names of the batch, rejecting any the tenant does not have.
+    async fn role_ids(
+        &self,
+        tenant: &TenantContext,
+        roles: &[String],
+    ) -> Result<Vec<i32>, MqttServiceError> {
+        if roles.is_empty() {
+            return Ok(Vec::new());
+        }
+        let known = self.repo_provision.get_role_ids(tenant.id).await?;
+        let mut role_ids = Vec::with_capacity(roles.len());
+        let mut errors = Vec::new();
+        for role in roles {
+            match known.get(role.trim()) {
+                Some(id) => role_ids.push(*id),
+                None => errors.push(ValidationError {
+                    field: "roles".to_string(),
+                    message: format!("role `{}` does not exist", role.trim()),
+                }),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+        role_ids.sort_unstable();
+        role_ids.dedup();
+        Ok(role_ids)
+    }
+
+    /// Validates the request and returns the expanded usernames.
+    fn provision_mqtt_validation(
+        &self,
+        dto: &ProvisionMqttDTO,
+    ) -> Result<Vec<String>, MqttServiceError> {
+        let mut errors = Vec::new();
+        if dto.batch_id.is_empty() || dto.batch_id.len() > MAX_BATCH_ID_LENGTH {
+            errors.push(ValidationError {
+                field: "batch_id".to_string(),
+                message: format!(
+                    "batch_id must be between 1 and {} characters",
+                    MAX_BATCH_ID_LENGTH
+                ),
+            });
+        } else if !dto
+            .batch_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        {
+            errors.push(ValidationError {
+                field: "batch_id".to_string(),
+                message: "batch_id may only contain letters, digits, '-', '_' and '.'".to_string(),
+            });
+        }
+
+        if dto.count == Some(0) {
+            errors.push(ValidationError {
+                field: "count".to_string(),
+                message: "count must be greater than zero".to_string(),
+            });
+        }
+
+        let mut usernames = Vec::new();
+        // The size is checked before expanding, so a huge count is rejected without allocating.
+        match NamePattern::parse(&dto.pattern, &dto.variables, dto.start, dto.count) {
+            Ok(pattern) if pattern.count() > self.max_users as u64 => {
+                errors.push(ValidationError {
+                    field: "count".to_string(),
+                    message: format!("a batch cannot exceed {} users", self.max_users),
+                })
+            }
+            Ok(pattern) => {
+                usernames = pattern
+                    .expand()
+                    .iter()
+                    .map(|u| self.policy.normalize_username(u))
+                    .collect()
+            }
+            Err(message) => errors.push(ValidationError {
+                field: "pattern".to_string(),
+                message,
+            }),
+        }
+
+        // Reports the first generated name that violates the policy.
+        if let Some((username, messages)) = usernames
+            .iter()
+            .map(|u| (u, self.policy.username_errors(u)))
+            .find(|(_, messages)| !messages.is_empty())
+        {
+            for message in messages {
+                errors.push(ValidationError {
+                    field: "pattern".to_string(),
+                    message: format!("{}: {}", username, message),
+                });
+            }
+        }
+
+        if let Some(message) = dto.password_length.and_then(password_length_error) {
+            errors.push(ValidationError {
+                field: "password_length".to_string(),
+                message,
+            });
+        }
+
+        if dto.metadata.as_ref().is_some_and(|m| !m.is_object()) {
+            errors.push(ValidationError {
+                field: "metadata".to_string(),
+                message: "metadata must be a JSON object".to_string(),
+            });
+        }
+
+        if let (Some(from), Some(until)) = (dto.valid_from, dto.valid_until)
+            && until <= from
+        {
+            errors.push(ValidationError {
+                field: "valid_until".to_string(),
+                message: "valid_until must be later than valid_from".to_string(),
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        Ok(usernames)
+    }
+}
+
+fn batch_conflict(batch_id: &str) -> MqttServiceError {
+    MqttServiceError::Conflict(format!(
+        "Provisioning batch {} was already run with different parameters",
+        batch_id
+    ))
+}
@@ -0,0 +1,176 @@
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+use crate::dtos::tenant_dto::{TenantContext, TenantUsageDTO};
+use crate::repositories::tenant_usage_repository::TenantUsageRepository;
+use crate::services::service_error::MqttServiceError;
+
+/// Auth calls of a tenant in the current minute on this instance.
+#[derive(Default)]
+struct AuthWindow {
+    minute: i64,
+    calls: u64,
+    throttled: u64,
+}
+
+/// Enforces tenant quotas. Row quotas are checked against the database before a write and
+/// fail with `QuotaExceeded`; the auth rate is counted in memory per instance and fails with
+/// `TooManyRequests`.
+pub struct QuotaService {
+    repo: Arc<TenantUsageRepository>,
+    auth_windows: Mutex<HashMap<i32, AuthWindow>>,
+}
+
+impl QuotaService {
+    pub fn new(repo: Arc<TenantUsageRepository>) -> Self {
+        Self {
+            repo,
+            auth_windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Checks that writing `users` (username, is_superuser) stays within the user and superuser
+    /// quotas. With `overwrite`, existing users are replaced and only count when promoted to
+    /// superuser; otherwise they are left alone.
+    pub async fn check_users(
+        &self,
+        tenant: &TenantContext,
+        users: &[(&str, bool)],
+        overwrite: bool,
+    ) -> Result<(), MqttServiceError> {
+        let quota = tenant.quota;
+        if quota.max_users.is_none() && quota.max_superusers.is_none() {
+            return Ok(());
+        }
+
+        let usernames: Vec<&str> = users.iter().map(|(username, _)| *username).collect();
+        let existing = self.repo.existing_users(tenant.id, &usernames).await?;
+        let (mut added, mut added_superusers) = (0u64, 0u64);
+        for (username, is_superuser) in users {
+            match existing.get(*username) {
+                None => {
+                    added += 1;
+                    added_superusers += u64::from(*is_superuser);
+                }
+                Some(false) if overwrite && *is_superuser => added_superusers += 1,
+                Some(_) => {}
+            }
+        }
+        if added == 0 && added_superusers == 0 {
+            return Ok(());
+        }
+
+        let (current, current_superusers) = self.repo.count_users(tenant.id).await?;
+        exceeds(tenant, "users", quota.max_users, current + added)?;
+        exceeds(
+            tenant,
+            "superusers",
+            quota.max_superusers,
+            current_superusers + added_superusers,
+        )
+    }
+
+    /// Checks that adding `added` ACL rules while replacing those of `replaced_roles` stays
+    /// within the ACL rule quota.
+    pub async fn check_acl_rules(
+        &self,
+        tenant: &TenantContext,
+        added: u64,
+        replaced_roles: &[String],
+    ) -> Result<(), MqttServiceError> {
+        let Some(max) = tenant.quota.max_acl_rules else {
+            return Ok(());
+        };
+        let current = self.repo.count_acl_rules(tenant.id).await?;
+        let replaced = self.repo.count_role_rules(tenant.id, replaced_roles).await?;
+        exceeds(
+            tenant,
+            "ACL rules",
+            Some(max),
+            current.saturating_sub(replaced) + added,
+        )
+    }
+
+    /// Counts an auth call of the tenant and rejects it once `max_auth_per_minute` calls were
+    /// made in the current minute.
+    pub fn check_auth_rate(&self, tenant: &TenantContext) -> Result<(), MqttServiceError> {
+        let minute = current_minute(Utc::now());
+        let mut windows = self.auth_windows.lock().unwrap_or_else(|e| e.into_inner());
+        let window = windows.entry(tenant.id).or_default();
+        if window.minute != minute {
+            window.minute = minute;
+            window.calls = 0;
+        }
+
+        if let Some(max) = tenant.quota.max_auth_per_minute
+            && window.calls >= max.max(0) as u64
+        {
+            window.throttled += 1;
+            debug!(
+                "[Service | Quota] Tenant {} reached {} auth calls per minute",
+                tenant.name, max
+            );
+            return Err(MqttServiceError::TooManyRequests(format!(
+                "Tenant {} exceeded its quota of {} auth calls per minute",
+                tenant.name, max
+            )));
+        }
+        window.calls += 1;
+        Ok(())
+    }
+
+    /// Current usage of the tenant against its quota.
+    pub async fn usage(&self, tenant: &TenantContext) -> Result<TenantUsageDTO, MqttServiceError> {
+        let (users, superusers) = self.repo.count_users(tenant.id).await?;
+        let acl_rules = self.repo.count_acl_rules(tenant.id).await?;
+        let (auth_calls_this_minute, auth_calls_throttled) = {
+            let windows = self.auth_windows.lock().unwrap_or_else(|e| e.into_inner());
+            windows.get(&tenant.id).map_or((0, 0), |window| {
+                let calls = if window.minute == current_minute(Utc::now()) {
+                    window.calls
+                } else {
+                    0
+                };
+                (calls, window.throttled)
+            })
+        };
+
+        Ok(TenantUsageDTO {
+            name: tenant.name.clone(),
+            users,
+            superusers,
+            acl_rules,
+            auth_calls_this_minute,
+            auth_calls_throttled,
+            quota: tenant.quota,
+        })
+    }
+}
+
+fn current_minute(now: DateTime<Utc>) -> i64 {
+    now.timestamp().div_euclid(60)
+}
+
+fn exceeds(
+    tenant: &TenantContext,
+    what: &str,
+    max: Option<i32>,
+    after: u64,
+) -> Result<(), MqttServiceError> {
+    match max {
+        Some(max) if after > max.max(0) as u64 => {
+            debug!(
+                "[Service | Quota] Tenant {} would have {} {} (quota {})",
+                tenant.name, after, what, max
+            );
+            Err(MqttServiceError::QuotaExceeded(format!(
+                "Tenant {} would exceed its quota of {} {} ({} after this change)",
+                tenant.name, max, what, after
+            )))
+        }
+        _ => Ok(()),
+    }
+}
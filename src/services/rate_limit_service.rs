@@ -0,0 +1,324 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::middleware::route_group::RouteGroup;
+
+/// Idle buckets are dropped this often, once they have refilled.
+const PRUNE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Token bucket settings: `burst` requests at once, refilled at `rate_per_sec`.
+#[derive(Clone, Copy, Debug)]
+pub struct RateLimitRule {
+    pub rate_per_sec: f64,
+    pub burst: f64,
+}
+
+impl RateLimitRule {
+    /// Parses `rate[:burst]`, e.g. `50:200`; the burst defaults to the rate.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (rate, burst) = match value.trim().split_once(':') {
+            Some((rate, burst)) => (rate, Some(burst)),
+            None => (value.trim(), None),
+        };
+        let rate_per_sec = rate
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|r| r.is_finite() && *r > 0.0)?;
+        let burst = match burst {
+            Some(burst) => burst
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|b| b.is_finite() && *b >= 1.0)?,
+            None => rate_per_sec.max(1.0),
+        };
+        Some(Self {
+            rate_per_sec,
+            burst: burst.floor(),
+        })
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct GroupLimits {
+    per_key: Option<RateLimitRule>,
+    per_ip: Option<RateLimitRule>,
+}
+
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+    rule: RateLimitRule,
+}
+
+impl Bucket {
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rule.rate_per_sec).min(self.rule.burst);
+        self.updated = now;
+    }
+}
+
+/// Outcome of a rate limit check, with what goes in the rate limit headers.
+pub enum RateLimitDecision {
+    /// No limit applies to the request
+    Unlimited,
+    Allowed {
+        limit: u64,
+        remaining: u64,
+    },
+    Limited {
+        limit: u64,
+        retry_after_secs: u64,
+    },
+}
+
+/// Token-bucket rate limits per API key and per client IP, configured per route group with
+/// `RATE_LIMIT_{HOOK,ADMIN}_{PER_KEY,PER_IP}`. Buckets are kept per instance only.
+pub struct RateLimitService {
+    limits: HashMap<RouteGroup, GroupLimits>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    last_prune: Mutex<Instant>,
+}
+
+impl RateLimitService {
+    pub fn from_env() -> Self {
+        let rule = |name: String| {
+            let value = std::env::var(&name).ok().filter(|v| !v.trim().is_empty())?;
+            let rule = RateLimitRule::parse(&value).unwrap_or_else(|| {
+                panic!(
+                    "❌ Invalid {}: {:?} is not rate[:burst] with a positive rate and a burst of at least 1",
+                    name, value
+                )
+            });
+            Some(rule)
+        };
+        let limits: HashMap<RouteGroup, GroupLimits> = RouteGroup::ALL
+            .into_iter()
+            .map(|group| {
+                let limits = GroupLimits {
+                    per_key: rule(format!("RATE_LIMIT_{}_PER_KEY", group.env_name())),
+                    per_ip: rule(format!("RATE_LIMIT_{}_PER_IP", group.env_name())),
+                };
+                if let Some(rule) = limits.per_key {
+                    info!(
+                        "🚦 Rate limit for {} routes: {}/s per key (burst {})",
+                        group.as_str(),
+                        rule.rate_per_sec,
+                        rule.burst
+                    );
+                }
+                if let Some(rule) = limits.per_ip {
+                    info!(
+                        "🚦 Rate limit for {} routes: {}/s per IP (burst {})",
+                        group.as_str(),
+                        rule.rate_per_sec,
+                        rule.burst
+                    );
+                }
+                (group, limits)
+            })
+            .collect();
+
+        Self {
+            limits,
+            buckets: Mutex::new(HashMap::new()),
+            last_prune: Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.limits
+            .values()
+            .any(|limits| limits.per_key.is_some() || limits.per_ip.is_some())
+    }
+
+    /// Takes a token from the key's and the IP's bucket of `group`, or from neither if either
+    /// is empty.
+    pub fn check(
+        &self,
+        group: RouteGroup,
+        key_name: Option<&str>,
+        client_ip: Option<&str>,
+    ) -> RateLimitDecision {
+        let limits = self.limits.get(&group).copied().unwrap_or_default();
+        let applicable: Vec<(String, RateLimitRule)> = [
+            limits
+                .per_key
+                .zip(key_name)
+                .map(|(rule, key)| (format!("{}:key:{}", group.as_str(), key), rule)),
+            limits
+                .per_ip
+                .zip(client_ip)
+                .map(|(rule, ip)| (format!("{}:ip:{}", group.as_str(), ip), rule)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        if applicable.is_empty() {
+            return RateLimitDecision::Unlimited;
+        }
+
+        let now = Instant::now();
+        self.prune(now);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        for (id, rule) in &applicable {
+            let bucket = buckets.entry(id.clone()).or_insert(Bucket {
+                tokens: rule.burst,
+                updated: now,
+                rule: *rule,
+            });
+            bucket.rule = *rule;
+            bucket.refill(now);
+        }
+
+        let limited = applicable
+            .iter()
+            .filter_map(|(id, rule)| {
+                let tokens = buckets.get(id)?.tokens;
+                (tokens < 1.0).then(|| (rule, ((1.0 - tokens) / rule.rate_per_sec).ceil()))
+            })
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        if let Some((rule, wait_secs)) = limited {
+            debug!(
+                "[Service | RateLimit] Limited {} request (key={:?}, ip={:?})",
+                group.as_str(),
+                key_name,
+                client_ip
+            );
+            return RateLimitDecision::Limited {
+                limit: rule.burst as u64,
+                retry_after_secs: (wait_secs as u64).max(1),
+            };
+        }
+
+        // Report the bucket closest to running out
+        let mut tightest: Option<(u64, u64)> = None;
+        for (id, rule) in &applicable {
+            if let Some(bucket) = buckets.get_mut(id) {
+                bucket.tokens -= 1.0;
+                let remaining = bucket.tokens.floor() as u64;
+                if tightest.is_none_or(|(_, r)| remaining < r) {
+                    tightest = Some((rule.burst as u64, remaining));
+                }
+            }
+        }
+        match tightest {
+            Some((limit, remaining)) => RateLimitDecision::Allowed { limit, remaining },
+            None => RateLimitDecision::Unlimited,
+        }
+    }
+
+    /// Drops buckets that have refilled, so one-off clients do not accumulate.
+    fn prune(&self, now: Instant) {
+        {
+            let mut last_prune = self.last_prune.lock().unwrap_or_else(|e| e.into_inner());
+            if now.duration_since(*last_prune) < PRUNE_INTERVAL {
+                return;
+            }
+            *last_prune = now;
+        }
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let before = buckets.len();
+        buckets.retain(|_, bucket| {
+            bucket.refill(now);
+            bucket.tokens < bucket.rule.burst
+        });
+        debug!(
+            "[Service | RateLimit] Pruned {} idle buckets",
+            before - buckets.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn service(group: RouteGroup, limits: GroupLimits) -> RateLimitService {
+        RateLimitService {
+            limits: HashMap::from([(group, limits)]),
+            buckets: Mutex::new(HashMap::new()),
+            last_prune: Mutex::new(Instant::now()),
+        }
+    }
+
+    fn rule(value: &str) -> RateLimitRule {
+        RateLimitRule::parse(value).unwrap()
+    }
+
+    #[test]
+    fn parses_rules() {
+        let rule = rule("50:200");
+        assert_eq!((rule.rate_per_sec, rule.burst), (50.0, 200.0));
+        let rule = RateLimitRule::parse("0.5").unwrap();
+        assert_eq!((rule.rate_per_sec, rule.burst), (0.5, 1.0));
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        for value in ["", "0", "-1", "abc", "10:0", "10:x", "inf", "NaN", "10:inf"] {
+            assert!(RateLimitRule::parse(value).is_none(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn limits_once_the_burst_is_spent() {
+        let limits = service(
+            RouteGroup::Hook,
+            GroupLimits {
+                per_key: None,
+                per_ip: Some(rule("1:2")),
+            },
+        );
+        let check = || limits.check(RouteGroup::Hook, None, Some("10.0.0.1"));
+        assert!(matches!(
+            check(),
+            RateLimitDecision::Allowed {
+                limit: 2,
+                remaining: 1
+            }
+        ));
+        assert!(matches!(
+            check(),
+            RateLimitDecision::Allowed {
+                limit: 2,
+                remaining: 0
+            }
+        ));
+        assert!(matches!(
+            check(),
+            RateLimitDecision::Limited {
+                limit: 2,
+                retry_after_secs: 1
+            }
+        ));
+        // Another address has its own bucket
+        assert!(matches!(
+            limits.check(RouteGroup::Hook, None, Some("10.0.0.2")),
+            RateLimitDecision::Allowed { .. }
+        ));
+    }
+
+    #[test]
+    fn unconfigured_limits_do_not_apply() {
+        let limits = service(
+            RouteGroup::Hook,
+            GroupLimits {
+                per_key: Some(rule("1")),
+                per_ip: None,
+            },
+        );
+        assert!(matches!(
+            limits.check(RouteGroup::Hook, None, Some("10.0.0.1")),
+            RateLimitDecision::Unlimited
+        ));
+        assert!(matches!(
+            limits.check(RouteGroup::Admin, Some("key"), None),
+            RateLimitDecision::Unlimited
+        ));
+    }
+}
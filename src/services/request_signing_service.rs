@@ -0,0 +1,312 @@
+use log::{debug, info};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use chrono::Utc;
+
+use crate::dtos::api_key_dto::{ApiKeyContext, ApiScope};
+use crate::services::service_error::MqttServiceError;
+use crate::utils::request_signature::{canonical_request, verify_request};
+
+/// Nonces are remembered for this many skew windows, so a replay is caught for as long as its
+/// timestamp is accepted.
+const NONCE_RETENTION_WINDOWS: i64 = 2;
+
+const DEFAULT_MAX_SKEW_SECS: i64 = 300;
+/// A wider window keeps replayable requests valid, and nonces in memory, for longer.
+const MAX_SKEW_LIMIT_SECS: i64 = 3600;
+
+/// The signature headers of a request, with what they sign.
+pub struct SignedRequest<'a> {
+    pub key_id: &'a str,
+    pub timestamp: &'a str,
+    pub nonce: &'a str,
+    pub signature: &'a str,
+    pub method: &'a str,
+    pub path_and_query: &'a str,
+    pub body: &'a [u8],
+}
+
+#[derive(Default)]
+struct NonceCache {
+    seen: HashSet<String>,
+    /// (timestamp, nonce) in insertion order, for expiry
+    order: VecDeque<(i64, String)>,
+}
+
+/// Verifies HMAC-signed requests, an alternative to bearer keys for the broker hooks.
+///
+/// Secrets are configured with `REQUEST_SIGNING_SECRETS` as `key_id:secret` pairs. A signed
+/// request is granted the `hook:auth` and `hook:acl` scopes. Nonces are remembered per
+/// instance only.
+pub struct RequestSigningService {
+    secrets: HashMap<String, Vec<u8>>,
+    max_skew_secs: i64,
+    nonces: Mutex<NonceCache>,
+}
+
+impl RequestSigningService {
+    pub fn from_env() -> Self {
+        let secrets = parse_secrets(&std::env::var("REQUEST_SIGNING_SECRETS").unwrap_or_default())
+            .unwrap_or_else(|e| panic!("❌ Invalid REQUEST_SIGNING_SECRETS: {}", e));
+        let max_skew_secs = parse_max_skew(
+            std::env::var("REQUEST_SIGNING_MAX_SKEW_SECS")
+                .ok()
+                .as_deref(),
+        )
+        .unwrap_or_else(|e| panic!("❌ Invalid REQUEST_SIGNING_MAX_SKEW_SECS: {}", e));
+
+        if !secrets.is_empty() {
+            info!(
+                "✍️ Request signing enabled for {} keys (max skew {}s)",
+                secrets.len(),
+                max_skew_secs
+            );
+        }
+        Self {
+            secrets,
+            max_skew_secs,
+            nonces: Mutex::new(NonceCache::default()),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.secrets.is_empty()
+    }
+
+    /// Checks the signature, then the clock skew, then that the nonce was not used before.
+    pub fn verify(&self, request: &SignedRequest<'_>) -> Result<ApiKeyContext, MqttServiceError> {
+        let Some(secret) = self.secrets.get(request.key_id) else {
+            return Err(MqttServiceError::InvalidCredentials(
+                "Unknown signing key".into(),
+            ));
+        };
+
+        let canonical = canonical_request(
+            request.method,
+            request.path_and_query,
+            request.timestamp,
+            request.nonce,
+            request.body,
+        );
+        if !verify_request(secret, &canonical, request.signature) {
+            return Err(MqttServiceError::InvalidCredentials(
+                "Invalid request signature".into(),
+            ));
+        }
+
+        let now = Utc::now().timestamp();
+        let Ok(timestamp) = request.timestamp.parse::<i64>() else {
+            return Err(MqttServiceError::InvalidCredentials(
+                "Invalid signature timestamp".into(),
+            ));
+        };
+        if now.abs_diff(timestamp) > self.max_skew_secs.unsigned_abs() {
+            return Err(MqttServiceError::InvalidCredentials(
+                "Signature timestamp is outside the allowed clock skew".into(),
+            ));
+        }
+
+        if request.nonce.is_empty() || !self.remember_nonce(request.key_id, request.nonce, now) {
+            debug!(
+                "[Service | RequestSigning] Replayed nonce from signing key {}",
+                request.key_id
+            );
+            return Err(MqttServiceError::InvalidCredentials(
+                "Signature nonce was already used".into(),
+            ));
+        }
+
+        Ok(ApiKeyContext {
+            name: format!("signed:{}", request.key_id),
+            scopes: vec![ApiScope::HookAuth, ApiScope::HookAcl],
+        })
+    }
+
+    /// Records a nonce; `false` if it was seen within the retention window.
+    fn remember_nonce(&self, key_id: &str, nonce: &str, now: i64) -> bool {
+        let expired_before = self
+            .max_skew_secs
+            .checked_mul(NONCE_RETENTION_WINDOWS)
+            .and_then(|retention| now.checked_sub(retention))
+            .unwrap_or(i64::MIN);
+        let mut cache = self.nonces.lock().unwrap_or_else(|e| e.into_inner());
+        while cache
+            .order
+            .front()
+            .is_some_and(|(seen_at, _)| *seen_at < expired_before)
+        {
+            if let Some((_, expired)) = cache.order.pop_front() {
+                cache.seen.remove(&expired);
+            }
+        }
+
+        let entry = format!("{}:{}", key_id, nonce);
+        if !cache.seen.insert(entry.clone()) {
+            return false;
+        }
+        cache.order.push_back((now, entry));
+        true
+    }
+}
+
+/// Parses `key_id:secret` pairs separated by commas. Secrets are left out of the errors.
+fn parse_secrets(value: &str) -> Result<HashMap<String, Vec<u8>>, String> {
+    let mut secrets = HashMap::new();
+    for (index, entry) in value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .enumerate()
+    {
+        let Some((key_id, secret)) = entry.split_once(':') else {
+            return Err(format!("entry {} is not a key_id:secret pair", index + 1));
+        };
+        let (key_id, secret) = (key_id.trim(), secret.trim());
+        if key_id.is_empty() || secret.is_empty() {
+            return Err(format!("entry {} has an empty key id or secret", index + 1));
+        }
+        if secrets
+            .insert(key_id.to_string(), secret.as_bytes().to_vec())
+            .is_some()
+        {
+            return Err(format!("key id '{}' is configured twice", key_id));
+        }
+    }
+    Ok(secrets)
+}
+
+/// Parses the allowed clock skew; unset or empty means the default.
+fn parse_max_skew(value: Option<&str>) -> Result<i64, String> {
+    let Some(value) = value.map(str::trim).filter(|v| !v.is_empty()) else {
+        return Ok(DEFAULT_MAX_SKEW_SECS);
+    };
+    match value.parse::<i64>() {
+        Ok(secs) if (1..=MAX_SKEW_LIMIT_SECS).contains(&secs) => Ok(secs),
+        _ => Err(format!(
+            "'{}' must be a number of seconds between 1 and {}",
+            value, MAX_SKEW_LIMIT_SECS
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hmac::{Hmac, Mac};
+    use sha2::Sha256;
+
+    fn service() -> RequestSigningService {
+        RequestSigningService {
+            secrets: HashMap::from([("hook".to_string(), b"secret".to_vec())]),
+            max_skew_secs: 300,
+            nonces: Mutex::new(NonceCache::default()),
+        }
+    }
+
+    fn sign(timestamp: &str, nonce: &str, body: &[u8]) -> String {
+        let canonical = canonical_request("POST", "/mqtt/check", timestamp, nonce, body);
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(canonical.as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    fn request<'a>(timestamp: &'a str, nonce: &'a str, signature: &'a str) -> SignedRequest<'a> {
+        SignedRequest {
+            key_id: "hook",
+            timestamp,
+            nonce,
+            signature,
+            method: "POST",
+            path_and_query: "/mqtt/check",
+            body: b"{}",
+        }
+    }
+
+    #[test]
+    fn accepts_a_signed_request_once() {
+        let service = service();
+        let now = Utc::now().timestamp().to_string();
+        let signature = sign(&now, "n1", b"{}");
+        let context = service
+            .verify(&request(&now, "n1", &signature))
+            .unwrap_or_else(|_| panic!("signed request rejected"));
+        assert_eq!(context.name, "signed:hook");
+        assert!(service.verify(&request(&now, "n1", &signature)).is_err());
+    }
+
+    #[test]
+    fn rejects_bad_signatures_and_keys() {
+        let service = service();
+        let now = Utc::now().timestamp().to_string();
+        let signature = sign(&now, "n1", b"other body");
+        assert!(service.verify(&request(&now, "n1", &signature)).is_err());
+        assert!(service.verify(&request(&now, "n1", "not hex")).is_err());
+
+        let signature = sign(&now, "n1", b"{}");
+        let mut unknown = request(&now, "n1", &signature);
+        unknown.key_id = "other";
+        assert!(service.verify(&unknown).is_err());
+    }
+
+    #[test]
+    fn rejects_timestamps_outside_the_skew() {
+        let service = service();
+        for timestamp in [
+            (Utc::now().timestamp() - 301).to_string(),
+            i64::MIN.to_string(),
+            i64::MAX.to_string(),
+            "soon".to_string(),
+        ] {
+            let signature = sign(&timestamp, "n1", b"{}");
+            assert!(
+                service
+                    .verify(&request(&timestamp, "n1", &signature))
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn forgets_nonces_after_the_retention_window() {
+        let service = service();
+        assert!(service.remember_nonce("hook", "n1", 1_000));
+        assert!(!service.remember_nonce("hook", "n1", 1_000));
+        assert!(service.remember_nonce("other", "n1", 1_000));
+        assert!(service.remember_nonce("hook", "n1", 1_000 + 601));
+    }
+
+    #[test]
+    fn keeps_nonces_when_the_window_reaches_before_the_epoch() {
+        let service = RequestSigningService {
+            max_skew_secs: i64::MAX,
+            ..service()
+        };
+        assert!(service.remember_nonce("hook", "n1", i64::MIN));
+        assert!(!service.remember_nonce("hook", "n1", 0));
+    }
+
+    #[test]
+    fn parses_signing_secrets() {
+        let secrets = parse_secrets(" hook:secret , broker:other:part,").unwrap();
+        assert_eq!(secrets["hook"], b"secret");
+        assert_eq!(secrets["broker"], b"other:part");
+        assert!(parse_secrets("").unwrap().is_empty());
+
+        for value in ["hook", "hook:", ":secret", "hook:a,hook:b"] {
+            assert!(parse_secrets(value).is_err(), "{value}");
+        }
+        assert!(!parse_secrets("hook=s3cr3t").unwrap_err().contains("s3cr3t"));
+    }
+
+    #[test]
+    fn parses_the_max_skew() {
+        assert_eq!(parse_max_skew(None), Ok(DEFAULT_MAX_SKEW_SECS));
+        assert_eq!(parse_max_skew(Some(" ")), Ok(DEFAULT_MAX_SKEW_SECS));
+        assert_eq!(parse_max_skew(Some("60")), Ok(60));
+        assert_eq!(parse_max_skew(Some("3600")), Ok(MAX_SKEW_LIMIT_SECS));
+        for value in ["0", "-5", "3601", "5m", "9223372036854775807"] {
+            assert!(parse_max_skew(Some(value)).is_err(), "{value}");
+        }
+    }
+}
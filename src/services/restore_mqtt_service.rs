@@ -0,0 +1,88 @@
+use log::debug;
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::MqttUserDTO;
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model as MqttEntity;
+use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::repositories::restore_mqtt_repository::RestoreMqttRepository;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::service_error::MqttServiceError;
+use crate::utils::credential_policy::CredentialPolicy;
+
+pub struct RestoreMqttService {
+    repo_get: Arc<GetMqttByUsernameRepository>,
+    repo_restore: Arc<RestoreMqttRepository>,
+    repo_tags: Arc<GetMqttTagsRepository>,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+impl RestoreMqttService {
+    pub fn new(
+        repo_get: Arc<GetMqttByUsernameRepository>,
+        repo_restore: Arc<RestoreMqttRepository>,
+        repo_tags: Arc<GetMqttTagsRepository>,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_get,
+            repo_restore,
+            repo_tags,
+            policy,
+            audit,
+        }
+    }
+
+    pub async fn restore_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        username: &str,
+    ) -> Result<MqttUserDTO, MqttServiceError> {
+        let username = self.policy.normalize_username(username);
+        let result = self.restore(tenant, &username).await;
+        let change = result
+            .as_ref()
+            .map(|(before, after, _)| AuditChange::updated(before, after));
+        self.audit
+            .record(actor, AuditAction::UserRestore, Some(&username), change)
+            .await;
+        result.map(|(_, restored, tags)| MqttUserDTO::new(restored, tags))
+    }
+
+    /// Returns the trashed user, the restored user and its tags.
+    async fn restore(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+    ) -> Result<(MqttEntity, MqttEntity, Vec<String>), MqttServiceError> {
+        let mqtt = match self
+            .repo_get
+            .get_deleted_mqtt_by_username(tenant.id, username)
+            .await
+        {
+            Ok(u) => u,
+            Err(_) => {
+                debug!(
+                    "[Service | RestoreMQTT] No user MQTT {} in the trash",
+                    username
+                );
+                return Err(MqttServiceError::MqttNotFound(
+                    "User MQTT not found in the trash".into(),
+                ));
+            }
+        };
+
+        let restored = self.repo_restore.restore_mqtt(mqtt.id).await?;
+        let tags = self.repo_tags.get_tags(restored.id).await?;
+        debug!(
+            "[Service | RestoreMQTT] User MQTT restored successfully: {}",
+            username
+        );
+        Ok((mqtt, restored, tags))
+    }
+}
@@ -0,0 +1,186 @@
+use chrono::{Duration, TimeDelta, Utc};
+use log::debug;
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{RotateMqttPasswordDTO, RotateMqttPasswordResultDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model as MqttEntity;
+use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::rotate_mqtt_password_repository::RotateMqttPasswordRepository;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::encryption::encrypt_password;
+
+/// Longest grace period for the previous password, one year.
+pub const MAX_GRACE_PERIOD_SECS: i64 = 365 * 24 * 3600;
+
+pub struct RotateMqttPasswordService {
+    repo_get: Arc<GetMqttByUsernameRepository>,
+    repo_rotate: Arc<RotateMqttPasswordRepository>,
+    default_grace_period: Duration,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+impl RotateMqttPasswordService {
+    pub fn new(
+        repo_get: Arc<GetMqttByUsernameRepository>,
+        repo_rotate: Arc<RotateMqttPasswordRepository>,
+        default_grace_period: Duration,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_get,
+            repo_rotate,
+            default_grace_period,
+            policy,
+            audit,
+        }
+    }
+
+    pub async fn rotate_password(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        username: &str,
+        dto: RotateMqttPasswordDTO,
+    ) -> Result<RotateMqttPasswordResultDTO, MqttServiceError> {
+        let username = self.policy.normalize_username(username);
+        let result = self.rotate(tenant, &username, dto).await;
+        let change = result
+            .as_ref()
+            .map(|(before, after)| AuditChange::updated(before, after));
+        self.audit
+            .record(
+                actor,
+                AuditAction::UserRotatePassword,
+                Some(&username),
+                change,
+            )
+            .await;
+        result.map(|(_, after)| RotateMqttPasswordResultDTO {
+            username: after.username,
+            previous_password_expires_at: after.previous_password_expires_at,
+        })
+    }
+
+    /// Returns the user before and after the rotation.
+    async fn rotate(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+        dto: RotateMqttPasswordDTO,
+    ) -> Result<(MqttEntity, MqttEntity), MqttServiceError> {
+        self.rotate_password_validation(&dto)?;
+
+        let mqtt = match self.repo_get.get_mqtt_by_username(tenant.id, username).await {
+            Ok(u) => u,
+            Err(_) => {
+                debug!("[Service | RotatePassword] User MQTT not found: {}", username);
+                return Err(MqttServiceError::MqttNotFound("User MQTT not found".into()));
+            }
+        };
+
+        let grace_period = dto
+            .grace_period_secs
+            .and_then(TimeDelta::try_seconds)
+            .unwrap_or(self.default_grace_period);
+        let (previous_password, previous_password_expires_at) = if grace_period > Duration::zero()
+        {
+            let expires_at = Utc::now().checked_add_signed(grace_period).ok_or_else(|| {
+                MqttServiceError::InternalError("grace period is out of range".into())
+            })?;
+            (Some(mqtt.password.clone()), Some(expires_at))
+        } else {
+            (None, None)
+        };
+
+        let encrypted = encrypt_password(&dto.password).map_err(MqttServiceError::InternalError)?;
+        self.repo_rotate
+            .rotate_password(
+                mqtt.id,
+                &encrypted,
+                previous_password.clone(),
+                previous_password_expires_at,
+            )
+            .await?;
+
+        debug!(
+            "[Service | RotatePassword] Password rotated for user MQTT {} (previous valid until {:?})",
+            username, previous_password_expires_at
+        );
+        let rotated = MqttEntity {
+            password: encrypted,
+            previous_password,
+            previous_password_expires_at,
+            ..mqtt.clone()
+        };
+        Ok((mqtt, rotated))
+    }
+
+    fn rotate_password_validation(
+        &self,
+        dto: &RotateMqttPasswordDTO,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if dto.password.trim().is_empty() {
+            errors.push(ValidationError {
+                field: "password".to_string(),
+                message: "password cannot be empty".to_string(),
+            });
+        } else {
+            for message in self.policy.password_errors(&dto.password) {
+                errors.push(ValidationError {
+                    field: "password".to_string(),
+                    message,
+                });
+            }
+        }
+
+        if let Some(message) = dto.grace_period_secs.and_then(grace_period_error) {
+            errors.push(ValidationError {
+                field: "grace_period_secs".to_string(),
+                message,
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        debug!("[Service | RotatePassword] Rotation input validation passed.");
+        Ok(true)
+    }
+}
+
+/// Why a requested grace period is not accepted, if it is not.
+pub fn grace_period_error(secs: i64) -> Option<String> {
+    (!(0..=MAX_GRACE_PERIOD_SECS).contains(&secs)).then(|| {
+        format!(
+            "grace_period_secs must be between 0 and {}",
+            MAX_GRACE_PERIOD_SECS
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_grace_periods_in_range() {
+        assert_eq!(grace_period_error(0), None);
+        assert_eq!(grace_period_error(86400), None);
+        assert_eq!(grace_period_error(MAX_GRACE_PERIOD_SECS), None);
+    }
+
+    #[test]
+    fn rejects_grace_periods_out_of_range() {
+        assert!(grace_period_error(-1).is_some());
+        assert!(grace_period_error(MAX_GRACE_PERIOD_SECS + 1).is_some());
+        assert!(grace_period_error(i64::MAX).is_some());
+    }
+}
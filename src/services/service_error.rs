@@ -22,6 +22,15 @@ pub enum MqttServiceError {
     #[error("{0}")]
     Conflict(String),
 
+    #[error("{0}")]
+    Forbidden(String),
+
+    #[error("{0}")]
+    QuotaExceeded(String),
+
+    #[error("{0}")]
+    TooManyRequests(String),
+
     #[error("Bad request")]
     BadRequest(Vec<ValidationError>),
 
@@ -0,0 +1,421 @@
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::tenant_dto::{
+    CreateTenantDTO, TenantApiKeyDTO, TenantContext, TenantDTO, TenantQuotaDTO,
+};
+use crate::entities::tenant_entity::Model as TenantEntity;
+use crate::repositories::repository_error::MqttRepositoryError;
+use crate::repositories::tenant_repository::TenantRepository;
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::api_key::{generate_api_key, hash_api_key};
+use crate::utils::topic::reaches_prefix;
+
+/// Name of the tenant every pre-existing user, role and rule was migrated into.
+pub const DEFAULT_TENANT: &str = "default";
+
+/// A cache miss reloads the tenants at most this often, so unknown keys cannot hammer the database.
+const MIN_RELOAD_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_TENANT_NAME_LENGTH: usize = 64;
+const MAX_TOPIC_PREFIX_LENGTH: usize = 128;
+
+#[derive(Default)]
+struct TenantCache {
+    by_name: HashMap<String, TenantEntity>,
+    /// API key hash to tenant name
+    by_key_hash: HashMap<String, String>,
+}
+
+/// Tenants and their API key hashes, cached in memory for `ApiKeyMiddleware`.
+pub struct TenantService {
+    repo: Arc<TenantRepository>,
+    audit: Arc<AuditService>,
+    cache: RwLock<TenantCache>,
+    last_reload: Mutex<Option<Instant>>,
+}
+
+impl TenantService {
+    pub fn new(repo: Arc<TenantRepository>, audit: Arc<AuditService>) -> Self {
+        Self {
+            repo,
+            audit,
+            cache: RwLock::new(TenantCache::default()),
+            last_reload: Mutex::new(None),
+        }
+    }
+
+    /// Loads every tenant into the cache.
+    pub async fn reload(&self) -> Result<usize, MqttServiceError> {
+        let tenants = self.repo.get_all().await?;
+        let mut cache = TenantCache::default();
+        for tenant in tenants {
+            if let Some(hash) = &tenant.api_key_hash {
+                cache.by_key_hash.insert(hash.clone(), tenant.name.clone());
+            }
+            cache.by_name.insert(tenant.name.clone(), tenant);
+        }
+
+        let count = cache.by_name.len();
+        *self.cache.write().unwrap_or_else(|e| e.into_inner()) = cache;
+        *self.last_reload.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+        debug!("[Service | Tenant] Loaded {} tenants", count);
+        Ok(count)
+    }
+
+    /// Reloads after a cache miss, so tenants created through another instance are picked up.
+    async fn reload_after_miss(&self) -> bool {
+        {
+            let mut last_reload = self.last_reload.lock().unwrap_or_else(|e| e.into_inner());
+            if last_reload.is_some_and(|at| at.elapsed() < MIN_RELOAD_INTERVAL) {
+                return false;
+            }
+            *last_reload = Some(Instant::now());
+        }
+        self.reload().await.is_ok()
+    }
+
+    /// Puts a tenant just written to the database into the cache, replacing its previous API
+    /// key. A full reload could fail after the row is committed, and the caller must still get
+    /// the result of the write.
+    fn cache_tenant(&self, tenant: &TenantEntity) {
+        let mut cache = self.cache.write().unwrap_or_else(|e| e.into_inner());
+        cache.by_key_hash.retain(|_, name| *name != tenant.name);
+        if let Some(hash) = &tenant.api_key_hash {
+            cache.by_key_hash.insert(hash.clone(), tenant.name.clone());
+        }
+        cache.by_name.insert(tenant.name.clone(), tenant.clone());
+    }
+
+    fn cached_by_name(&self, name: &str) -> Option<TenantContext> {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        cache.by_name.get(name).map(|tenant| context(tenant, false))
+    }
+
+    fn cached_by_id(&self, id: i32) -> Option<TenantContext> {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        cache
+            .by_name
+            .values()
+            .find(|tenant| tenant.id == id)
+            .map(|tenant| context(tenant, true))
+    }
+
+    fn cached_by_key(&self, api_key: &str) -> Option<TenantContext> {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        let name = cache.by_key_hash.get(&hash_api_key(api_key))?;
+        cache.by_name.get(name).map(|tenant| context(tenant, true))
+    }
+
+    /// Tenant by name, as seen through the global API key.
+    pub async fn by_name(&self, name: &str) -> Option<TenantContext> {
+        match self.cached_by_name(name) {
+            Some(tenant) => Some(tenant),
+            None if self.reload_after_miss().await => self.cached_by_name(name),
+            None => None,
+        }
+    }
+
+    /// Tenant by id, for API keys limited to a tenant.
+    pub async fn by_id(&self, id: i32) -> Option<TenantContext> {
+        match self.cached_by_id(id) {
+            Some(tenant) => Some(tenant),
+            None if self.reload_after_miss().await => self.cached_by_id(id),
+            None => None,
+        }
+    }
+
+    /// Tenant owning a tenant-scoped API key.
+    pub async fn by_api_key(&self, api_key: &str) -> Option<TenantContext> {
+        if api_key.is_empty() {
+            return None;
+        }
+        match self.cached_by_key(api_key) {
+            Some(tenant) => Some(tenant),
+            None if self.reload_after_miss().await => self.cached_by_key(api_key),
+            None => None,
+        }
+    }
+
+    /// Whether `topic`, or a topic matched by it as a filter, lies under the prefix of a tenant
+    /// other than the default one.
+    pub fn is_tenant_topic(&self, topic: &str) -> bool {
+        let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+        cache
+            .by_name
+            .values()
+            .any(|t| !t.topic_prefix.is_empty() && reaches_prefix(topic, &t.topic_prefix))
+    }
+
+    /// Tenant named in a request body or path. Callers using the global key may name any
+    /// tenant; a tenant-scoped key only its own.
+    pub async fn resolve_tenant(
+        &self,
+        tenant: &TenantContext,
+        requested: Option<&str>,
+    ) -> Result<TenantContext, MqttServiceError> {
+        match requested.map(str::trim).filter(|name| !name.is_empty()) {
+            Some(name) if name != tenant.name => {
+                if tenant.scoped {
+                    return Err(MqttServiceError::Forbidden(
+                        "API key is scoped to another tenant".into(),
+                    ));
+                }
+                self.by_name(name)
+                    .await
+                    .ok_or_else(|| MqttServiceError::MqttNotFound("Tenant not found".into()))
+            }
+            _ => Ok(tenant.clone()),
+        }
+    }
+
+    pub async fn list_tenants(
+        &self,
+        tenant: &TenantContext,
+    ) -> Result<Vec<TenantDTO>, MqttServiceError> {
+        require_global(tenant)?;
+        let tenants = self.repo.get_all().await?;
+        Ok(tenants.into_iter().map(TenantDTO::from).collect())
+    }
+
+    pub async fn create_tenant(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        dto: CreateTenantDTO,
+    ) -> Result<TenantApiKeyDTO, MqttServiceError> {
+        let name = dto.name.trim().to_string();
+        let result = self.create(tenant, dto).await;
+        let change = result
+            .as_ref()
+            .map(|(created, _)| AuditChange::created(created));
+        self.audit
+            .record(actor, AuditAction::TenantCreate, Some(&name), change)
+            .await;
+        result.map(|(_, created)| created)
+    }
+
+    /// Returns the created tenant along with its API key.
+    async fn create(
+        &self,
+        tenant: &TenantContext,
+        dto: CreateTenantDTO,
+    ) -> Result<(TenantEntity, TenantApiKeyDTO), MqttServiceError> {
+        require_global(tenant)?;
+        let name = dto.name.trim().to_string();
+        let topic_prefix = dto
+            .topic_prefix
+            .map(|p| p.trim().to_string())
+            .unwrap_or_else(|| format!("tenants/{}/", name));
+        self.create_tenant_validation(&name, &topic_prefix, &dto.quota)?;
+
+        let api_key = generate_api_key();
+        let created = match self
+            .repo
+            .create_tenant(&name, &topic_prefix, &hash_api_key(&api_key), dto.quota)
+            .await
+        {
+            Ok(created) => created,
+            Err(MqttRepositoryError::AlreadyExists) => {
+                return Err(MqttServiceError::Conflict(
+                    "Tenant name or topic prefix already exists".into(),
+                ));
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.cache_tenant(&created);
+
+        info!(
+            "🏢 Tenant {} created with topic prefix {}",
+            created.name, created.topic_prefix
+        );
+        let api_key = TenantApiKeyDTO {
+            name: created.name.clone(),
+            topic_prefix: created.topic_prefix.clone(),
+            api_key,
+        };
+        Ok((created, api_key))
+    }
+
+    /// Issues a new tenant-scoped API key; the previous one stops working immediately.
+    pub async fn rotate_api_key(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        name: &str,
+    ) -> Result<TenantApiKeyDTO, MqttServiceError> {
+        let result = self.rotate(tenant, name).await;
+        let change = result.as_ref().map(|_| AuditChange::none());
+        self.audit
+            .record(actor, AuditAction::TenantRotateKey, Some(name), change)
+            .await;
+        result
+    }
+
+    async fn rotate(
+        &self,
+        tenant: &TenantContext,
+        name: &str,
+    ) -> Result<TenantApiKeyDTO, MqttServiceError> {
+        require_global(tenant)?;
+        let Some(target) = self.by_name(name).await else {
+            return Err(MqttServiceError::MqttNotFound("Tenant not found".into()));
+        };
+
+        let api_key = generate_api_key();
+        let updated = self
+            .repo
+            .set_api_key_hash(target.id, &hash_api_key(&api_key))
+            .await?;
+        self.cache_tenant(&updated);
+
+        debug!("[Service | Tenant] API key of tenant {} replaced", name);
+        Ok(TenantApiKeyDTO {
+            name: updated.name,
+            topic_prefix: updated.topic_prefix,
+            api_key,
+        })
+    }
+
+    /// Replaces the quota of a tenant; `null` limits are unlimited.
+    pub async fn set_quota(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        name: &str,
+        quota: TenantQuotaDTO,
+    ) -> Result<TenantDTO, MqttServiceError> {
+        let result = self.replace_quota(tenant, name, quota).await;
+        let change = result
+            .as_ref()
+            .map(|(before, after)| AuditChange::updated(before, &after.quota));
+        self.audit
+            .record(actor, AuditAction::TenantSetQuota, Some(name), change)
+            .await;
+        result.map(|(_, updated)| updated)
+    }
+
+    /// Returns the quota before the change and the updated tenant.
+    async fn replace_quota(
+        &self,
+        tenant: &TenantContext,
+        name: &str,
+        quota: TenantQuotaDTO,
+    ) -> Result<(TenantQuotaDTO, TenantDTO), MqttServiceError> {
+        require_global(tenant)?;
+        let errors = quota_errors(&quota);
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+        let Some(target) = self.by_name(name).await else {
+            return Err(MqttServiceError::MqttNotFound("Tenant not found".into()));
+        };
+
+        let updated = self.repo.set_quota(target.id, quota).await?;
+        self.cache_tenant(&updated);
+
+        debug!("[Service | Tenant] Quota of tenant {} replaced", name);
+        Ok((target.quota, TenantDTO::from(updated)))
+    }
+
+    fn create_tenant_validation(
+        &self,
+        name: &str,
+        topic_prefix: &str,
+        quota: &TenantQuotaDTO,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = quota_errors(quota);
+        if name.is_empty()
+            || name.len() > MAX_TENANT_NAME_LENGTH
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        {
+            errors.push(ValidationError {
+                field: "name".to_string(),
+                message: format!(
+                    "name must be 1-{} characters of lowercase letters, digits and '-'",
+                    MAX_TENANT_NAME_LENGTH
+                ),
+            });
+        }
+
+        if topic_prefix.len() < 2
+            || topic_prefix.len() > MAX_TOPIC_PREFIX_LENGTH
+            || !topic_prefix.ends_with('/')
+            || topic_prefix.starts_with('/')
+            || topic_prefix.contains(['+', '#'])
+            || topic_prefix.contains("//")
+            || topic_prefix.starts_with("users/")
+        {
+            errors.push(ValidationError {
+                field: "topic_prefix".to_string(),
+                message: format!(
+                    "topic_prefix must be at most {} characters, end with '/', contain no wildcards or empty levels, and not lie under users/",
+                    MAX_TOPIC_PREFIX_LENGTH
+                ),
+            });
+        } else {
+            let cache = self.cache.read().unwrap_or_else(|e| e.into_inner());
+            let overlapping = cache.by_name.values().find(|t| {
+                !t.topic_prefix.is_empty()
+                    && (t.topic_prefix.starts_with(topic_prefix)
+                        || topic_prefix.starts_with(&t.topic_prefix))
+            });
+            if let Some(other) = overlapping {
+                errors.push(ValidationError {
+                    field: "topic_prefix".to_string(),
+                    message: format!(
+                        "topic_prefix overlaps with the prefix of tenant {}",
+                        other.name
+                    ),
+                });
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+        Ok(true)
+    }
+}
+
+fn context(tenant: &TenantEntity, scoped: bool) -> TenantContext {
+    TenantContext {
+        id: tenant.id,
+        name: tenant.name.clone(),
+        topic_prefix: tenant.topic_prefix.clone(),
+        scoped,
+        quota: TenantQuotaDTO::from(tenant),
+    }
+}
+
+fn quota_errors(quota: &TenantQuotaDTO) -> Vec<ValidationError> {
+    [
+        ("max_users", quota.max_users),
+        ("max_superusers", quota.max_superusers),
+        ("max_acl_rules", quota.max_acl_rules),
+        ("max_auth_per_minute", quota.max_auth_per_minute),
+    ]
+    .into_iter()
+    .filter(|(_, limit)| limit.is_some_and(|limit| limit < 0))
+    .map(|(field, _)| ValidationError {
+        field: field.to_string(),
+        message: format!("{} cannot be negative", field),
+    })
+    .collect()
+}
+
+/// Tenant management needs the global API key.
+fn require_global(tenant: &TenantContext) -> Result<(), MqttServiceError> {
+    if tenant.scoped {
+        return Err(MqttServiceError::Forbidden(
+            "Tenant-scoped API keys cannot manage tenants".into(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use log::debug;
+use std::sync::Arc;
+
+use crate::dtos::audit_dto::{AuditAction, AuditContext};
+use crate::dtos::mqtt_dto::{MqttUserDTO, UpdateMqttDTO};
+use crate::dtos::tenant_dto::TenantContext;
+use crate::entities::mqtt_entity::Model as MqttEntity;
+use crate::repositories::get_mqtt_by_username_repository::GetMqttByUsernameRepository;
+use crate::repositories::get_mqtt_client_ids_repository::GetMqttClientIdsRepository;
+use crate::repositories::get_mqtt_tags_repository::GetMqttTagsRepository;
+use crate::repositories::update_mqtt_repository::{MqttUserChanges, UpdateMqttRepository};
+use crate::services::audit_service::{AuditChange, AuditService};
+use crate::services::quota_service::QuotaService;
+use crate::services::service_error::{MqttServiceError, ValidationError};
+use crate::utils::attributes::{
+    client_id_errors, metadata_error, normalize_client_ids, normalize_tags, tag_errors,
+};
+use crate::utils::credential_policy::CredentialPolicy;
+use crate::utils::encryption::encrypt_password;
+
+pub struct UpdateMqttService {
+    repo_get: Arc<GetMqttByUsernameRepository>,
+    repo_update: Arc<UpdateMqttRepository>,
+    repo_tags: Arc<GetMqttTagsRepository>,
+    repo_client_ids: Arc<GetMqttClientIdsRepository>,
+    quota: Arc<QuotaService>,
+    policy: Arc<CredentialPolicy>,
+    audit: Arc<AuditService>,
+}
+
+/// A user with the attributes stored in their own tables.
+struct UserState {
+    user: MqttEntity,
+    tags: Vec<String>,
+    client_ids: Vec<String>,
+}
+
+impl UpdateMqttService {
+    pub fn new(
+        repo_get: Arc<GetMqttByUsernameRepository>,
+        repo_update: Arc<UpdateMqttRepository>,
+        repo_tags: Arc<GetMqttTagsRepository>,
+        repo_client_ids: Arc<GetMqttClientIdsRepository>,
+        quota: Arc<QuotaService>,
+        policy: Arc<CredentialPolicy>,
+        audit: Arc<AuditService>,
+    ) -> Self {
+        Self {
+            repo_get,
+            repo_update,
+            repo_tags,
+            repo_client_ids,
+            quota,
+            policy,
+            audit,
+        }
+    }
+
+    pub async fn update_mqtt(
+        &self,
+        tenant: &TenantContext,
+        actor: &AuditContext,
+        username: &str,
+        dto: UpdateMqttDTO,
+    ) -> Result<MqttUserDTO, MqttServiceError> {
+        let username = self.policy.normalize_username(username);
+        let result = self.update(tenant, &username, dto).await;
+        let change = result.as_ref().map(|(before, after)| {
+            AuditChange::updated(&before.user, &after.user)
+                .with_field("tags", &before.tags, &after.tags)
+                .with_field("client_ids", &before.client_ids, &after.client_ids)
+        });
+        self.audit
+            .record(actor, AuditAction::UserUpdate, Some(&username), change)
+            .await;
+        result.map(|(_, after)| MqttUserDTO::new(after.user, after.tags))
+    }
+
+    /// Returns the user before and after the update.
+    async fn update(
+        &self,
+        tenant: &TenantContext,
+        username: &str,
+        dto: UpdateMqttDTO,
+    ) -> Result<(UserState, UserState), MqttServiceError> {
+        let mqtt = match self
+            .repo_get
+            .get_mqtt_by_username(tenant.id, username)
+            .await
+        {
+            Ok(u) => u,
+            Err(_) => {
+                debug!("[Service | UpdateMQTT] User MQTT not found: {}", username);
+                return Err(MqttServiceError::MqttNotFound("User MQTT not found".into()));
+            }
+        };
+
+        self.update_mqtt_validation(&mqtt, &dto)?;
+        if dto.is_superuser == Some(true) && !mqtt.is_superuser {
+            self.quota
+                .check_users(tenant, &[(mqtt.username.as_str(), true)], true)
+                .await?;
+        }
+
+        let before = self.state(mqtt).await?;
+        let password = match dto.password {
+            Some(ref password) => {
+                Some(encrypt_password(password).map_err(MqttServiceError::InternalError)?)
+            }
+            None => None,
+        };
+        let changes = MqttUserChanges {
+            password,
+            is_superuser: dto.is_superuser,
+            is_enabled: dto.is_enabled,
+            metadata: dto.metadata,
+            valid_from: dto.valid_from,
+            valid_until: dto.valid_until,
+            tags: dto.tags.as_deref().map(normalize_tags),
+            client_ids: dto.client_ids.as_deref().map(normalize_client_ids),
+        };
+
+        let updated = self
+            .repo_update
+            .update_mqtt(before.user.id, changes)
+            .await?;
+        let after = self.state(updated).await?;
+        debug!(
+            "[Service | UpdateMQTT] User MQTT updated successfully: {}",
+            username
+        );
+        Ok((before, after))
+    }
+
+    async fn state(&self, user: MqttEntity) -> Result<UserState, MqttServiceError> {
+        Ok(UserState {
+            tags: self.repo_tags.get_tags(user.id).await?,
+            client_ids: self.repo_client_ids.get_client_ids(user.id).await?,
+            user,
+        })
+    }
+
+    fn update_mqtt_validation(
+        &self,
+        current: &MqttEntity,
+        dto: &UpdateMqttDTO,
+    ) -> Result<bool, MqttServiceError> {
+        let mut errors = Vec::new();
+        if dto.password.is_none()
+            && dto.is_superuser.is_none()
+            && dto.is_enabled.is_none()
+            && dto.metadata.is_none()
+            && dto.valid_from.is_none()
+            && dto.valid_until.is_none()
+            && dto.tags.is_none()
+            && dto.client_ids.is_none()
+        {
+            errors.push(ValidationError {
+                field: "body".to_string(),
+                message: "at least one field must be provided".to_string(),
+            });
+        }
+
+        match &dto.password {
+            Some(password) if password.trim().is_empty() => errors.push(ValidationError {
+                field: "password".to_string(),
+                message: "password cannot be empty".to_string(),
+            }),
+            Some(password) => {
+                for message in self.policy.password_errors(password) {
+                    errors.push(ValidationError {
+                        field: "password".to_string(),
+                        message,
+                    });
+                }
+            }
+            None => {}
+        }
+
+        if let Some(Some(metadata)) = &dto.metadata
+            && let Some(message) = metadata_error(metadata)
+        {
+            errors.push(ValidationError {
+                field: "metadata".to_string(),
+                message,
+            });
+        }
+
+        for message in tag_errors(dto.tags.as_deref().unwrap_or_default()) {
+            errors.push(ValidationError {
+                field: "tags".to_string(),
+                message,
+            });
+        }
+
+        for message in client_id_errors(dto.client_ids.as_deref().unwrap_or_default()) {
+            errors.push(ValidationError {
+                field: "client_ids".to_string(),
+                message,
+            });
+        }
+
+        let valid_from = dto.valid_from.unwrap_or(current.valid_from);
+        let valid_until = dto.valid_until.unwrap_or(current.valid_until);
+        if let (Some(from), Some(until)) = (valid_from, valid_until)
+            && until <= from
+        {
+            errors.push(ValidationError {
+                field: "valid_until".to_string(),
+                message: "valid_until must be later than valid_from".to_string(),
+            });
+        }
+
+        if !errors.is_empty() {
+            return Err(MqttServiceError::BadRequest(errors));
+        }
+
+        debug!("[Service | UpdateMQTT] User MQTT input validation passed.");
+        Ok(true)
+    }
+}
@@ -0,0 +1,65 @@
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+const API_KEY_BYTES: usize = 32;
+
+/// A new random API key; only its hash is stored.
+pub fn generate_api_key() -> String {
+    let mut key = [0u8; API_KEY_BYTES];
+    rand::thread_rng().fill_bytes(&mut key);
+    hex::encode(key)
+}
+
+/// `hex(sha256(key))`, the form API keys are stored and looked up in.
+pub fn hash_api_key(key: &str) -> String {
+    hex::encode(Sha256::digest(key.as_bytes()))
+}
+
+/// `sha256(key)`, compared in constant time against the keys configured outside the database.
+pub fn digest_api_key(key: &str) -> [u8; 32] {
+    Sha256::digest(key.as_bytes()).into()
+}
+
+/// Index of the digest equal to `sha256(key)`. Every digest is compared in constant time, so the
+/// response time does not reveal how much of a key matched.
+pub fn find_api_key(key: &str, digests: &[[u8; 32]]) -> Option<usize> {
+    let digest = digest_api_key(key);
+    let mut found = None;
+    for (i, candidate) in digests.iter().enumerate() {
+        if bool::from(candidate.ct_eq(&digest)) && found.is_none() {
+            found = Some(i);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_distinct_hex_keys() {
+        let key = generate_api_key();
+        assert_eq!(key.len(), API_KEY_BYTES * 2);
+        assert!(key.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(key, generate_api_key());
+    }
+
+    #[test]
+    fn finds_the_first_matching_key() {
+        let digests = [
+            digest_api_key("primary"),
+            digest_api_key("secondary"),
+            digest_api_key("primary"),
+        ];
+        assert_eq!(find_api_key("primary", &digests), Some(0));
+        assert_eq!(find_api_key("secondary", &digests), Some(1));
+        assert_eq!(find_api_key("primar", &digests), None);
+        assert_eq!(find_api_key("", &[]), None);
+        assert_eq!(
+            hash_api_key("primary"),
+            hex::encode(digest_api_key("primary"))
+        );
+    }
+}
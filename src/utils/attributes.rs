@@ -0,0 +1,253 @@
+use serde_json::Value;
+
+pub const MAX_METADATA_BYTES: usize = 8 * 1024;
+pub const MAX_TAGS: usize = 32;
+pub const MAX_TAG_LENGTH: usize = 64;
+pub const MAX_CLIENT_IDS: usize = 16;
+pub const MAX_CLIENT_ID_LENGTH: usize = 128;
+
+/// Validation error of a user's attribute object, if any.
+pub fn metadata_error(metadata: &Value) -> Option<String> {
+    if !metadata.is_object() {
+        return Some("metadata must be a JSON object".to_string());
+    }
+    if metadata.to_string().len() > MAX_METADATA_BYTES {
+        return Some(format!(
+            "metadata cannot exceed {} bytes",
+            MAX_METADATA_BYTES
+        ));
+    }
+    None
+}
+
+/// Attribute keys usable in list filters and ACL templates.
+pub fn is_attribute_key(key: &str) -> bool {
+    !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-'))
+}
+
+/// Validation errors of a tag set; tags are compared after trimming.
+pub fn tag_errors(tags: &[String]) -> Vec<String> {
+    let mut errors = Vec::new();
+    if normalize_tags(tags).len() > MAX_TAGS {
+        errors.push(format!("a user cannot have more than {} tags", MAX_TAGS));
+    }
+    for tag in tags.iter().map(|t| t.trim()) {
+        if tag.is_empty() || tag.len() > MAX_TAG_LENGTH {
+            errors.push(format!(
+                "tag `{}` must be between 1 and {} characters",
+                tag, MAX_TAG_LENGTH
+            ));
+        } else if !tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'))
+        {
+            errors.push(format!(
+                "tag `{}` may only contain letters, digits and `_-.:`",
+                tag
+            ));
+        }
+    }
+    errors
+}
+
+/// Trimmed, sorted and deduplicated tags.
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags.iter().map(|t| t.trim().to_string()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// Validation errors of a set of client ID bindings; client IDs are compared after trimming.
+pub fn client_id_errors(client_ids: &[String]) -> Vec<String> {
+    let mut errors = Vec::new();
+    if normalize_client_ids(client_ids).len() > MAX_CLIENT_IDS {
+        errors.push(format!(
+            "a user cannot be bound to more than {} client IDs",
+            MAX_CLIENT_IDS
+        ));
+    }
+    for client_id in client_ids.iter().map(|c| c.trim()) {
+        if client_id.is_empty() || client_id.chars().count() > MAX_CLIENT_ID_LENGTH {
+            errors.push(format!(
+                "client ID `{}` must be between 1 and {} characters",
+                client_id, MAX_CLIENT_ID_LENGTH
+            ));
+        } else if client_id
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control())
+        {
+            errors.push(format!(
+                "client ID `{}` cannot contain whitespace or control characters",
+                client_id
+            ));
+        }
+    }
+    errors
+}
+
+/// Trimmed, sorted and deduplicated client IDs.
+pub fn normalize_client_ids(client_ids: &[String]) -> Vec<String> {
+    let mut client_ids: Vec<String> = client_ids.iter().map(|c| c.trim().to_string()).collect();
+    client_ids.sort();
+    client_ids.dedup();
+    client_ids
+}
+
+/// Scalar attribute value as text; objects, arrays and `null` have none.
+pub fn attribute_value(metadata: Option<&Value>, key: &str) -> Option<String> {
+    match metadata?.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// Renders an ACL topic template, substituting `${username}` and `${attr.<key>}`.
+///
+/// Returns `None` when a referenced attribute is missing, so the rule cannot match.
+pub fn render_topic_template(
+    template: &str,
+    username: &str,
+    metadata: Option<&Value>,
+) -> Option<String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let placeholder = &rest[start + 2..start + end];
+        match placeholder.strip_prefix("attr.") {
+            _ if placeholder == "username" => rendered.push_str(username),
+            Some(key) => {
+                let value = attribute_value(metadata, key)?;
+                // A value spanning levels or holding wildcards would widen the rule.
+                if value.is_empty() || value.contains(['/', '+', '#']) {
+                    return None;
+                }
+                rendered.push_str(&value);
+            }
+            None => rendered.push_str(&rest[start..start + end + 1]),
+        }
+        rest = &rest[start + end + 1..];
+    }
+    rendered.push_str(rest);
+    Some(rendered)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn accepts_client_ids() {
+        assert!(client_id_errors(&strings(&["sensor-01", " gw/α:1 "])).is_empty());
+        assert_eq!(
+            normalize_client_ids(&strings(&["b", " a ", "b"])),
+            strings(&["a", "b"])
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_client_ids() {
+        assert_eq!(client_id_errors(&strings(&[" "])).len(), 1);
+        assert_eq!(client_id_errors(&strings(&["two words"])).len(), 1);
+        assert_eq!(client_id_errors(&strings(&["a\u{0}"])).len(), 1);
+        let long = "x".repeat(MAX_CLIENT_ID_LENGTH + 1);
+        assert_eq!(client_id_errors(&[long]).len(), 1);
+        let many: Vec<String> = (0..=MAX_CLIENT_IDS).map(|i| i.to_string()).collect();
+        assert_eq!(client_id_errors(&many).len(), 1);
+    }
+
+    #[test]
+    fn renders_username_and_attributes() {
+        let metadata = json!({ "site": "jkt-01", "line": 4, "active": true });
+        assert_eq!(
+            render_topic_template(
+                "sites/${attr.site}/${username}/line-${attr.line}",
+                "sensor-01",
+                Some(&metadata)
+            ),
+            Some("sites/jkt-01/sensor-01/line-4".to_string())
+        );
+        assert_eq!(
+            render_topic_template("a/${other}/b", "u", None),
+            Some("a/${other}/b".to_string())
+        );
+        assert_eq!(
+            render_topic_template("a/${attr.site", "u", Some(&metadata)),
+            Some("a/${attr.site".to_string())
+        );
+    }
+
+    #[test]
+    fn refuses_attributes_that_would_widen_the_topic() {
+        for value in ["a/b", "+", "#", "jkt/#", ""] {
+            let metadata = json!({ "site": value });
+            assert_eq!(
+                render_topic_template("sites/${attr.site}/data", "u", Some(&metadata)),
+                None,
+                "{value}"
+            );
+        }
+        let metadata = json!({ "site": { "id": 1 } });
+        assert_eq!(
+            render_topic_template("sites/${attr.site}", "u", Some(&metadata)),
+            None
+        );
+        assert_eq!(render_topic_template("sites/${attr.site}", "u", None), None);
+    }
+
+    #[test]
+    fn reads_scalar_attribute_values() {
+        let metadata = json!({
+            "site": "jkt", "line": 4, "ratio": 0.5, "active": false,
+            "none": null, "list": [1], "nested": { "a": 1 }
+        });
+        assert_eq!(attribute_value(Some(&metadata), "site"), Some("jkt".into()));
+        assert_eq!(attribute_value(Some(&metadata), "line"), Some("4".into()));
+        assert_eq!(
+            attribute_value(Some(&metadata), "ratio"),
+            Some("0.5".into())
+        );
+        assert_eq!(
+            attribute_value(Some(&metadata), "active"),
+            Some("false".into())
+        );
+        for key in ["none", "list", "nested", "missing"] {
+            assert_eq!(attribute_value(Some(&metadata), key), None, "{key}");
+        }
+        assert_eq!(attribute_value(None, "site"), None);
+        assert_eq!(attribute_value(Some(&json!("site")), "site"), None);
+    }
+
+    #[test]
+    fn accepts_tags() {
+        assert!(tag_errors(&strings(&["factory", " a_b-c.d:e "])).is_empty());
+        let many: Vec<String> = (0..MAX_TAGS).map(|i| i.to_string()).collect();
+        assert!(tag_errors(&many).is_empty());
+        let duplicated: Vec<String> = (0..=MAX_TAGS).map(|_| "same".to_string()).collect();
+        assert!(tag_errors(&duplicated).is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_tags() {
+        assert_eq!(tag_errors(&strings(&[" "])).len(), 1);
+        assert_eq!(tag_errors(&strings(&["two words"])).len(), 1);
+        assert_eq!(tag_errors(&strings(&["a/b"])).len(), 1);
+        assert_eq!(tag_errors(&["x".repeat(MAX_TAG_LENGTH + 1)]).len(), 1);
+        let many: Vec<String> = (0..=MAX_TAGS).map(|i| i.to_string()).collect();
+        assert_eq!(tag_errors(&many).len(), 1);
+    }
+}
@@ -0,0 +1,97 @@
+use ipnet::IpNet;
+use std::net::IpAddr;
+
+/// Client address of a request, resolved by `IpAllowListMiddleware` and stored in the request
+/// extensions.
+#[derive(Clone, Copy, Debug)]
+pub struct ClientIp(pub IpAddr);
+
+/// Parses comma-separated networks in CIDR notation; a bare address is a single host.
+pub fn parse_networks(value: &str) -> Result<Vec<IpNet>, String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            entry
+                .parse::<IpNet>()
+                .or_else(|_| entry.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| format!("'{}' is not an IP address or CIDR network", entry))
+        })
+        .collect()
+}
+
+pub fn contains(networks: &[IpNet], ip: IpAddr) -> bool {
+    networks.iter().any(|network| network.contains(&ip))
+}
+
+/// The client behind `peer`. `X-Forwarded-For` is only read when `peer` is a trusted proxy,
+/// and from the right, skipping trusted proxies, so a client cannot prepend a forged address.
+pub fn resolve_client_ip(peer: IpAddr, forwarded_for: Option<&str>, trusted: &[IpNet]) -> IpAddr {
+    if !contains(trusted, peer) {
+        return peer;
+    }
+    let Some(forwarded_for) = forwarded_for else {
+        return peer;
+    };
+
+    let mut client = peer;
+    for hop in forwarded_for.rsplit(',') {
+        let Ok(hop) = hop.trim().parse::<IpAddr>() else {
+            break;
+        };
+        client = hop;
+        if !contains(trusted, hop) {
+            break;
+        }
+    }
+    client
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> IpAddr {
+        value.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_networks_and_single_addresses() {
+        let networks = parse_networks(" 10.0.0.0/8, 192.168.1.20,,2001:db8::/32 ").unwrap();
+        assert_eq!(networks.len(), 3);
+        assert!(contains(&networks, ip("10.1.2.3")));
+        assert!(contains(&networks, ip("192.168.1.20")));
+        assert!(!contains(&networks, ip("192.168.1.21")));
+        assert!(contains(&networks, ip("2001:db8::1")));
+        assert!(parse_networks("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_networks() {
+        assert!(parse_networks("10.0.0.0/33").is_err());
+        assert!(parse_networks("10.0.0.1,example.com").is_err());
+    }
+
+    #[test]
+    fn ignores_forwarded_for_from_untrusted_peers() {
+        let trusted = parse_networks("10.0.0.0/8").unwrap();
+        let client = resolve_client_ip(ip("203.0.113.7"), Some("198.51.100.1"), &trusted);
+        assert_eq!(client, ip("203.0.113.7"));
+    }
+
+    #[test]
+    fn reads_forwarded_for_from_the_right() {
+        let trusted = parse_networks("10.0.0.0/8").unwrap();
+        let peer = ip("10.0.0.1");
+        assert_eq!(resolve_client_ip(peer, None, &trusted), peer);
+        // The forged left-most entry is never reached.
+        let client = resolve_client_ip(peer, Some("1.2.3.4, 198.51.100.1, 10.0.0.2"), &trusted);
+        assert_eq!(client, ip("198.51.100.1"));
+        // A malformed hop stops the walk at the last address that could be verified.
+        let client = resolve_client_ip(peer, Some("198.51.100.1, garbage, 10.0.0.2"), &trusted);
+        assert_eq!(client, ip("10.0.0.2"));
+        let client = resolve_client_ip(peer, Some("10.0.0.3, 10.0.0.2"), &trusted);
+        assert_eq!(client, ip("10.0.0.3"));
+    }
+}
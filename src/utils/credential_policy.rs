@@ -0,0 +1,310 @@
+use log::info;
+use std::collections::HashSet;
+
+/// Characters that break the `users/{username}/` topic scheme; never allowed in usernames.
+const FORBIDDEN_USERNAME_CHARS: &[char] = &['/', '+', '#'];
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum UsernameCase {
+    Preserve,
+    Lower,
+}
+
+impl UsernameCase {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "preserve" => Ok(UsernameCase::Preserve),
+            "lower" | "lowercase" => Ok(UsernameCase::Lower),
+            other => Err(format!(
+                "unknown USERNAME_CASE {:?}, expected preserve or lower",
+                other
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CharacterClass {
+    Lower,
+    Upper,
+    Digit,
+    Symbol,
+}
+
+impl CharacterClass {
+    fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "lower" | "lowercase" => Ok(CharacterClass::Lower),
+            "upper" | "uppercase" => Ok(CharacterClass::Upper),
+            "digit" | "digits" => Ok(CharacterClass::Digit),
+            "symbol" | "symbols" => Ok(CharacterClass::Symbol),
+            other => Err(format!(
+                "unknown PASSWORD_REQUIRED_CLASSES entry {:?}, expected lower, upper, digit or symbol",
+                other
+            )),
+        }
+    }
+
+    fn as_str(&self) -> &str {
+        match self {
+            CharacterClass::Lower => "a lowercase letter",
+            CharacterClass::Upper => "an uppercase letter",
+            CharacterClass::Digit => "a digit",
+            CharacterClass::Symbol => "a symbol",
+        }
+    }
+
+    fn matches(&self, c: char) -> bool {
+        match self {
+            CharacterClass::Lower => c.is_lowercase(),
+            CharacterClass::Upper => c.is_uppercase(),
+            CharacterClass::Digit => c.is_ascii_digit(),
+            CharacterClass::Symbol => !c.is_alphanumeric() && !c.is_whitespace(),
+        }
+    }
+}
+
+/// Rules applied to usernames and user-supplied passwords on every write path.
+#[derive(Debug)]
+pub struct CredentialPolicy {
+    pub username_min_length: usize,
+    pub username_max_length: usize,
+    /// Symbols allowed in usernames besides ASCII letters and digits.
+    pub username_symbols: String,
+    /// Reserved usernames, stored lowercase and compared case-insensitively.
+    pub reserved_usernames: HashSet<String>,
+    pub username_case: UsernameCase,
+    pub password_min_length: usize,
+    pub password_classes: Vec<CharacterClass>,
+    pub breached_passwords: HashSet<String>,
+}
+
+impl CredentialPolicy {
+    /// Reads the `USERNAME_*`, `PASSWORD_MIN_LENGTH`, `PASSWORD_REQUIRED_CLASSES` and
+    /// `BREACHED_PASSWORDS_FILE` variables. Panics on a malformed value or an unreadable file.
+    pub fn from_env() -> Self {
+        let number = |name: &str, default: usize| match std::env::var(name) {
+            Ok(value) if !value.trim().is_empty() => {
+                value.trim().parse::<usize>().unwrap_or_else(|_| {
+                    panic!(
+                        "❌ {} must be a non-negative integer, got {:?}",
+                        name, value
+                    )
+                })
+            }
+            _ => default,
+        };
+        let list = |name: &str| {
+            std::env::var(name)
+                .unwrap_or_default()
+                .split(',')
+                .map(|item| item.trim().to_string())
+                .filter(|item| !item.is_empty())
+                .collect::<Vec<_>>()
+        };
+
+        let breached_passwords = match std::env::var("BREACHED_PASSWORDS_FILE") {
+            Ok(path) if !path.is_empty() => {
+                let contents = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+                    panic!("❌ Could not read BREACHED_PASSWORDS_FILE {}: {}", path, e)
+                });
+                let passwords: HashSet<String> = contents
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                info!(
+                    "🔐 Loaded {} breached passwords from {}",
+                    passwords.len(),
+                    path
+                );
+                passwords
+            }
+            _ => HashSet::new(),
+        };
+
+        let username_min_length = number("USERNAME_MIN_LENGTH", 1);
+        let username_max_length = number("USERNAME_MAX_LENGTH", 64);
+        if username_min_length > username_max_length {
+            panic!(
+                "❌ USERNAME_MIN_LENGTH ({}) cannot exceed USERNAME_MAX_LENGTH ({})",
+                username_min_length, username_max_length
+            );
+        }
+
+        CredentialPolicy {
+            username_min_length,
+            username_max_length,
+            username_symbols: std::env::var("USERNAME_ALLOWED_SYMBOLS")
+                .unwrap_or_else(|_| "-_.@:".to_string())
+                .chars()
+                .filter(|c| !FORBIDDEN_USERNAME_CHARS.contains(c) && !c.is_whitespace())
+                .collect(),
+            reserved_usernames: list("USERNAME_RESERVED")
+                .into_iter()
+                .map(|name| name.to_lowercase())
+                .collect(),
+            username_case: UsernameCase::parse(&std::env::var("USERNAME_CASE").unwrap_or_default())
+                .unwrap_or_else(|e| panic!("❌ {}", e)),
+            password_min_length: number("PASSWORD_MIN_LENGTH", 12),
+            password_classes: list("PASSWORD_REQUIRED_CLASSES")
+                .iter()
+                .map(|class| CharacterClass::parse(class))
+                .collect::<Result<_, _>>()
+                .unwrap_or_else(|e| panic!("❌ {}", e)),
+            breached_passwords,
+        }
+    }
+
+    /// The username as it is stored and looked up.
+    pub fn normalize_username(&self, username: &str) -> String {
+        match self.username_case {
+            UsernameCase::Preserve => username.to_string(),
+            UsernameCase::Lower => username.to_lowercase(),
+        }
+    }
+
+    /// Violations of the username rules, empty when the username is acceptable.
+    pub fn username_errors(&self, username: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        let length = username.chars().count();
+        if length < self.username_min_length || length > self.username_max_length {
+            errors.push(format!(
+                "username must be between {} and {} characters",
+                self.username_min_length, self.username_max_length
+            ));
+        }
+
+        if username
+            .chars()
+            .any(|c| FORBIDDEN_USERNAME_CHARS.contains(&c) || c.is_whitespace())
+        {
+            errors.push("username cannot contain '/', '+', '#' or whitespace".to_string());
+        } else if let Some(c) = username
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric() && !self.username_symbols.contains(*c))
+        {
+            errors.push(format!(
+                "username contains '{}'; only letters, digits and '{}' are allowed",
+                c, self.username_symbols
+            ));
+        }
+
+        if self.reserved_usernames.contains(&username.to_lowercase()) {
+            errors.push("username is reserved".to_string());
+        }
+
+        errors
+    }
+
+    /// Violations of the password rules, empty when the password is acceptable.
+    pub fn password_errors(&self, password: &str) -> Vec<String> {
+        let mut errors = Vec::new();
+        if password.chars().count() < self.password_min_length {
+            errors.push(format!(
+                "password must be at least {} characters",
+                self.password_min_length
+            ));
+        }
+
+        for class in &self.password_classes {
+            if !password.chars().any(|c| class.matches(c)) {
+                errors.push(format!("password must contain {}", class.as_str()));
+            }
+        }
+
+        if self.breached_passwords.contains(password) {
+            errors.push("password appears in a list of breached passwords".to_string());
+        }
+
+        errors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> CredentialPolicy {
+        CredentialPolicy {
+            username_min_length: 3,
+            username_max_length: 8,
+            username_symbols: "-_".to_string(),
+            reserved_usernames: HashSet::from(["admin".to_string()]),
+            username_case: UsernameCase::Preserve,
+            password_min_length: 10,
+            password_classes: vec![CharacterClass::Upper, CharacterClass::Digit],
+            breached_passwords: HashSet::from(["Password123".to_string()]),
+        }
+    }
+
+    #[test]
+    fn parses_username_case() {
+        assert_eq!(UsernameCase::parse(""), Ok(UsernameCase::Preserve));
+        assert_eq!(UsernameCase::parse("Preserve"), Ok(UsernameCase::Preserve));
+        assert_eq!(UsernameCase::parse(" lower "), Ok(UsernameCase::Lower));
+        assert!(UsernameCase::parse("lowre").is_err());
+    }
+
+    #[test]
+    fn parses_character_classes() {
+        assert_eq!(CharacterClass::parse("Digits"), Ok(CharacterClass::Digit));
+        assert_eq!(CharacterClass::parse("symbol"), Ok(CharacterClass::Symbol));
+        assert!(CharacterClass::parse("uper").is_err());
+    }
+
+    #[test]
+    fn normalizes_usernames_by_case() {
+        let mut policy = policy();
+        assert_eq!(policy.normalize_username("Sensor-01"), "Sensor-01");
+        policy.username_case = UsernameCase::Lower;
+        assert_eq!(policy.normalize_username("Sensor-01"), "sensor-01");
+    }
+
+    #[test]
+    fn accepts_valid_usernames() {
+        assert!(policy().username_errors("sensor-1").is_empty());
+        assert!(policy().username_errors("abc").is_empty());
+    }
+
+    #[test]
+    fn reports_username_violations() {
+        let policy = policy();
+        assert_eq!(policy.username_errors("ab").len(), 1);
+        assert_eq!(policy.username_errors("sensor-001").len(), 1);
+        for username in ["a/b", "a+b", "a#b", "a b"] {
+            assert_eq!(
+                policy.username_errors(username),
+                vec!["username cannot contain '/', '+', '#' or whitespace"],
+                "{username}"
+            );
+        }
+        assert!(policy.username_errors("a.b")[0].contains("'.'"));
+        assert_eq!(
+            policy.username_errors("ADMIN"),
+            vec!["username is reserved"]
+        );
+    }
+
+    #[test]
+    fn accepts_valid_passwords() {
+        assert!(policy().password_errors("Correct42horse").is_empty());
+    }
+
+    #[test]
+    fn reports_password_violations() {
+        let policy = policy();
+        assert_eq!(
+            policy.password_errors("short"),
+            vec![
+                "password must be at least 10 characters",
+                "password must contain an uppercase letter",
+                "password must contain a digit",
+            ]
+        );
+        assert_eq!(
+            policy.password_errors("Password123"),
+            vec!["password appears in a list of breached passwords"]
+        );
+    }
+}
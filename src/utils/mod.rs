@@ -1,2 +1,11 @@
+pub mod api_key;
+pub mod attributes;
+pub mod client_ip;
+pub mod credential_policy;
 pub mod encryption;
 pub mod jwt_sign;
+pub mod name_pattern;
+pub mod password_generator;
+pub mod password_hash;
+pub mod request_signature;
+pub mod topic;
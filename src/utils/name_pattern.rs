@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+
+/// Widest zero padding of a sequence; `u64::MAX` has 20 digits.
+const MAX_SEQUENCE_WIDTH: usize = 20;
+
+/// A parsed provisioning name pattern: `count` names from `first`, between `prefix` and
+/// `suffix`.
+pub struct NamePattern {
+    prefix: String,
+    suffix: String,
+    first: u64,
+    count: u64,
+    width: usize,
+}
+
+impl NamePattern {
+    /// Parses a provisioning name pattern without expanding it.
+    ///
+    /// The pattern holds exactly one sequence placeholder: `{seq}`, `{seq:N}` (zero-padded to
+    /// `N` digits, counting from `start`) or an explicit range such as `{0001..5000}`, whose
+    /// width follows the first bound. Any other `{name}` is replaced from `variables`.
+    pub fn parse(
+        pattern: &str,
+        variables: &BTreeMap<String, String>,
+        start: Option<u64>,
+        count: Option<u64>,
+    ) -> Result<Self, String> {
+        let mut prefix = String::new();
+        let mut suffix = String::new();
+        let mut sequence: Option<(u64, u64, usize)> = None;
+
+        let mut rest = pattern;
+        while let Some(open) = rest.find('{') {
+            let close = rest[open..]
+                .find('}')
+                .map(|i| open + i)
+                .ok_or_else(|| "pattern has an unclosed '{'".to_string())?;
+            if rest[..open].contains('}') {
+                return Err("pattern has an unmatched '}'".to_string());
+            }
+            let token = &rest[open + 1..close];
+            let target = if sequence.is_some() {
+                &mut suffix
+            } else {
+                &mut prefix
+            };
+            target.push_str(&rest[..open]);
+
+            if let Some(parsed) = parse_sequence(token, start, count)? {
+                if sequence.is_some() {
+                    return Err("pattern can only contain one sequence placeholder".to_string());
+                }
+                sequence = Some(parsed);
+            } else {
+                let value = variables
+                    .get(token)
+                    .ok_or_else(|| format!("pattern variable '{}' is not defined", token))?;
+                target.push_str(value);
+            }
+            rest = &rest[close + 1..];
+        }
+        if rest.contains('}') {
+            return Err("pattern has an unmatched '}'".to_string());
+        }
+        if sequence.is_some() {
+            suffix.push_str(rest);
+        } else {
+            prefix.push_str(rest);
+        }
+
+        let (first, count, width) = sequence.ok_or_else(|| {
+            "pattern must contain a sequence placeholder such as {seq} or {0001..0100}".to_string()
+        })?;
+        if width > MAX_SEQUENCE_WIDTH {
+            return Err(format!(
+                "sequence width cannot exceed {} digits",
+                MAX_SEQUENCE_WIDTH
+            ));
+        }
+        if count > 0 && first.checked_add(count - 1).is_none() {
+            return Err("sequence runs past the largest supported number".to_string());
+        }
+        Ok(Self {
+            prefix,
+            suffix,
+            first,
+            count,
+            width,
+        })
+    }
+
+    /// Number of names the pattern expands to, known before expanding it.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn expand(&self) -> Vec<String> {
+        (0..self.count)
+            .map(|i| {
+                format!(
+                    "{}{:0width$}{}",
+                    self.prefix,
+                    self.first + i,
+                    self.suffix,
+                    width = self.width
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns `(first, count, width)` for a sequence token, or `None` for a variable name.
+fn parse_sequence(
+    token: &str,
+    start: Option<u64>,
+    count: Option<u64>,
+) -> Result<Option<(u64, u64, usize)>, String> {
+    if let Some((from, to)) = token.split_once("..") {
+        let first: u64 = from
+            .parse()
+            .map_err(|_| format!("invalid range start '{}'", from))?;
+        let last: u64 = to
+            .parse()
+            .map_err(|_| format!("invalid range end '{}'", to))?;
+        if last < first {
+            return Err("range end must not be lower than its start".to_string());
+        }
+        let range_count = (last - first)
+            .checked_add(1)
+            .ok_or_else(|| "range is too large".to_string())?;
+        if count.is_some_and(|c| c != range_count) {
+            return Err(format!(
+                "count {} does not match the {} names in the range",
+                count.unwrap_or_default(),
+                range_count
+            ));
+        }
+        return Ok(Some((first, range_count, from.len())));
+    }
+
+    let width = match token.strip_prefix("seq") {
+        Some("") => 0,
+        Some(spec) => match spec.strip_prefix(':') {
+            Some(width) => width
+                .parse()
+                .map_err(|_| format!("invalid sequence width '{}'", width))?,
+            None => return Ok(None),
+        },
+        None => return Ok(None),
+    };
+    let count = count.ok_or_else(|| "count is required with a {seq} placeholder".to_string())?;
+    Ok(Some((start.unwrap_or(1), count, width)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(pattern: &str, start: Option<u64>, count: Option<u64>) -> Result<NamePattern, String> {
+        let variables = BTreeMap::from([("site".to_string(), "jkt".to_string())]);
+        NamePattern::parse(pattern, &variables, start, count)
+    }
+
+    #[test]
+    fn expands_ranges_and_sequences() {
+        let pattern = parse("sensor-{site}-{0008..0011}", None, None).unwrap();
+        assert_eq!(pattern.count(), 4);
+        assert_eq!(
+            pattern.expand(),
+            vec![
+                "sensor-jkt-0008",
+                "sensor-jkt-0009",
+                "sensor-jkt-0010",
+                "sensor-jkt-0011"
+            ]
+        );
+
+        let pattern = parse("{seq:3}-{site}", Some(99), Some(2)).unwrap();
+        assert_eq!(pattern.expand(), vec!["099-jkt", "100-jkt"]);
+        assert_eq!(
+            parse("d{seq}", None, Some(2)).unwrap().expand(),
+            vec!["d1", "d2"]
+        );
+    }
+
+    #[test]
+    fn counts_huge_sequences_without_expanding_them() {
+        let pattern = parse("d{seq}", None, Some(1_000_000_000_000)).unwrap();
+        assert_eq!(pattern.count(), 1_000_000_000_000);
+        let pattern = parse("d{1..18446744073709551615}", None, None).unwrap();
+        assert_eq!(pattern.count(), u64::MAX);
+    }
+
+    #[test]
+    fn rejects_sequences_that_overflow() {
+        assert!(parse("d{0..18446744073709551615}", None, None).is_err());
+        assert!(parse("d{seq}", Some(u64::MAX), Some(2)).is_err());
+        assert!(parse("d{seq}", Some(2), Some(u64::MAX)).is_err());
+        assert!(parse("d{seq}", Some(u64::MAX), Some(1)).is_ok());
+        assert!(parse("d{seq:21}", None, Some(1)).is_err());
+        assert!(parse("d{000000000000000000001..2}", None, None).is_err());
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!(parse("sensor", None, Some(1)).is_err());
+        assert!(parse("d{seq}{seq}", None, Some(1)).is_err());
+        assert!(parse("d{seq}", None, None).is_err());
+        assert!(parse("d{5..1}", None, None).is_err());
+        assert!(parse("d{1..5}", None, Some(4)).is_err());
+        assert!(parse("d{a..5}", None, None).is_err());
+        assert!(parse("d{seq:x}", None, Some(1)).is_err());
+        assert!(parse("d{seq", None, Some(1)).is_err());
+        assert!(parse("d}{seq}", None, Some(1)).is_err());
+        assert!(parse("{region}-{seq}", None, Some(1)).is_err());
+    }
+}
@@ -0,0 +1,120 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+pub const MIN_PASSWORD_LENGTH: usize = 12;
+pub const MAX_PASSWORD_LENGTH: usize = 256;
+
+const ALPHANUMERIC: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+const SYMBOLS: &[u8] = b"!#%&()*+,-.:;<=>?@[]^_{}~";
+const HEX: &[u8] = b"0123456789abcdef";
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Default, Debug, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PasswordAlphabet {
+    #[default]
+    Alphanumeric,
+    AlphanumericSymbols,
+    Hex,
+}
+
+impl PasswordAlphabet {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "alphanumeric" => Ok(PasswordAlphabet::Alphanumeric),
+            "alphanumeric_symbols" | "symbols" => Ok(PasswordAlphabet::AlphanumericSymbols),
+            "hex" => Ok(PasswordAlphabet::Hex),
+            other => Err(format!(
+                "unknown PASSWORD_GENERATOR_ALPHABET {:?}, expected alphanumeric, alphanumeric_symbols or hex",
+                other
+            )),
+        }
+    }
+
+    fn charset(&self) -> Vec<u8> {
+        match self {
+            PasswordAlphabet::Alphanumeric => ALPHANUMERIC.to_vec(),
+            PasswordAlphabet::AlphanumericSymbols => [ALPHANUMERIC, SYMBOLS].concat(),
+            PasswordAlphabet::Hex => HEX.to_vec(),
+        }
+    }
+}
+
+/// Defaults applied when a request asks for a generated password without overriding them.
+#[derive(Clone, Copy, Debug)]
+pub struct PasswordGeneratorConfig {
+    pub length: usize,
+    pub alphabet: PasswordAlphabet,
+}
+
+impl PasswordGeneratorConfig {
+    pub fn generate(&self, length: Option<usize>, alphabet: Option<PasswordAlphabet>) -> String {
+        generate_password(
+            length.unwrap_or(self.length),
+            alphabet.unwrap_or(self.alphabet),
+        )
+    }
+}
+
+/// Generates a random password by sampling `alphabet` uniformly with the thread-local CSPRNG.
+pub fn generate_password(length: usize, alphabet: PasswordAlphabet) -> String {
+    let charset = alphabet.charset();
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| charset[rng.gen_range(0..charset.len())] as char)
+        .collect()
+}
+
+/// Validation message for a requested length, or `None` when it is acceptable.
+pub fn password_length_error(length: usize) -> Option<String> {
+    if (MIN_PASSWORD_LENGTH..=MAX_PASSWORD_LENGTH).contains(&length) {
+        return None;
+    }
+    Some(format!(
+        "password_length must be between {} and {}",
+        MIN_PASSWORD_LENGTH, MAX_PASSWORD_LENGTH
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_alphabets() {
+        assert_eq!(
+            PasswordAlphabet::parse(""),
+            Ok(PasswordAlphabet::Alphanumeric)
+        );
+        assert_eq!(PasswordAlphabet::parse("HEX"), Ok(PasswordAlphabet::Hex));
+        assert_eq!(
+            PasswordAlphabet::parse("symbols"),
+            Ok(PasswordAlphabet::AlphanumericSymbols)
+        );
+        assert!(PasswordAlphabet::parse("base64").is_err());
+    }
+
+    #[test]
+    fn generates_passwords_of_the_requested_length_and_alphabet() {
+        for alphabet in [
+            PasswordAlphabet::Alphanumeric,
+            PasswordAlphabet::AlphanumericSymbols,
+            PasswordAlphabet::Hex,
+        ] {
+            let charset = alphabet.charset();
+            for length in [MIN_PASSWORD_LENGTH, 32, MAX_PASSWORD_LENGTH] {
+                let password = generate_password(length, alphabet);
+                assert_eq!(password.len(), length);
+                assert!(password.bytes().all(|b| charset.contains(&b)), "{password}");
+            }
+        }
+    }
+
+    #[test]
+    fn checks_password_lengths() {
+        assert_eq!(password_length_error(MIN_PASSWORD_LENGTH), None);
+        assert_eq!(password_length_error(MAX_PASSWORD_LENGTH), None);
+        assert!(password_length_error(MIN_PASSWORD_LENGTH - 1).is_some());
+        assert!(password_length_error(MAX_PASSWORD_LENGTH + 1).is_some());
+        assert!(password_length_error(0).is_some());
+    }
+}
@@ -0,0 +1,198 @@
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use rand::RngCore;
+use sha2::{Digest, Sha256, Sha512};
+use subtle::ConstantTimeEq;
+
+use crate::utils::encryption::decrypt_password;
+
+const SALT_BYTES: usize = 16;
+
+/// Length of a SHA-512 digest, the only hash length a `$6$` entry can have.
+const SHA512_HASH_BYTES: usize = 64;
+/// Shortest PBKDF2 output accepted; Mosquitto writes 64 bytes.
+const MIN_PBKDF2_HASH_BYTES: usize = 16;
+/// Highest PBKDF2 iteration count accepted, so one imported row cannot stall logins.
+/// Mosquitto uses 101 by default.
+const MAX_PBKDF2_ITERATIONS: u32 = 1_000_000;
+
+/// Random hex-encoded salt for exported password hashes.
+pub fn generate_salt() -> String {
+    let mut salt = [0u8; SALT_BYTES];
+    rand::thread_rng().fill_bytes(&mut salt);
+    hex::encode(salt)
+}
+
+/// `hex(sha256(password || salt))`, EMQX's `sha256` algorithm with `salt_position = suffix`.
+pub fn sha256_salted(password: &str, salt: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(password.as_bytes());
+    hasher.update(salt.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Whether a stored password is a one-way hash rather than an AES-GCM payload.
+///
+/// Encrypted payloads are base64 and never contain `$`, while imported Mosquitto hashes
+/// always start with it.
+pub fn is_password_hash(stored: &str) -> bool {
+    stored.starts_with('$')
+}
+
+/// Formats a Mosquitto PBKDF2-SHA512 hash (`$7$<iterations>$<salt>$<hash>`, base64 parts).
+pub fn mosquitto_pbkdf2_hash(iterations: u32, salt_b64: &str, hash_b64: &str) -> String {
+    format!("$7${}${}${}", iterations, salt_b64, hash_b64)
+}
+
+enum MosquittoHash {
+    /// `$6$<salt>$<hash>`: sha512(password || salt), Mosquitto 1.x
+    Sha512 { salt: Vec<u8>, hash: Vec<u8> },
+    /// `$7$<iterations>$<salt>$<hash>`: PBKDF2-HMAC-SHA512, Mosquitto 2.x and dynsec
+    Pbkdf2 {
+        iterations: u32,
+        salt: Vec<u8>,
+        hash: Vec<u8>,
+    },
+}
+
+fn parse_hash(stored: &str) -> Result<MosquittoHash, String> {
+    let parts: Vec<&str> = stored.split('$').collect();
+    match parts.as_slice() {
+        ["", "6", salt, hash] => {
+            let hash = decode(hash)?;
+            if hash.len() != SHA512_HASH_BYTES {
+                return Err(format!(
+                    "SHA-512 password hash must be {} bytes",
+                    SHA512_HASH_BYTES
+                ));
+            }
+            Ok(MosquittoHash::Sha512 {
+                salt: decode(salt)?,
+                hash,
+            })
+        }
+        ["", "7", iterations, salt, hash] => {
+            let iterations = iterations
+                .parse()
+                .ok()
+                .filter(|i| (1..=MAX_PBKDF2_ITERATIONS).contains(i))
+                .ok_or_else(|| {
+                    format!(
+                        "PBKDF2 iteration count must be between 1 and {}",
+                        MAX_PBKDF2_ITERATIONS
+                    )
+                })?;
+            let hash = decode(hash)?;
+            if hash.len() < MIN_PBKDF2_HASH_BYTES {
+                return Err(format!(
+                    "PBKDF2 password hash must be at least {} bytes",
+                    MIN_PBKDF2_HASH_BYTES
+                ));
+            }
+            Ok(MosquittoHash::Pbkdf2 {
+                iterations,
+                salt: decode(salt)?,
+                hash,
+            })
+        }
+        _ => Err("unsupported password hash format".to_string()),
+    }
+}
+
+/// Checks that a stored hash is a supported Mosquitto format without hashing anything.
+pub fn validate_password_hash(stored: &str) -> Result<(), String> {
+    parse_hash(stored).map(|_| ())
+}
+
+/// Checks `candidate` against a stored password, decrypting or hashing as needed.
+pub fn verify_password(stored: &str, candidate: &str) -> Result<bool, String> {
+    if !is_password_hash(stored) {
+        return Ok(decrypt_password(stored)? == candidate);
+    }
+
+    match parse_hash(stored)? {
+        MosquittoHash::Sha512 { salt, hash } => {
+            let mut hasher = Sha512::new();
+            hasher.update(candidate.as_bytes());
+            hasher.update(&salt);
+            Ok(bool::from(hasher.finalize().as_slice().ct_eq(&hash)))
+        }
+        MosquittoHash::Pbkdf2 {
+            iterations,
+            salt,
+            hash,
+        } => {
+            let mut derived = vec![0u8; hash.len()];
+            pbkdf2::pbkdf2_hmac::<Sha512>(candidate.as_bytes(), &salt, iterations, &mut derived);
+            Ok(bool::from(derived.ct_eq(&hash)))
+        }
+    }
+}
+
+fn decode(part: &str) -> Result<Vec<u8>, String> {
+    STANDARD
+        .decode(part)
+        .map_err(|e| format!("invalid base64 in password hash: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha512_entry(password: &str, salt: &[u8]) -> String {
+        let mut hasher = Sha512::new();
+        hasher.update(password.as_bytes());
+        hasher.update(salt);
+        format!(
+            "$6${}${}",
+            STANDARD.encode(salt),
+            STANDARD.encode(hasher.finalize())
+        )
+    }
+
+    fn pbkdf2_entry(password: &str, salt: &[u8], iterations: u32) -> String {
+        let mut hash = [0u8; 64];
+        pbkdf2::pbkdf2_hmac::<Sha512>(password.as_bytes(), salt, iterations, &mut hash);
+        mosquitto_pbkdf2_hash(iterations, &STANDARD.encode(salt), &STANDARD.encode(hash))
+    }
+
+    #[test]
+    fn verifies_sha512_hash() {
+        let stored = sha512_entry("secret", b"salt");
+        assert_eq!(verify_password(&stored, "secret"), Ok(true));
+        assert_eq!(verify_password(&stored, "wrong"), Ok(false));
+    }
+
+    #[test]
+    fn verifies_pbkdf2_hash() {
+        let stored = pbkdf2_entry("secret", b"salt", 101);
+        assert_eq!(verify_password(&stored, "secret"), Ok(true));
+        assert_eq!(verify_password(&stored, "wrong"), Ok(false));
+    }
+
+    #[test]
+    fn rejects_empty_hashes() {
+        assert!(validate_password_hash("$7$100$c2FsdA==$").is_err());
+        assert!(validate_password_hash("$6$c2FsdA==$").is_err());
+        assert!(verify_password("$7$100$c2FsdA==$", "anything").is_err());
+    }
+
+    #[test]
+    fn rejects_short_hashes() {
+        assert!(validate_password_hash("$6$c2FsdA==$YWJj").is_err());
+        assert!(validate_password_hash("$7$100$c2FsdA==$YWJj").is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_iterations() {
+        let hash = STANDARD.encode([0u8; 64]);
+        assert!(validate_password_hash(&format!("$7$0$c2FsdA==${}", hash)).is_err());
+        assert!(validate_password_hash(&format!("$7$4294967295$c2FsdA==${}", hash)).is_err());
+        assert!(validate_password_hash(&format!("$7$1000000$c2FsdA==${}", hash)).is_ok());
+    }
+
+    #[test]
+    fn rejects_unknown_formats() {
+        assert!(validate_password_hash("$2b$10$abc").is_err());
+        assert!(validate_password_hash("plain").is_err());
+    }
+}
@@ -0,0 +1,103 @@
+/// Whether an MQTT topic filter (`+` and `#` wildcards) matches `topic`.
+///
+/// `topic` may itself be a subscription filter; its wildcards only match a rule that covers
+/// them, so `a/+` matches `a/+` and `a/#` but not `a/b`. As in MQTT, a leading wildcard does
+/// not match topics starting with `$`, such as `$SYS/...`.
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    if topic.starts_with('$') && (filter.starts_with('+') || filter.starts_with('#')) {
+        return false;
+    }
+    let mut filter_levels = filter.split('/');
+    let mut topic_levels = topic.split('/');
+    loop {
+        match (filter_levels.next(), topic_levels.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(level)) if level != "#" => {}
+            (Some(expected), Some(level)) if expected == level => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Whether a topic or subscription filter can reach a topic under `prefix` (which ends in `/`).
+pub fn reaches_prefix(topic: &str, prefix: &str) -> bool {
+    let mut topic_levels = topic.split('/');
+    for expected in prefix.trim_end_matches('/').split('/') {
+        match topic_levels.next() {
+            Some("#") => return true,
+            Some("+") => {}
+            Some(level) if level == expected => {}
+            _ => return false,
+        }
+    }
+    topic_levels.next().is_some()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_exact_topics() {
+        assert!(topic_matches("a/b/c", "a/b/c"));
+        assert!(!topic_matches("a/b/c", "a/b"));
+        assert!(!topic_matches("a/b", "a/b/c"));
+        assert!(!topic_matches("a/b", "a/bc"));
+        assert!(topic_matches("a//b", "a//b"));
+    }
+
+    #[test]
+    fn matches_single_level_wildcards() {
+        assert!(topic_matches("a/+/c", "a/b/c"));
+        assert!(topic_matches("a/+", "a/"));
+        assert!(topic_matches("+/+", "a/b"));
+        assert!(!topic_matches("a/+", "a/b/c"));
+        assert!(!topic_matches("a/+", "a"));
+        assert!(topic_matches("a/+", "a/+"));
+        assert!(!topic_matches("a/+", "a/#"));
+    }
+
+    #[test]
+    fn matches_multi_level_wildcards() {
+        assert!(topic_matches("#", "a/b/c"));
+        assert!(topic_matches("a/#", "a/b/c"));
+        assert!(topic_matches("a/#", "a/b"));
+        // A trailing `#` also matches its parent level.
+        assert!(topic_matches("a/#", "a"));
+        assert!(topic_matches("a/#", "a/#"));
+        assert!(topic_matches("a/#", "a/+"));
+        assert!(!topic_matches("a/b", "a/#"));
+        assert!(!topic_matches("a/+", "a/#"));
+        assert!(!topic_matches("b/#", "a/b"));
+    }
+
+    #[test]
+    fn keeps_dollar_topics_out_of_leading_wildcards() {
+        assert!(!topic_matches("#", "$SYS/brokers"));
+        assert!(!topic_matches("+/brokers", "$SYS/brokers"));
+        assert!(topic_matches("$SYS/#", "$SYS/brokers"));
+        assert!(topic_matches("$SYS/+", "$SYS/brokers"));
+        assert!(topic_matches("a/#", "a/$b"));
+    }
+
+    #[test]
+    fn reaches_topics_under_the_prefix() {
+        assert!(reaches_prefix("tenant-a/sensors/1", "tenant-a/"));
+        assert!(reaches_prefix("tenant-a/#", "tenant-a/"));
+        assert!(reaches_prefix("+/sensors", "tenant-a/"));
+        assert!(reaches_prefix("#", "tenant-a/"));
+        assert!(reaches_prefix("org/+/x", "org/tenant-a/"));
+        assert!(reaches_prefix("org/#", "org/tenant-a/"));
+    }
+
+    #[test]
+    fn stops_at_prefix_boundaries() {
+        assert!(!reaches_prefix("tenant-ab/sensors", "tenant-a/"));
+        assert!(!reaches_prefix("tenant-a", "tenant-a/"));
+        assert!(!reaches_prefix("tenant", "tenant-a/"));
+        assert!(!reaches_prefix("tenant-b/#", "tenant-a/"));
+        assert!(!reaches_prefix("org/+", "org/tenant-a/"));
+        assert!(!reaches_prefix("$SYS/#", "tenant-a/"));
+    }
+}